@@ -0,0 +1,50 @@
+//! Compares [`read_test_cases_streaming`]'s `io::Read`-based parsing
+//! against [`read_test_cases_from_slice`]'s in-memory parsing, to see
+//! what (if anything) skipping `Read`'s internal buffering buys on a
+//! capture file big enough for parsing cost to matter. See
+//! [`read_test_cases_from_slice`]'s doc comment for why this isn't
+//! expected to be a "zero-copy strings" win: `TestCase` has no owned
+//! string fields to begin with.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use power_instruction_analyzer::capture::{read_test_cases_from_slice, read_test_cases_streaming, TestCase};
+use power_instruction_analyzer::{Instr, InstructionInput, InstructionOutput};
+
+fn sample_jsonl(case_count: usize) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for i in 0..case_count {
+        let case = TestCase {
+            instr: Instr::Add,
+            input: InstructionInput { ra: i as u64, rb: (i as u64).wrapping_mul(3), ..InstructionInput::default() },
+            native_output: InstructionOutput { rt: Some(i as u64), ..InstructionOutput::default() },
+            model_output: InstructionOutput { rt: Some(i as u64), ..InstructionOutput::default() },
+            model_revision: 1,
+            skip: None,
+            latency: None,
+        };
+        serde_json::to_writer(&mut buf, &case).unwrap();
+        buf.push(b'\n');
+    }
+    buf
+}
+
+fn bench_deserialize(c: &mut Criterion) {
+    let data = sample_jsonl(50_000);
+
+    c.bench_function("read_test_cases_streaming (io::Read)", |b| {
+        b.iter(|| {
+            let count = read_test_cases_streaming(data.as_slice()).map(Result::unwrap).count();
+            assert_eq!(count, 50_000);
+        });
+    });
+
+    c.bench_function("read_test_cases_from_slice (in-memory)", |b| {
+        b.iter(|| {
+            let count = read_test_cases_from_slice(&data).map(Result::unwrap).count();
+            assert_eq!(count, 50_000);
+        });
+    });
+}
+
+criterion_group!(benches, bench_deserialize);
+criterion_main!(benches);
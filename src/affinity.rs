@@ -0,0 +1,99 @@
+//! Optional CPU-affinity and scheduling-priority pinning applied before
+//! native execution, so latency/timing numbers collected on a shared
+//! POWER server aren't skewed by migration between cores or contention
+//! from other processes.
+//!
+//! Linux-only, and only meaningful paired with the `powerpc64` native
+//! backend -- see [`crate::native`].
+
+use crate::native::Error;
+use serde::{Deserialize, Serialize};
+
+/// The pinning to apply before a batch of native runs. `None` in either
+/// field leaves that setting alone.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Pinning {
+    /// Pin the current process to this CPU, via `sched_setaffinity`.
+    pub cpu: Option<usize>,
+    /// Set the current process's nice value, via `setpriority`.
+    pub nice: Option<i32>,
+}
+
+impl Pinning {
+    /// `true` if neither field requests a change.
+    pub fn is_noop(&self) -> bool {
+        self.cpu.is_none() && self.nice.is_none()
+    }
+
+    /// Applies `self` to the current process. Always `Ok` if [`is_noop`]
+    /// would be `true`, even off Linux/powerpc64.
+    ///
+    /// [`is_noop`]: Self::is_noop
+    pub fn apply(&self) -> Result<(), Error> {
+        if self.is_noop() {
+            return Ok(());
+        }
+        #[cfg(all(target_os = "linux", target_arch = "powerpc64"))]
+        {
+            linux::apply(self)
+        }
+        #[cfg(not(all(target_os = "linux", target_arch = "powerpc64")))]
+        {
+            Err(Error::UnsupportedPlatform)
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "powerpc64"))]
+mod linux {
+    use super::*;
+    use std::io;
+    use std::mem;
+
+    pub fn apply(pinning: &Pinning) -> Result<(), Error> {
+        if let Some(cpu) = pinning.cpu {
+            // SAFETY: `set` is a validly-initialized, correctly-sized
+            // `cpu_set_t`; `pid=0` means the calling process.
+            unsafe {
+                let mut set: libc::cpu_set_t = mem::zeroed();
+                libc::CPU_SET(cpu, &mut set);
+                if libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                    return Err(Error::Affinity(io::Error::last_os_error()));
+                }
+            }
+        }
+        if let Some(nice) = pinning.nice {
+            // SAFETY: `setpriority` takes no pointers; `PRIO_PROCESS`/`0`
+            // targets the calling process.
+            unsafe {
+                // `setpriority` returns -1 on both error and (legitimately)
+                // a successfully-set negative priority, so check `errno`.
+                *libc::__errno_location() = 0;
+                if libc::setpriority(libc::PRIO_PROCESS, 0, nice) == -1 {
+                    let err = io::Error::last_os_error();
+                    if err.raw_os_error() != Some(0) {
+                        return Err(Error::Affinity(err));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_pinning_is_a_noop_everywhere() {
+        assert!(Pinning::default().is_noop());
+        assert!(Pinning::default().apply().is_ok());
+    }
+
+    #[test]
+    fn a_chosen_cpu_or_priority_is_not_a_noop() {
+        assert!(!Pinning { cpu: Some(0), nice: None }.is_noop());
+        assert!(!Pinning { cpu: None, nice: Some(10) }.is_noop());
+    }
+}
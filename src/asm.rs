@@ -0,0 +1,113 @@
+//! A minimal textual assembler for the instructions in [`Instr::ALL`],
+//! pairing with [`crate::encoder`]/[`crate::decoder`] so test input and
+//! captures can be written as assembly instead of raw instruction words.
+
+use crate::encoder::encode;
+use crate::instr::Instr;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum AsmError {
+    UnrecognizedInstruction(String),
+    MalformedOperands(String),
+    InvalidRegister(String),
+    ModelOnlyInstruction(Instr),
+    /// Fewer than `rt,ra,rb` were given, naming the first one that's
+    /// missing rather than just complaining about the operand list as a
+    /// whole, so callers that want to react to a specific missing operand
+    /// (rather than just print the error) don't have to parse the message.
+    MissingOperand { instr: Instr, operand: &'static str },
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AsmError::UnrecognizedInstruction(s) => write!(f, "unrecognized instruction: {:?}", s),
+            AsmError::MalformedOperands(s) => write!(f, "malformed operand list: {:?}", s),
+            AsmError::InvalidRegister(s) => write!(f, "invalid register: {:?}", s),
+            AsmError::ModelOnlyInstruction(instr) => write!(f, "{} has no native encoding (model-only)", instr),
+            AsmError::MissingOperand { instr, operand } => write!(f, "{} is missing its {} operand", instr, operand),
+        }
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// Names of `assemble`'s three positional operands, in order.
+const OPERAND_NAMES: &[&str] = &["rt", "ra", "rb"];
+
+/// Assembles one line of text, e.g. `"add r3,r4,r5"`, into its instruction
+/// word.
+pub fn assemble(text: &str) -> Result<u32, AsmError> {
+    let text = text.trim();
+    let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let instr: Instr = mnemonic
+        .parse()
+        .map_err(|_| AsmError::UnrecognizedInstruction(mnemonic.to_string()))?;
+    let regs = operands
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(parse_register)
+        .collect::<Result<Vec<u32>, AsmError>>()?;
+    if let Some(&operand) = OPERAND_NAMES.get(regs.len()) {
+        return Err(AsmError::MissingOperand { instr, operand });
+    }
+    match regs[..] {
+        [rt, ra, rb] => encode(instr, rt, ra, rb).ok_or(AsmError::ModelOnlyInstruction(instr)),
+        _ => Err(AsmError::MalformedOperands(text.to_string())),
+    }
+}
+
+fn parse_register(s: &str) -> Result<u32, AsmError> {
+    let digits = s
+        .strip_prefix('r')
+        .or_else(|| s.strip_prefix('R'))
+        .ok_or_else(|| AsmError::InvalidRegister(s.to_string()))?;
+    digits.parse().map_err(|_| AsmError::InvalidRegister(s.to_string()))
+}
+
+/// Disassembles to canonical assembly text, e.g. `"subf r3,r4,r5"`.
+pub fn disassemble(instr: Instr, rt: u32, ra: u32, rb: u32) -> String {
+    format_with_mnemonic(instr.name(), rt, ra, rb)
+}
+
+/// Disassembles using the instruction's first extended/alternate mnemonic
+/// (see [`Instr::aliases`]) where one exists, e.g. `"sf r3,r4,r5"` instead
+/// of `"subf r3,r4,r5"`, falling back to the canonical mnemonic otherwise.
+pub fn disassemble_extended(instr: Instr, rt: u32, ra: u32, rb: u32) -> String {
+    let mnemonic = instr.aliases().first().copied().unwrap_or_else(|| instr.name());
+    format_with_mnemonic(mnemonic, rt, ra, rb)
+}
+
+fn format_with_mnemonic(mnemonic: &str, rt: u32, ra: u32, rb: u32) -> String {
+    format!("{} r{},r{},r{}", mnemonic, rt, ra, rb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assembles_known_encoding() {
+        assert_eq!(assemble("add r3,r4,r5").unwrap(), 0x7c64_2a14);
+    }
+
+    #[test]
+    fn rejects_wrong_operand_count() {
+        assert!(assemble("add r3,r4").is_err());
+    }
+
+    #[test]
+    fn names_the_missing_operand() {
+        match assemble("add r3,r4") {
+            Err(AsmError::MissingOperand { instr: Instr::Add, operand: "rb" }) => {}
+            other => panic!("expected a named missing operand, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_unrecognized_instruction() {
+        assert!(assemble("frob r3,r4,r5").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+//! Cross-checks the hand-maintained `reads`/`writes` lists in
+//! [`crate::metadata`] against what each instruction's [`crate::model`]
+//! implementation actually does, so declaration drift shows up as a
+//! `cargo test` failure instead of waiting for a human to notice a stale
+//! doc comment.
+//!
+//! There's no literal "was this input read" signal to observe here --
+//! [`InstructionInput`] is a fixed struct, not a set of optional fields a
+//! model can fail to provide, so there's no `MissingInstructionInput`
+//! error to instrument. The two signals actually available are the same
+//! probe [`crate::taint`] already uses for `reads` (does perturbing an
+//! input field change the output) and a new one this module adds for
+//! `writes`: across every corner case (see [`crate::corner_cases`]), does
+//! the model ever actually set the output field a declared flag implies,
+//! and does it ever set a field no declared flag implies.
+
+use crate::corner_cases;
+use crate::instr::Instr;
+use crate::metadata::{self, Flag};
+use crate::model;
+use crate::taint::{self, InputField};
+use crate::types::InstructionInput;
+use std::fmt;
+
+/// One way `instr`'s declared metadata and its model implementation were
+/// found to disagree.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Discrepancy {
+    /// The model is sensitive to an incoming flag the metadata doesn't
+    /// list under `reads`.
+    ReadsUndeclaredFlag(Flag),
+    /// The metadata lists `flag` under `writes`, but across every probed
+    /// input, the model never actually set the output field `flag`
+    /// corresponds to.
+    NeverWritesDeclaredFlag(Flag),
+    /// The model set an output field across some probed input, but the
+    /// metadata doesn't list the corresponding flag under `writes`.
+    WritesUndeclaredFlag(Flag),
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::ReadsUndeclaredFlag(flag) => write!(f, "model reads {:?} but metadata doesn't declare it under `reads`", flag),
+            Discrepancy::NeverWritesDeclaredFlag(flag) => {
+                write!(f, "metadata declares {:?} under `writes`, but the model never sets it across any probed input", flag)
+            }
+            Discrepancy::WritesUndeclaredFlag(flag) => write!(f, "model sets {:?} but metadata doesn't declare it under `writes`", flag),
+        }
+    }
+}
+
+/// Every probed input for `instr`: the all-zero default plus every curated
+/// corner case, since a single probe (as [`taint::sensitivity`] itself
+/// notes) can miss a dependency or a write that only shows up for
+/// particular operand values.
+fn probe_inputs(instr: Instr) -> Vec<InstructionInput> {
+    let mut inputs = vec![InstructionInput::default()];
+    inputs.extend(corner_cases::corner_case_inputs(instr).into_iter().map(|(_, input)| input));
+    inputs
+}
+
+/// Finds every [`Discrepancy`] between `instr`'s declared metadata and its
+/// model, probing across [`probe_inputs`].
+pub fn audit(instr: Instr) -> Vec<Discrepancy> {
+    let declared = metadata::metadata(instr);
+    let mut discrepancies = Vec::new();
+
+    for field in [InputField::Cr0, InputField::Fpscr] {
+        let flag = match field {
+            InputField::Cr0 => Flag::Cr0,
+            InputField::Fpscr => Flag::Fpscr,
+            _ => unreachable!("only Cr0 and Fpscr are probed here"),
+        };
+        let reads_flag = probe_inputs(instr)
+            .into_iter()
+            .any(|baseline| taint::sensitivity(instr, baseline).depends_on(field));
+        if reads_flag && !declared.reads.contains(&flag) {
+            discrepancies.push(Discrepancy::ReadsUndeclaredFlag(flag));
+        }
+    }
+
+    let mut cr0_ever_set = false;
+    let mut xer_ever_set = false;
+    let mut fpscr_ever_set = false;
+    for input in probe_inputs(instr) {
+        let output = model::model(instr, input);
+        cr0_ever_set |= output.cr0.is_some();
+        xer_ever_set |= output.xer.is_some();
+        fpscr_ever_set |= output.fpscr.is_some();
+    }
+
+    let declares_xer = [Flag::So, Flag::Ov, Flag::Ca].into_iter().filter(|flag| declared.writes.contains(flag));
+    for flag in declares_xer {
+        if !xer_ever_set {
+            discrepancies.push(Discrepancy::NeverWritesDeclaredFlag(flag));
+        }
+    }
+    if declared.writes.contains(&Flag::Cr0) && !cr0_ever_set {
+        discrepancies.push(Discrepancy::NeverWritesDeclaredFlag(Flag::Cr0));
+    }
+    if declared.writes.contains(&Flag::Fpscr) && !fpscr_ever_set {
+        discrepancies.push(Discrepancy::NeverWritesDeclaredFlag(Flag::Fpscr));
+    }
+
+    if cr0_ever_set && !declared.writes.contains(&Flag::Cr0) {
+        discrepancies.push(Discrepancy::WritesUndeclaredFlag(Flag::Cr0));
+    }
+    if xer_ever_set && ![Flag::So, Flag::Ov, Flag::Ca].into_iter().any(|flag| declared.writes.contains(&flag)) {
+        discrepancies.push(Discrepancy::WritesUndeclaredFlag(Flag::So));
+    }
+    if fpscr_ever_set && !declared.writes.contains(&Flag::Fpscr) {
+        discrepancies.push(Discrepancy::WritesUndeclaredFlag(Flag::Fpscr));
+    }
+
+    discrepancies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_and_the_model_agree_for_every_currently_modeled_instruction() {
+        for instr in Instr::ALL.iter().copied() {
+            let discrepancies = audit(instr);
+            assert!(discrepancies.is_empty(), "{instr}: metadata and model disagree: {:?}", discrepancies);
+        }
+    }
+
+    #[test]
+    fn add_dot_declares_and_actually_writes_cr0() {
+        assert!(audit(Instr::AddDot).is_empty());
+    }
+
+    #[test]
+    fn addo_declares_and_actually_writes_so_and_ov() {
+        assert!(audit(Instr::AddO).is_empty());
+    }
+}
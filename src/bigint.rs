@@ -0,0 +1,99 @@
+//! 128-bit GPR-pair arithmetic built by composing the existing scalar
+//! instruction models, the same instruction sequences SVP64 bigint code
+//! emits: `addc` on the low limb produces a carry that `adde` consumes on
+//! the high limb, rather than this module computing 128-bit results
+//! directly.
+
+use crate::instr::Instr;
+use crate::model;
+use crate::types::InstructionInput;
+
+/// A 128-bit value split across two GPRs, low limb first, matching how
+/// multi-precision code lays out a pair of registers.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GprPair {
+    pub lo: u64,
+    pub hi: u64,
+}
+
+impl GprPair {
+    pub fn to_u128(self) -> u128 {
+        ((self.hi as u128) << 64) | self.lo as u128
+    }
+
+    pub fn from_u128(value: u128) -> Self {
+        Self { lo: value as u64, hi: (value >> 64) as u64 }
+    }
+}
+
+/// Adds two 128-bit values held as GPR pairs, modulo 2^128, via `addc` on
+/// the low limbs (producing the carry) followed by `adde` on the high
+/// limbs (consuming it).
+pub fn add128(a: GprPair, b: GprPair) -> GprPair {
+    let lo = model::model(Instr::AddC, InstructionInput { ra: a.lo, rb: b.lo, ..InstructionInput::default() });
+    let hi_input = InstructionInput {
+        ra: a.hi,
+        rb: b.hi,
+        xer: lo.xer.expect("addc always records CA"),
+        ..InstructionInput::default()
+    };
+    let hi = model::model(Instr::AddE, hi_input);
+    GprPair { lo: lo.rt.expect("addc always produces rt"), hi: hi.rt.expect("adde always produces rt") }
+}
+
+/// The two's complement negation of a 128-bit value (`~a + 1`), modulo
+/// 2^128, built on [`add128`] so [`sub128`] doesn't need a carrying
+/// subtract instruction of its own.
+fn negate128(a: GprPair) -> GprPair {
+    add128(GprPair { lo: !a.lo, hi: !a.hi }, GprPair { lo: 1, hi: 0 })
+}
+
+/// Subtracts `b` from `a`, modulo 2^128, as `a + (-b)`.
+pub fn sub128(a: GprPair, b: GprPair) -> GprPair {
+    add128(a, negate128(b))
+}
+
+/// Multiplies the 128-bit `a` by the 64-bit `b`, keeping only the low 128
+/// bits of the product (matching how a fixed-width bigint limb multiply
+/// truncates), via the schoolbook decomposition `mulld`/`mulhdu` already
+/// model for a single 64x64 multiply, plus [`add128`] to combine the
+/// cross term.
+pub fn mul128_scalar(a: GprPair, b: u64) -> GprPair {
+    let mulld = |ra: u64| model::model(Instr::Mulld, InstructionInput { ra, rb: b, ..InstructionInput::default() })
+        .rt
+        .expect("mulld always produces rt");
+    let mulhdu = |ra: u64| model::model(Instr::Mulhdu, InstructionInput { ra, rb: b, ..InstructionInput::default() })
+        .rt
+        .expect("mulhdu always produces rt");
+
+    let lo = mulld(a.lo);
+    let cross = add128(GprPair { lo: mulhdu(a.lo), hi: 0 }, GprPair { lo: mulld(a.hi), hi: 0 });
+    GprPair { lo, hi: cross.lo }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add128_matches_u128_arithmetic_and_carries_across_the_limb_boundary() {
+        let a = GprPair::from_u128(u128::MAX - 1);
+        let b = GprPair { lo: 5, hi: 0 };
+        assert_eq!(add128(a, b).to_u128(), (u128::MAX - 1).wrapping_add(5));
+    }
+
+    #[test]
+    fn sub128_matches_u128_arithmetic_including_borrow_across_the_limb_boundary() {
+        let a = GprPair::from_u128(1u128 << 64);
+        let b = GprPair { lo: 1, hi: 0 };
+        assert_eq!(sub128(a, b).to_u128(), (1u128 << 64).wrapping_sub(1));
+    }
+
+    #[test]
+    fn mul128_scalar_matches_u128_arithmetic_modulo_2_128() {
+        let a = GprPair::from_u128(0x0001_0000_0000_0000_0000_0000_0000_0003);
+        let b = 0x0000_0000_0000_0005u64;
+        let expected = a.to_u128().wrapping_mul(b as u128);
+        assert_eq!(mul128_scalar(a, b).to_u128(), expected);
+    }
+}
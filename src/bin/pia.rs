@@ -0,0 +1,1176 @@
+//! `pia`: the power-instruction-analyzer command-line tool.
+
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use power_instruction_analyzer::affinity;
+use power_instruction_analyzer::audit;
+use power_instruction_analyzer::cache::Cache;
+use power_instruction_analyzer::campaign;
+use power_instruction_analyzer::capture::WholeTest;
+use power_instruction_analyzer::capture_index;
+use power_instruction_analyzer::cdiff;
+use power_instruction_analyzer::check;
+use power_instruction_analyzer::cluster;
+use power_instruction_analyzer::cocotb_vectors;
+use power_instruction_analyzer::corner_cases;
+use power_instruction_analyzer::decoder::{self, Strictness};
+use power_instruction_analyzer::div_report;
+use power_instruction_analyzer::docgen;
+use power_instruction_analyzer::encoder;
+use power_instruction_analyzer::fill::{self, InputOnlyCase};
+use power_instruction_analyzer::filter::FilterExpr;
+use power_instruction_analyzer::junit;
+use power_instruction_analyzer::metadata;
+use power_instruction_analyzer::metrics;
+use power_instruction_analyzer::model::{self, Variant};
+use power_instruction_analyzer::native::RegisterAssignment;
+use power_instruction_analyzer::neighborhood;
+use power_instruction_analyzer::parquet_export;
+use power_instruction_analyzer::registry;
+use power_instruction_analyzer::remote;
+use power_instruction_analyzer::rerun;
+use power_instruction_analyzer::sequence::{self, Sequence};
+use power_instruction_analyzer::sqlite_export;
+use power_instruction_analyzer::sva;
+use power_instruction_analyzer::timebox;
+use power_instruction_analyzer::vcd;
+use power_instruction_analyzer::Instr;
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default on-disk cache location, relative to the current directory.
+const DEFAULT_CACHE_PATH: &str = ".pia-cache.json";
+
+#[derive(Parser)]
+#[command(name = "pia", about = "Analyzer for POWER instructions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a native-execution endpoint that remote clients can shard work to.
+    Serve {
+        /// Address to listen on, e.g. `0.0.0.0:7643`.
+        #[arg(long, default_value = "0.0.0.0:7643")]
+        addr: String,
+    },
+    /// Run an exhaustive campaign sharded across one or more `pia serve` hosts.
+    Farm {
+        /// Addresses of `pia serve` endpoints to shard work across.
+        #[arg(long = "host", required = true)]
+        hosts: Vec<String>,
+        /// Don't read or write the on-disk result cache.
+        #[arg(long)]
+        no_cache: bool,
+        /// Ignore any cached results, but still write fresh ones to the cache.
+        #[arg(long)]
+        refresh: bool,
+        /// Write one JSONL file per instruction (plus an index manifest)
+        /// into this directory instead of printing one combined JSON blob.
+        #[arg(long)]
+        split_output: Option<PathBuf>,
+        /// Run only the curated corner cases from
+        /// `power_instruction_analyzer::corner_cases`, not the full
+        /// exhaustive campaign -- seconds instead of hours, for a quick
+        /// hardware sanity check.
+        #[arg(long)]
+        corner_cases_only: bool,
+        /// Limit this run to roughly fit within a wall-clock budget (e.g.
+        /// `30s`, `10m`, `2h`), prioritizing corner cases first and
+        /// reporting achieved coverage against the full campaign. Takes
+        /// precedence over `--corner-cases-only` if both are given.
+        #[arg(long, value_parser = parse_time_budget)]
+        time_budget: Option<Duration>,
+        /// After this run, generate a neighborhood of nearby inputs (bit
+        /// flips, +-1, sign flips; see `power_instruction_analyzer::neighborhood`)
+        /// around every mismatching case, run those through the same
+        /// hosts, and fold the results in -- mapping out the extent of a
+        /// divergence automatically instead of leaving it at one data point.
+        #[arg(long)]
+        expand_mismatches: bool,
+        /// Serve live progress/mismatch counts in Prometheus exposition
+        /// format on this address's `/metrics` (e.g.
+        /// `0.0.0.0:9898`), for Grafana/Prometheus to scrape during a
+        /// long-running campaign.
+        #[arg(long)]
+        metrics_addr: Option<String>,
+        /// Keep at most this many mismatching cases per instruction in the
+        /// output, dropping the rest, so a badly broken model doesn't flood
+        /// the result with millions of near-identical failures. Unset
+        /// keeps every mismatch, as before.
+        #[arg(long)]
+        max_mismatches_per_instr: Option<usize>,
+    },
+    /// Print a machine-readable table of every supported instruction.
+    DumpIsa {
+        #[arg(long, value_enum, default_value_t = IsaFormat::Json)]
+        format: IsaFormat,
+    },
+    /// Generate per-instruction Markdown reference pages into a docs/ tree.
+    GenDocs {
+        /// Directory to write the generated pages into.
+        #[arg(long, default_value = "docs")]
+        out_dir: PathBuf,
+    },
+    /// Read a JSON file of `{instr, input}` cases with no outputs yet, and
+    /// print a full WholeTest with model (and optionally native) outputs
+    /// filled in.
+    Fill {
+        /// Path to a JSON array of `{instr, input}` cases.
+        input_file: PathBuf,
+        /// Also run native execution, not just the model.
+        #[arg(long)]
+        native: bool,
+        /// Use an alternate model variant for one instruction, as
+        /// `mnemonic=variant` (e.g. `divdu=isa_strict`). May be repeated.
+        #[arg(long = "model-variant", value_parser = parse_model_variant)]
+        model_variant: Vec<(Instr, Variant)>,
+        /// Only keep cases whose input/model output satisfy this
+        /// expression, e.g. `'ov32 != ov && ra < 0'` (see
+        /// `power_instruction_analyzer::filter`), so a targeted corpus can
+        /// be built directly instead of post-processing the full output.
+        #[arg(long)]
+        filter: Option<FilterExpr>,
+        /// Re-execute each case's native execution this many times and
+        /// flag any that disagree with themselves. Implies `--native`.
+        #[arg(long, default_value_t = 1)]
+        repeat: usize,
+        /// Pin the current process to this CPU before running (see
+        /// `power_instruction_analyzer::affinity`), recorded in the output.
+        #[arg(long)]
+        cpu: Option<usize>,
+        /// Set the current process's nice value before running, recorded
+        /// in the output.
+        #[arg(long)]
+        nice: Option<i32>,
+        /// Number of worker threads to split the batch across. Defaults to
+        /// the host's available parallelism; pass `1` to run sequentially
+        /// (e.g. for a reproducible single-threaded timing baseline). `0`
+        /// is rejected rather than treated as "unset".
+        #[arg(long, value_parser = parse_num_threads)]
+        threads: Option<usize>,
+    },
+    /// Read newline-delimited JSON `{instr, input}` cases and stream model
+    /// (and, optionally, native) outputs back as newline-delimited
+    /// `TestCase`s, one line out per line in -- for composing with an
+    /// external generator of any language via a pipe, e.g. `generator |
+    /// pia exec -`. Unlike `fill`, which reads one JSON array and writes
+    /// one `WholeTest`, this never holds the whole input or output in
+    /// memory at once.
+    Exec {
+        /// Path to a newline-delimited JSON file of `{instr, input}`
+        /// cases, or `-` to read from standard input.
+        input_file: PathBuf,
+        /// Also run native execution, not just the model.
+        #[arg(long)]
+        native: bool,
+        /// Use an alternate model variant for one instruction, as
+        /// `mnemonic=variant` (e.g. `divdu=isa_strict`). May be repeated.
+        #[arg(long = "model-variant", value_parser = parse_model_variant)]
+        model_variant: Vec<(Instr, Variant)>,
+    },
+    /// Re-run the current model (and native execution, where recorded)
+    /// over a golden capture and fail with a field-level report on any
+    /// disagreement.
+    Check {
+        /// Path to a golden `WholeTest` JSON capture.
+        golden_file: PathBuf,
+        /// Ignore `xer.ca32`/`xer.ov32`, for captures from hardware or
+        /// simulators that predate ISA 3.0 and never recorded them.
+        #[arg(long)]
+        legacy32: bool,
+        /// Compare NaN payloads and the sign of a zero result loosely, for
+        /// captures from cores/simulators that legitimately differ there.
+        #[arg(long)]
+        fp_loose: bool,
+    },
+    /// Decode a single 32-bit instruction word and print the instruction
+    /// and operands it contains.
+    Decode {
+        /// Instruction word, e.g. `0x7c642a14`.
+        #[arg(value_parser = parse_word)]
+        word: u32,
+        /// How to handle reserved bits the decoder doesn't expect set.
+        #[arg(long, value_enum, default_value_t = StrictnessArg::Strict)]
+        strictness: StrictnessArg,
+        /// Print using extended/alternate mnemonics where one exists.
+        #[arg(long)]
+        extended: bool,
+    },
+    /// List instructions registered at runtime via
+    /// `power_instruction_analyzer::registry`, e.g. by out-of-tree/
+    /// experimental instruction proposals.
+    ListCustom,
+    /// Cross-checks every instruction's declared `reads`/`writes` metadata
+    /// against what its model implementation actually does (see
+    /// `power_instruction_analyzer::audit`), exiting nonzero if any
+    /// disagree.
+    Audit,
+    /// Generate an `addc; adde; adde; ...` carry-chain sequence, run it
+    /// through the model (and, optionally, natively), and report whether
+    /// the carry propagated correctly end to end.
+    CarryChain {
+        /// Number of limbs in the chain (1 `addc` plus `length - 1` `adde`s).
+        #[arg(long, default_value_t = 4)]
+        length: usize,
+        /// Also run the sequence natively and compare against the model.
+        #[arg(long)]
+        native: bool,
+    },
+    /// Run the divide family across its curated zero/overflow corner cases
+    /// (see `power_instruction_analyzer::corner_cases`) and print a
+    /// behavior matrix of `rt`/`ov`/`ov32`/`cr0` from the model and,
+    /// optionally, native execution -- since this is the area where
+    /// simulators most often disagree.
+    DivReport {
+        /// Also run each case natively and show its behavior alongside the
+        /// model's.
+        #[arg(long)]
+        native: bool,
+        /// Which core's behavior to assume for the CR0 a Dot-form divide
+        /// would report on these inputs, where the ISA leaves RT (and so
+        /// CR0) implementation-defined. Recorded in the report so a saved
+        /// copy says which assumption it made.
+        #[arg(long, value_enum, default_value_t = CoreProfileArg::DerivedFromRt)]
+        core_profile: CoreProfileArg,
+    },
+    /// Print the literal instruction word (and fixed register assignment)
+    /// the jit-lite backend assembles and executes for a given mnemonic,
+    /// for debugging encoding/register-constraint issues without attaching
+    /// a debugger to a running case.
+    ShowAsm {
+        /// Mnemonic to show, e.g. `addo.`.
+        instr: Instr,
+    },
+    /// Export a WholeTest capture as a VCD waveform, for replay as
+    /// simulation stimulus in GTKWave or an HDL testbench.
+    ExportVcd {
+        /// Path to a WholeTest JSON capture.
+        golden_file: PathBuf,
+    },
+    /// Export a WholeTest capture as per-instruction Python modules of
+    /// cocotb/nmigen test vectors.
+    ExportCocotbVectors {
+        /// Path to a WholeTest JSON capture.
+        golden_file: PathBuf,
+        /// Directory to write one `<mnemonic>.py` module per instruction into.
+        #[arg(long, default_value = "cocotb-vectors")]
+        out_dir: PathBuf,
+    },
+    /// Generate SVA checkers for instructions simple enough to reduce to
+    /// one SystemVerilog expression (see `power_instruction_analyzer::sva`).
+    GenSva {
+        /// File to write the generated assertions into.
+        #[arg(long, default_value = "checkers.sv")]
+        out_file: PathBuf,
+    },
+    /// Differentially test a WholeTest capture's cases against a C
+    /// compiler's own constant folding (see
+    /// `power_instruction_analyzer::cdiff`). Cases whose instruction isn't
+    /// covered are skipped.
+    CheckConstantFolding {
+        /// Path to a WholeTest JSON capture.
+        golden_file: PathBuf,
+        /// C compiler to invoke.
+        #[arg(long, default_value = "cc")]
+        compiler: String,
+    },
+    /// Re-check a golden capture and write the results as JUnit XML, for
+    /// CI pipelines to display mismatches as test failures.
+    JunitReport {
+        /// Path to a golden `WholeTest` JSON capture.
+        golden_file: PathBuf,
+        /// Ignore `xer.ca32`/`xer.ov32`, for captures from hardware or
+        /// simulators that predate ISA 3.0 and never recorded them.
+        #[arg(long)]
+        legacy32: bool,
+        /// Compare NaN payloads and the sign of a zero result loosely, for
+        /// captures from cores/simulators that legitimately differ there.
+        #[arg(long)]
+        fp_loose: bool,
+        /// How finely to split results into `<testcase>` elements.
+        #[arg(long, value_enum, default_value_t = GranularityArg::PerInstr)]
+        granularity: GranularityArg,
+        /// File to write the JUnit XML report into.
+        #[arg(long, default_value = "junit.xml")]
+        out_file: PathBuf,
+    },
+    /// Re-execute a golden capture's mismatching cases on the native
+    /// backend `--repeat` times, to tell apart a deterministic model/native
+    /// divergence from flakiness.
+    RerunFailures {
+        /// Path to a golden `WholeTest` JSON capture.
+        golden_file: PathBuf,
+        /// How many times to re-execute each mismatching case.
+        #[arg(long, default_value_t = 10)]
+        repeat: usize,
+    },
+    /// Groups a golden capture's mismatching cases by which output fields
+    /// disagree and the sign of `ra`/`rb` (see `power_instruction_analyzer::cluster`),
+    /// and prints one representative example per cluster instead of every
+    /// mismatch, so triaging a large divergence set starts from the
+    /// distinct causes instead of a wall of near-duplicate cases.
+    ClusterMismatches {
+        /// Path to a golden `WholeTest` JSON capture.
+        golden_file: PathBuf,
+    },
+    /// Builds a sidecar index over a JSONL capture file (one `TestCase`
+    /// per line, e.g. from `pia farm --split-output` or
+    /// `capture::write_test_cases_streaming`), so `pia query` can look up
+    /// cases without parsing the whole file. Writes alongside
+    /// `capture_file` at `capture_index::default_index_path`.
+    Index {
+        /// Path to a JSONL capture file (NOT a pretty-printed `WholeTest`).
+        capture_file: PathBuf,
+    },
+    /// Looks up cases in a JSONL capture file by `--instr`/`--ra`, using
+    /// the sidecar index from `pia index` if present (building one
+    /// in-memory otherwise, with a full scan, since there's no index to
+    /// binary-search yet).
+    Query {
+        /// Path to a JSONL capture file (NOT a pretty-printed `WholeTest`).
+        capture_file: PathBuf,
+        #[arg(long)]
+        instr: Instr,
+        #[arg(long, value_parser = parse_word_64)]
+        ra: u64,
+    },
+    /// Exports a golden `WholeTest` JSON capture into a normalized SQLite
+    /// database (`instructions`/`cases`/`outputs` tables), for analyzing a
+    /// large corpus with SQL instead of a one-off script.
+    ExportSqlite {
+        /// Path to a golden `WholeTest` JSON capture.
+        golden_file: PathBuf,
+        /// Path to the SQLite database to create. Must not already exist.
+        sqlite_file: PathBuf,
+    },
+    /// The inverse of `export-sqlite`: reads a SQLite database back into a
+    /// `WholeTest` JSON capture, e.g. to replay what's left after filtering
+    /// the database in SQL.
+    ImportSqlite {
+        /// Path to a SQLite database previously written by `export-sqlite`.
+        sqlite_file: PathBuf,
+        /// Path to write the resulting `WholeTest` JSON capture.
+        golden_file: PathBuf,
+    },
+    /// Exports a golden `WholeTest` JSON capture as a flattened Parquet
+    /// table, one row per test case, for pandas/polars-style analysis.
+    ExportParquet {
+        /// Path to a golden `WholeTest` JSON capture.
+        golden_file: PathBuf,
+        /// Path to the Parquet file to create (overwritten if it exists).
+        parquet_file: PathBuf,
+    },
+    /// Prints a shell completion script for `pia` to stdout, for sourcing
+    /// from a shell startup file (e.g. `pia completions bash >>
+    /// ~/.bash_completion`).
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Prints a man page for `pia` (troff format) to stdout, e.g. `pia
+    /// man-page > pia.1`.
+    ManPage,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum IsaFormat {
+    Json,
+    Yaml,
+}
+
+/// CLI-facing mirror of [`Strictness`] (`ValueEnum` can't be derived on a
+/// library type without pulling `clap` into the library's dependencies).
+#[derive(Clone, Copy, ValueEnum)]
+enum StrictnessArg {
+    Strict,
+    Warn,
+    Ignore,
+}
+
+/// CLI-facing mirror of [`model::CoreProfile`], for the same reason as
+/// [`StrictnessArg`].
+#[derive(Clone, Copy, ValueEnum)]
+enum CoreProfileArg {
+    DerivedFromRt,
+    ForcedZero,
+}
+
+impl From<CoreProfileArg> for model::CoreProfile {
+    fn from(arg: CoreProfileArg) -> Self {
+        match arg {
+            CoreProfileArg::DerivedFromRt => model::CoreProfile::DerivedFromRt,
+            CoreProfileArg::ForcedZero => model::CoreProfile::ForcedZero,
+        }
+    }
+}
+
+impl From<StrictnessArg> for Strictness {
+    fn from(arg: StrictnessArg) -> Strictness {
+        match arg {
+            StrictnessArg::Strict => Strictness::Strict,
+            StrictnessArg::Warn => Strictness::Warn,
+            StrictnessArg::Ignore => Strictness::Ignore,
+        }
+    }
+}
+
+/// CLI-facing mirror of [`junit::Granularity`] (`ValueEnum` can't be
+/// derived on a library type without pulling `clap` into the library's
+/// dependencies).
+#[derive(Clone, Copy, ValueEnum)]
+enum GranularityArg {
+    PerInstr,
+    PerTestCase,
+}
+
+impl From<GranularityArg> for junit::Granularity {
+    fn from(arg: GranularityArg) -> junit::Granularity {
+        match arg {
+            GranularityArg::PerInstr => junit::Granularity::PerInstr,
+            GranularityArg::PerTestCase => junit::Granularity::PerTestCase,
+        }
+    }
+}
+
+fn parse_model_variant(s: &str) -> Result<(Instr, Variant), String> {
+    let (mnemonic, variant) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected mnemonic=variant, got {:?}", s))?;
+    let instr: Instr = mnemonic.parse().map_err(|err: power_instruction_analyzer::instr::ParseInstrError| err.to_string())?;
+    let variant: Variant = variant.parse().map_err(|err: power_instruction_analyzer::model::ParseVariantError| err.to_string())?;
+    Ok((instr, variant))
+}
+
+/// Parses a wall-clock budget like `30s`, `10m`, or `2h` (no suffix means
+/// seconds) for `pia farm --time-budget`.
+fn parse_time_budget(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (digits, seconds_per_unit) = match s.strip_suffix('h') {
+        Some(digits) => (digits, 3600.0),
+        None => match s.strip_suffix('m') {
+            Some(digits) => (digits, 60.0),
+            None => (s.strip_suffix('s').unwrap_or(s), 1.0),
+        },
+    };
+    let units: f64 = digits.trim().parse().map_err(|_| format!("invalid time budget: {:?}", s))?;
+    Ok(Duration::from_secs_f64(units * seconds_per_unit))
+}
+
+/// Parses `pia fill --threads`'s count, rejecting `0` outright rather than
+/// letting it reach [`fill::resolve_num_threads`] (which treats `None`,
+/// not `0`, as "use the host's available parallelism"; dividing the batch
+/// into zero chunks would panic).
+fn parse_num_threads(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(0) => Err("--threads must be at least 1 (it isn't \"unset\" like omitting the flag)".to_string()),
+        Ok(n) => Ok(n),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn parse_word(s: &str) -> Result<u32, String> {
+    let s = s.trim();
+    let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => (hex, 16),
+        None => (s, 10),
+    };
+    u32::from_str_radix(digits, radix).map_err(|err| err.to_string())
+}
+
+fn parse_word_64(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => (hex, 16),
+        None => (s, 10),
+    };
+    u64::from_str_radix(digits, radix).map_err(|err| err.to_string())
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Serve { addr } => match remote::serve(&addr) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("pia serve: {}", err);
+                ExitCode::FAILURE
+            }
+        },
+        Command::Farm {
+            hosts,
+            no_cache,
+            refresh,
+            split_output,
+            corner_cases_only,
+            time_budget,
+            expand_mismatches,
+            metrics_addr,
+            max_mismatches_per_instr,
+        } => {
+            let campaign_metrics = match metrics_addr {
+                Some(addr) => {
+                    let campaign_metrics = Arc::new(metrics::CampaignMetrics::default());
+                    if let Err(err) = metrics::spawn(&addr, Arc::clone(&campaign_metrics)) {
+                        eprintln!("pia farm: failed to bind metrics endpoint on {}: {}", addr, err);
+                        return ExitCode::FAILURE;
+                    }
+                    eprintln!("pia farm: serving metrics on http://{}/metrics", addr);
+                    Some(campaign_metrics)
+                }
+                None => None,
+            };
+            let mut cache = if no_cache {
+                Cache::disabled()
+            } else {
+                match Cache::load(DEFAULT_CACHE_PATH) {
+                    Ok(cache) => cache,
+                    Err(err) => {
+                        eprintln!("pia farm: failed to load cache: {}", err);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            };
+            if refresh {
+                cache.clear();
+            }
+            let supported_instrs = || Instr::ALL.iter().copied().filter(|instr| !instr.is_model_only());
+            let cases = if let Some(time_budget) = time_budget {
+                let budget = timebox::case_budget(time_budget);
+                let selection = timebox::select_cases(supported_instrs(), budget);
+                eprintln!(
+                    "pia farm: time budget {:?} ~= {} cases, {:.1}% coverage of the full campaign",
+                    time_budget,
+                    budget,
+                    selection.coverage * 100.0
+                );
+                selection.cases
+            } else if corner_cases_only {
+                supported_instrs().flat_map(corner_cases::corner_case_inputs).collect()
+            } else {
+                supported_instrs().flat_map(campaign::exhaustive_cases).collect()
+            };
+            if let Some(campaign_metrics) = &campaign_metrics {
+                campaign_metrics.set_total(cases.len() as u64);
+            }
+            let farm = remote::Farm::new(hosts);
+            let (mut whole_test, mut cache) = farm.run_sharded(cases, cache, campaign_metrics.as_ref());
+            if expand_mismatches {
+                let neighbor_cases = neighborhood::neighbor_cases_for_mismatches(&whole_test);
+                if !neighbor_cases.is_empty() {
+                    eprintln!("pia farm: expanding {} mismatch(es) into {} neighboring case(s)", whole_test.statistics().per_instruction.values().map(|stats| stats.mismatches).sum::<usize>(), neighbor_cases.len());
+                    if let Some(campaign_metrics) = &campaign_metrics {
+                        campaign_metrics.set_total(whole_test.test_cases.len() as u64 + neighbor_cases.len() as u64);
+                    }
+                    let (expansion, new_cache) = farm.run_sharded(neighbor_cases, cache, campaign_metrics.as_ref());
+                    whole_test.test_cases.extend(expansion.test_cases);
+                    cache = new_cache;
+                }
+            }
+            if let Err(err) = cache.save() {
+                eprintln!("pia farm: failed to save cache: {}", err);
+            }
+            for (instr, stats) in &whole_test.statistics().per_instruction {
+                eprintln!(
+                    "pia farm: {}: {}/{} mismatched ({:.1}%)",
+                    instr,
+                    stats.mismatches,
+                    stats.ran(),
+                    stats.mismatch_rate() * 100.0
+                );
+                for (reason, count) in &stats.skip_counts {
+                    eprintln!("pia farm: {}: {} skipped ({})", instr, count, reason);
+                }
+            }
+            if let Some(max_mismatches_per_instr) = max_mismatches_per_instr {
+                for (instr, dropped) in whole_test.truncate_mismatches_per_instr(max_mismatches_per_instr) {
+                    eprintln!(
+                        "pia farm: {}: truncated {} mismatch(es) beyond --max-mismatches-per-instr {}",
+                        instr, dropped, max_mismatches_per_instr
+                    );
+                }
+            }
+            if let Some(split_output) = split_output {
+                if let Err(err) = whole_test.write_split(&split_output) {
+                    eprintln!("pia farm: failed to write split output: {}", err);
+                    return ExitCode::FAILURE;
+                }
+            } else {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&whole_test).expect("serialization cannot fail")
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Command::DumpIsa { format } => {
+            let table = metadata::all_metadata();
+            let rendered = match format {
+                IsaFormat::Json => serde_json::to_string_pretty(&table).expect("serialization cannot fail"),
+                IsaFormat::Yaml => serde_yaml::to_string(&table).expect("serialization cannot fail"),
+            };
+            println!("{}", rendered);
+            ExitCode::SUCCESS
+        }
+        Command::GenDocs { out_dir } => match docgen::generate_docs(&out_dir) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("pia gen-docs: {}", err);
+                ExitCode::FAILURE
+            }
+        },
+        Command::Fill { input_file, native, model_variant, filter, repeat, cpu, nice, threads } => {
+            let contents = match fs::read_to_string(&input_file) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    eprintln!("pia fill: failed to read {}: {}", input_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let cases: Vec<InputOnlyCase> = match serde_json::from_str(&contents) {
+                Ok(cases) => cases,
+                Err(err) => {
+                    eprintln!("pia fill: failed to parse {}: {}", input_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let variants = model_variant.into_iter().collect();
+            let pinning = affinity::Pinning { cpu, nice };
+            let whole_test = if repeat > 1 {
+                if !pinning.is_noop() {
+                    if let Err(err) = pinning.apply() {
+                        eprintln!("pia fill: failed to apply {:?}: {}", pinning, err);
+                    }
+                }
+                let (mut whole_test, flakiness_reports) = fill::fill_checking_flakiness(cases, repeat, &variants);
+                for report in &flakiness_reports {
+                    eprintln!(
+                        "pia fill: {} {:?} is flaky across {} native re-executions: {:?}",
+                        report.instr, report.input, repeat, report.outputs
+                    );
+                }
+                if !pinning.is_noop() {
+                    whole_test.pinning = Some(pinning);
+                }
+                whole_test
+            } else if !pinning.is_noop() {
+                fill::fill_pinned(cases, native, &variants, pinning, threads)
+            } else {
+                fill::fill_parallel(cases, native, &variants, threads)
+            };
+            let mut whole_test = whole_test;
+            if let Some(filter) = &filter {
+                whole_test
+                    .test_cases
+                    .retain(|case| filter.matches(&case.input, &case.model_output));
+            }
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&whole_test).expect("serialization cannot fail")
+            );
+            ExitCode::SUCCESS
+        }
+        Command::Exec { input_file, native, model_variant } => {
+            let reader: Box<dyn BufRead> = if input_file.as_os_str() == "-" {
+                Box::new(io::BufReader::new(io::stdin()))
+            } else {
+                match fs::File::open(&input_file) {
+                    Ok(file) => Box::new(io::BufReader::new(file)),
+                    Err(err) => {
+                        eprintln!("pia exec: failed to open {}: {}", input_file.display(), err);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            };
+            let variants = model_variant.into_iter().collect();
+            let mut had_error = false;
+            let cases = reader.lines().filter_map(|line| {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(err) => {
+                        eprintln!("pia exec: failed to read a line: {}", err);
+                        had_error = true;
+                        return None;
+                    }
+                };
+                if line.trim().is_empty() {
+                    return None;
+                }
+                match serde_json::from_str::<InputOnlyCase>(&line) {
+                    Ok(case) => Some((case.instr, case.input)),
+                    Err(err) => {
+                        eprintln!("pia exec: failed to parse case {:?}: {}", line, err);
+                        had_error = true;
+                        None
+                    }
+                }
+            });
+            for test_case in fill::run_batch(cases, native, &variants) {
+                println!("{}", serde_json::to_string(&test_case).expect("serialization cannot fail"));
+            }
+            if had_error {
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
+        Command::Check { golden_file, legacy32, fp_loose } => {
+            let contents = match fs::read_to_string(&golden_file) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    eprintln!("pia check: failed to read {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let golden: WholeTest = match serde_json::from_str(&contents) {
+                Ok(golden) => golden,
+                Err(err) => {
+                    eprintln!("pia check: failed to parse {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let profile = match (legacy32, fp_loose) {
+                (true, _) => check::ComparisonProfile::Legacy32,
+                (false, true) => check::ComparisonProfile::FpLoose,
+                (false, false) => check::ComparisonProfile::Full,
+            };
+            eprintln!("pia check: comparing under profile {}", profile);
+            let mismatches = check::check_golden_with_profile(&golden, profile);
+            for mismatch in &mismatches {
+                println!("{}", mismatch);
+            }
+            if mismatches.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                eprintln!("pia check: {} mismatch(es)", mismatches.len());
+                ExitCode::FAILURE
+            }
+        }
+        Command::Decode { word, strictness, extended } => {
+            match decoder::disassemble(word, strictness.into(), extended) {
+                Ok(text) => {
+                    println!("{}", text);
+                    ExitCode::SUCCESS
+                }
+                Err(err) => {
+                    eprintln!("pia decode: {}", err);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::ListCustom => {
+            for name in registry::list() {
+                println!("{}", name);
+            }
+            ExitCode::SUCCESS
+        }
+        Command::Audit => {
+            let mut found_any = false;
+            for instr in Instr::ALL.iter().copied() {
+                for discrepancy in audit::audit(instr) {
+                    found_any = true;
+                    println!("{}: {}", instr, discrepancy);
+                }
+            }
+            if found_any {
+                ExitCode::FAILURE
+            } else {
+                println!("pia audit: metadata and models agree for every instruction");
+                ExitCode::SUCCESS
+            }
+        }
+        Command::CarryChain { length, native } => {
+            let chain = Sequence::carry_chain(length);
+            let model_result = sequence::run_model(&chain);
+            println!("model: final xer = {:?}", model_result.final_xer);
+            if native {
+                match sequence::run_native(&chain) {
+                    Ok(native_result) => {
+                        println!("native: final xer = {:?}", native_result.final_xer);
+                        if native_result.final_xer == model_result.final_xer {
+                            println!("carry propagation matches");
+                        } else {
+                            eprintln!("pia carry-chain: model and native disagree on final xer");
+                            return ExitCode::FAILURE;
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("pia carry-chain: native execution failed: {}", err);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            ExitCode::SUCCESS
+        }
+        Command::DivReport { native, core_profile } => {
+            print!("{}", div_report::render(&div_report::rows(native, core_profile.into())));
+            ExitCode::SUCCESS
+        }
+        Command::ShowAsm { instr } => {
+            if instr.is_model_only() {
+                eprintln!("pia show-asm: {} is model-only; no native backend can execute it", instr);
+                return ExitCode::FAILURE;
+            }
+            let regs = RegisterAssignment::DEFAULT;
+            let word = encoder::encode(instr, regs.rt, regs.ra, regs.rb)
+                .expect("encode() only returns None for model-only instructions, already checked above");
+            println!("; the jit-lite backend assembles and runs exactly this, followed by `blr`:");
+            println!("{}\tr{},r{},r{}", instr, regs.rt, regs.ra, regs.rb);
+            println!("; word = {:#010x}", word);
+            println!(
+                "; register assignment is fixed at rt=r{}, ra=r{}, rb=r{} here (RegisterAssignment::DEFAULT);",
+                regs.rt, regs.ra, regs.rb
+            );
+            println!("; see RegisterAssignment::STRESS_SET for the aliased assignments also probed natively");
+            ExitCode::SUCCESS
+        }
+        Command::ExportVcd { golden_file } => {
+            let contents = match fs::read_to_string(&golden_file) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    eprintln!("pia export-vcd: failed to read {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let golden: WholeTest = match serde_json::from_str(&contents) {
+                Ok(golden) => golden,
+                Err(err) => {
+                    eprintln!("pia export-vcd: failed to parse {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            match vcd::write_vcd(&golden, std::io::stdout()) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    eprintln!("pia export-vcd: {}", err);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::ExportCocotbVectors { golden_file, out_dir } => {
+            let contents = match fs::read_to_string(&golden_file) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    eprintln!("pia export-cocotb-vectors: failed to read {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let golden: WholeTest = match serde_json::from_str(&contents) {
+                Ok(golden) => golden,
+                Err(err) => {
+                    eprintln!("pia export-cocotb-vectors: failed to parse {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            match cocotb_vectors::export(&golden, &out_dir) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    eprintln!("pia export-cocotb-vectors: failed to write {}: {}", out_dir.display(), err);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::GenSva { out_file } => match fs::write(&out_file, sva::render_all()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("pia gen-sva: failed to write {}: {}", out_file.display(), err);
+                ExitCode::FAILURE
+            }
+        },
+        Command::CheckConstantFolding { golden_file, compiler } => {
+            let contents = match fs::read_to_string(&golden_file) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    eprintln!("pia check-constant-folding: failed to read {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let golden: WholeTest = match serde_json::from_str(&contents) {
+                Ok(golden) => golden,
+                Err(err) => {
+                    eprintln!("pia check-constant-folding: failed to parse {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let mut checked = 0;
+            let mut mismatches = 0;
+            for case in &golden.test_cases {
+                match cdiff::check_constant_folding(case.instr, case.input.ra, case.input.rb, &compiler) {
+                    Ok(()) => checked += 1,
+                    Err(cdiff::Error::Unsupported(_)) => {}
+                    Err(err) => {
+                        eprintln!("pia check-constant-folding: {} (ra={:#x}, rb={:#x}): {}", case.instr, case.input.ra, case.input.rb, err);
+                        mismatches += 1;
+                    }
+                }
+            }
+            println!("checked {} case(s), {} mismatch(es)", checked, mismatches);
+            if mismatches == 0 {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Command::JunitReport { golden_file, legacy32, fp_loose, granularity, out_file } => {
+            let contents = match fs::read_to_string(&golden_file) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    eprintln!("pia junit-report: failed to read {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let golden: WholeTest = match serde_json::from_str(&contents) {
+                Ok(golden) => golden,
+                Err(err) => {
+                    eprintln!("pia junit-report: failed to parse {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let profile = match (legacy32, fp_loose) {
+                (true, _) => check::ComparisonProfile::Legacy32,
+                (false, true) => check::ComparisonProfile::FpLoose,
+                (false, false) => check::ComparisonProfile::Full,
+            };
+            eprintln!("pia junit-report: comparing under profile {}", profile);
+            let mismatches = check::check_golden_with_profile(&golden, profile);
+            let xml = junit::render(&golden, &mismatches, granularity.into());
+            match fs::write(&out_file, xml) {
+                Ok(()) if mismatches.is_empty() => ExitCode::SUCCESS,
+                Ok(()) => ExitCode::FAILURE,
+                Err(err) => {
+                    eprintln!("pia junit-report: failed to write {}: {}", out_file.display(), err);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::RerunFailures { golden_file, repeat } => {
+            let contents = match fs::read_to_string(&golden_file) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    eprintln!("pia rerun-failures: failed to read {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let golden: WholeTest = match serde_json::from_str(&contents) {
+                Ok(golden) => golden,
+                Err(err) => {
+                    eprintln!("pia rerun-failures: failed to parse {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let results = rerun::rerun_mismatches(&golden, repeat);
+            let mut flaky = 0;
+            for result in &results {
+                let observed: Vec<String> = result
+                    .outputs
+                    .iter()
+                    .map(|output| match output {
+                        Ok(output) => format!("{:?}", output),
+                        Err(err) => format!("error: {}", err),
+                    })
+                    .collect();
+                if result.is_deterministic() {
+                    println!("{} {:?}: deterministic divergence, observed {:?}", result.case.instr, result.case.input, observed.first());
+                } else {
+                    flaky += 1;
+                    println!("{} {:?}: FLAKY, observed {:?}", result.case.instr, result.case.input, observed);
+                }
+            }
+            println!("{} mismatching case(s) re-run, {} flaky", results.len(), flaky);
+            ExitCode::SUCCESS
+        }
+        Command::ClusterMismatches { golden_file } => {
+            let contents = match fs::read_to_string(&golden_file) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    eprintln!("pia cluster-mismatches: failed to read {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let golden: WholeTest = match serde_json::from_str(&contents) {
+                Ok(golden) => golden,
+                Err(err) => {
+                    eprintln!("pia cluster-mismatches: failed to parse {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let clusters = cluster::cluster_mismatches(&golden);
+            for cluster in &clusters {
+                println!(
+                    "{} case(s): {} disagrees on {:?}, ra {:?}, rb {:?} (e.g. {:?})",
+                    cluster.count,
+                    cluster.example.instr,
+                    cluster.key.differing_fields,
+                    cluster.key.ra_sign,
+                    cluster.key.rb_sign,
+                    cluster.example.input
+                );
+            }
+            println!("{} cluster(s) across {} mismatching case(s)", clusters.len(), clusters.iter().map(|cluster| cluster.count).sum::<usize>());
+            ExitCode::SUCCESS
+        }
+        Command::Index { capture_file } => {
+            let capture = match fs::read(&capture_file) {
+                Ok(capture) => capture,
+                Err(err) => {
+                    eprintln!("pia index: failed to read {}: {}", capture_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let index = match capture_index::build_index(&capture) {
+                Ok(index) => index,
+                Err(err) => {
+                    eprintln!("pia index: failed to parse {}: {}", capture_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let index_path = capture_index::default_index_path(&capture_file);
+            match capture_index::write_index(&index, &index_path) {
+                Ok(()) => {
+                    println!("pia index: wrote {} entries to {}", index.entries.len(), index_path.display());
+                    ExitCode::SUCCESS
+                }
+                Err(err) => {
+                    eprintln!("pia index: failed to write {}: {}", index_path.display(), err);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::Query { capture_file, instr, ra } => {
+            let file = match fs::File::open(&capture_file) {
+                Ok(file) => file,
+                Err(err) => {
+                    eprintln!("pia query: failed to open {}: {}", capture_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let capture = match unsafe { memmap2::Mmap::map(&file) } {
+                Ok(capture) => capture,
+                Err(err) => {
+                    eprintln!("pia query: failed to mmap {}: {}", capture_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let index_path = capture_index::default_index_path(&capture_file);
+            let index = match capture_index::read_index(&index_path) {
+                Ok(index) => index,
+                Err(err) => {
+                    eprintln!(
+                        "pia query: no usable index at {} ({}); scanning {} in full -- run `pia index` first to avoid this",
+                        index_path.display(),
+                        err,
+                        capture_file.display()
+                    );
+                    match capture_index::build_index(&capture) {
+                        Ok(index) => index,
+                        Err(err) => {
+                            eprintln!("pia query: failed to parse {}: {}", capture_file.display(), err);
+                            return ExitCode::FAILURE;
+                        }
+                    }
+                }
+            };
+            match capture_index::query(&index, &capture, instr, ra) {
+                Ok(cases) => {
+                    for case in &cases {
+                        println!("{}", serde_json::to_string(case).expect("serialization cannot fail"));
+                    }
+                    println!("{} matching case(s)", cases.len());
+                    ExitCode::SUCCESS
+                }
+                Err(err) => {
+                    eprintln!("pia query: a matched entry failed to parse: {}", err);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::ExportSqlite { golden_file, sqlite_file } => {
+            let contents = match fs::read_to_string(&golden_file) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    eprintln!("pia export-sqlite: failed to read {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let golden: WholeTest = match serde_json::from_str(&contents) {
+                Ok(golden) => golden,
+                Err(err) => {
+                    eprintln!("pia export-sqlite: failed to parse {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            match sqlite_export::export(&golden, &sqlite_file) {
+                Ok(()) => {
+                    println!("pia export-sqlite: wrote {} case(s) to {}", golden.test_cases.len(), sqlite_file.display());
+                    ExitCode::SUCCESS
+                }
+                Err(err) => {
+                    eprintln!("pia export-sqlite: failed to write {}: {}", sqlite_file.display(), err);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::ImportSqlite { sqlite_file, golden_file } => {
+            let golden = match sqlite_export::import(&sqlite_file) {
+                Ok(golden) => golden,
+                Err(err) => {
+                    eprintln!("pia import-sqlite: failed to read {}: {}", sqlite_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            match fs::write(&golden_file, serde_json::to_string_pretty(&golden).expect("serialization cannot fail")) {
+                Ok(()) => {
+                    println!("pia import-sqlite: wrote {} case(s) to {}", golden.test_cases.len(), golden_file.display());
+                    ExitCode::SUCCESS
+                }
+                Err(err) => {
+                    eprintln!("pia import-sqlite: failed to write {}: {}", golden_file.display(), err);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::ExportParquet { golden_file, parquet_file } => {
+            let contents = match fs::read_to_string(&golden_file) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    eprintln!("pia export-parquet: failed to read {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let golden: WholeTest = match serde_json::from_str(&contents) {
+                Ok(golden) => golden,
+                Err(err) => {
+                    eprintln!("pia export-parquet: failed to parse {}: {}", golden_file.display(), err);
+                    return ExitCode::FAILURE;
+                }
+            };
+            match parquet_export::export(&golden, &parquet_file) {
+                Ok(()) => {
+                    println!("pia export-parquet: wrote {} case(s) to {}", golden.test_cases.len(), parquet_file.display());
+                    ExitCode::SUCCESS
+                }
+                Err(err) => {
+                    eprintln!("pia export-parquet: failed to write {}: {}", parquet_file.display(), err);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        Command::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "pia", &mut io::stdout());
+            ExitCode::SUCCESS
+        }
+        Command::ManPage => match clap_mangen::Man::new(Cli::command()).render(&mut io::stdout()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("pia man-page: failed to render: {}", err);
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
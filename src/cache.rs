@@ -0,0 +1,88 @@
+//! On-disk cache of native execution results, keyed by instruction,
+//! canonicalized inputs, backend, and CPU model, so that repeated campaigns
+//! don't re-execute cases they've already seen.
+
+use crate::instr::Instr;
+use crate::types::{InstructionInput, InstructionOutput};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Identifies one cached native-execution result.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub instr: Instr,
+    pub input: InstructionInput,
+    pub backend: String,
+    pub cpu_model: String,
+}
+
+/// A cache of native execution results, persisted as a single JSON file.
+#[derive(Default)]
+pub struct Cache {
+    path: PathBuf,
+    entries: HashMap<CacheKey, InstructionOutput>,
+    dirty: bool,
+}
+
+impl Cache {
+    /// Loads the cache from `path`, treating a missing file as an empty
+    /// cache.
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let entries: Vec<(CacheKey, InstructionOutput)> =
+                    serde_json::from_str(&contents)?;
+                entries.into_iter().collect()
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            path,
+            entries,
+            dirty: false,
+        })
+    }
+
+    /// Creates a cache that discards its contents instead of persisting
+    /// them, for `--no-cache`.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<InstructionOutput> {
+        self.entries.get(key).copied()
+    }
+
+    pub fn insert(&mut self, key: CacheKey, output: InstructionOutput) {
+        self.entries.insert(key, output);
+        self.dirty = true;
+    }
+
+    /// Discards every entry, for `--refresh`.
+    pub fn clear(&mut self) {
+        if !self.entries.is_empty() {
+            self.entries.clear();
+            self.dirty = true;
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        if !self.dirty || self.path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let entries: Vec<(&CacheKey, &InstructionOutput)> = self.entries.iter().collect();
+        fs::write(&self.path, serde_json::to_string(&entries)?)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
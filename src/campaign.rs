@@ -0,0 +1,124 @@
+//! Exhaustive native-execution test-case generation for `pia farm`, using
+//! each instruction's flags-read metadata (see [`crate::metadata`]) so an
+//! instruction that doesn't architecturally read a given XER bit doesn't
+//! pay for iterating it.
+
+use crate::instr::Instr;
+use crate::metadata::{self, Flag};
+use crate::prng::RandomGenerator;
+use crate::types::{InstructionInput, Xer};
+
+/// Candidate `ra`/`rb` operand pairs iterated for every instruction. Kept
+/// small and fixed -- covering zero, one, and the boundaries most likely to
+/// trip up carry/overflow logic -- rather than exhaustive over `u64`, since
+/// this generator's job is covering flag combinations, not operand space.
+const OPERAND_PAIRS: &[(u64, u64)] = &[(0, 0), (1, 1), (u64::MAX, 1), (i64::MAX as u64, 1)];
+
+/// Builds one `(instr, input)` case per combination of [`OPERAND_PAIRS`]
+/// and every XER flag `instr` actually reads, instead of the full
+/// `2.pow(3)` SO/OV/CA cross product every instruction would otherwise pay
+/// for regardless of whether it reads those bits.
+pub fn exhaustive_cases(instr: Instr) -> Vec<(Instr, InstructionInput)> {
+    let reads = metadata::metadata(instr).reads;
+    let so_values = flag_values(&reads, Flag::So);
+    let ov_values = flag_values(&reads, Flag::Ov);
+    let ca_values = flag_values(&reads, Flag::Ca);
+
+    let mut cases = Vec::new();
+    for &(ra, rb) in OPERAND_PAIRS {
+        for &so in &so_values {
+            for &ov in &ov_values {
+                for &ca in &ca_values {
+                    let xer = Xer { so, ov, ca, ..Xer::default() };
+                    cases.push((instr, InstructionInput { ra, rb, xer, ..InstructionInput::default() }));
+                }
+            }
+        }
+    }
+    cases
+}
+
+/// Builds `count` random `(instr, input)` cases, drawing `ra`/`rb`
+/// uniformly from the full `u64` range and only the XER flags `instr`
+/// actually reads (like [`exhaustive_cases`]) from `generator`.
+///
+/// Unlike [`exhaustive_cases`], this covers operand space [`OPERAND_PAIRS`]
+/// doesn't -- useful once the fixed corner cases stop turning up new
+/// mismatches. `generator`'s state is advanced, not reset, so a caller that
+/// saves it (see [`RandomGenerator::state`]) can resume the exact same
+/// sequence across runs, e.g. to grow an existing random sample rather than
+/// restart it.
+pub fn random_cases(instr: Instr, generator: &mut RandomGenerator, count: usize) -> Vec<(Instr, InstructionInput)> {
+    let reads = metadata::metadata(instr).reads;
+    let so_values = flag_values(&reads, Flag::So);
+    let ov_values = flag_values(&reads, Flag::Ov);
+    let ca_values = flag_values(&reads, Flag::Ca);
+
+    (0..count)
+        .map(|_| {
+            let ra = generator.next_u64();
+            let rb = generator.next_u64();
+            let so = so_values[generator.next_below(so_values.len() as u64) as usize];
+            let ov = ov_values[generator.next_below(ov_values.len() as u64) as usize];
+            let ca = ca_values[generator.next_below(ca_values.len() as u64) as usize];
+            let xer = Xer { so, ov, ca, ..Xer::default() };
+            (instr, InstructionInput { ra, rb, xer, ..InstructionInput::default() })
+        })
+        .collect()
+}
+
+/// `[false, true]` if `reads` contains `flag`, or just `[false]` otherwise
+/// -- an instruction that never looks at a flag can't distinguish its
+/// values, so there's nothing to gain from running it twice.
+fn flag_values(reads: &[Flag], flag: Flag) -> Vec<bool> {
+    if reads.contains(&flag) {
+        vec![false, true]
+    } else {
+        vec![false]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_flags_an_instruction_never_reads() {
+        // `add` reads no XER flags, so only one XER value per operand pair.
+        assert_eq!(exhaustive_cases(Instr::Add).len(), OPERAND_PAIRS.len());
+    }
+
+    #[test]
+    fn covers_flags_an_instruction_does_read() {
+        // `adde` reads CA, so both CA values are covered per operand pair.
+        assert_eq!(exhaustive_cases(Instr::AddE).len(), OPERAND_PAIRS.len() * 2);
+    }
+
+    #[test]
+    fn random_cases_never_sets_a_flag_an_instruction_does_not_read() {
+        let mut generator = RandomGenerator::from_seed(1);
+        for (instr, input) in random_cases(Instr::Add, &mut generator, 64) {
+            assert_eq!(instr, Instr::Add);
+            assert_eq!(input.xer, Xer::default());
+        }
+    }
+
+    #[test]
+    fn random_cases_is_reproducible_from_the_same_seed() {
+        let mut a = RandomGenerator::from_seed(99);
+        let mut b = RandomGenerator::from_seed(99);
+        assert_eq!(random_cases(Instr::AddE, &mut a, 32), random_cases(Instr::AddE, &mut b, 32));
+    }
+
+    #[test]
+    fn random_cases_resumes_from_a_saved_generator_state() {
+        let mut generator = RandomGenerator::from_seed(7);
+        let first_batch = random_cases(Instr::Add, &mut generator, 10);
+        let checkpoint = generator.state();
+        let second_batch = random_cases(Instr::Add, &mut generator, 10);
+
+        let mut resumed = RandomGenerator::from_state(checkpoint);
+        assert_eq!(random_cases(Instr::Add, &mut resumed, 10), second_batch);
+        assert_ne!(first_batch, second_batch);
+    }
+}
@@ -0,0 +1,751 @@
+//! Types for recording the result of running an instruction both natively
+//! and through its software model, and collections of those results.
+
+use crate::affinity::Pinning;
+use crate::endian::Endianness;
+use crate::hex_format::{format_hex, HexFormatConfig};
+use crate::host_info::HostInfo;
+use crate::instr::Instr;
+use crate::metadata::{self, Flag};
+use crate::timing::LatencyStats;
+use crate::types::{InstructionInput, InstructionOutput};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Why a [`TestCase`] carries no comparison, recorded instead of running
+/// (or instead of keeping) native/model execution for it, so a summary can
+/// report "not run" separately from "ran and matched" rather than
+/// conflating the two.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum SkipReason {
+    /// The instruction isn't implemented on the ISA level native execution
+    /// ran under (e.g. a 3.1-only instruction on a 3.0 host).
+    UnsupportedIsaLevel,
+    /// The instruction requires privileged state this process doesn't have.
+    Privileged,
+    /// The backend (native execution or the model) didn't return within
+    /// its allotted time.
+    BackendTimeout,
+    /// A [`crate::filter`] expression excluded this case from comparison,
+    /// but the case itself is kept (e.g. to record why it was dropped)
+    /// rather than removed from `test_cases` outright.
+    FilteredUndefined,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SkipReason::UnsupportedIsaLevel => "unsupported ISA level",
+            SkipReason::Privileged => "privileged",
+            SkipReason::BackendTimeout => "backend timeout",
+            SkipReason::FilteredUndefined => "filtered undefined",
+        })
+    }
+}
+
+/// One native-vs-model comparison.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TestCase {
+    pub instr: Instr,
+    pub input: InstructionInput,
+    pub native_output: InstructionOutput,
+    pub model_output: InstructionOutput,
+    /// The instruction's [`crate::metadata::model_revision`] at the time
+    /// `model_output` was computed. `#[serde(default)]` so older captures
+    /// that predate this field deserialize as `0`, a sentinel guaranteed to
+    /// differ from any real revision (which start at 1) -- see
+    /// [`crate::check`].
+    #[serde(default)]
+    pub model_revision: u32,
+    /// Set when this case wasn't actually run to comparison -- see
+    /// [`SkipReason`]. `native_output`/`model_output` are meaningless
+    /// (typically left at their default) when this is set; check it before
+    /// trusting [`TestCase::matches`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip: Option<SkipReason>,
+    /// Latency measured alongside this case, if timing mode was enabled.
+    /// Never compared for correctness -- see [`crate::timing`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<LatencyStats>,
+}
+
+impl TestCase {
+    /// Whether the native and model outputs agree on every field that
+    /// either of them populated. Always `false` for a skipped case ([`Self::skip`]
+    /// is set) -- a skipped case was never run, so it can't have matched.
+    pub fn matches(&self) -> bool {
+        self.skip.is_none()
+            && self.native_output.rt == self.model_output.rt
+            && self.native_output.cr0 == self.model_output.cr0
+            && self.native_output.xer == self.model_output.xer
+    }
+}
+
+/// A full set of [`TestCase`]s gathered during one campaign run, as written
+/// to and read from capture files.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WholeTest {
+    pub test_cases: Vec<TestCase>,
+    /// The CPU-affinity/priority pinning in effect while these cases' native
+    /// outputs were collected, if any was requested. Purely forensic, like
+    /// `TestCase::latency` -- it records how reproducible the run *should*
+    /// have been, not anything a model is expected to match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinning: Option<Pinning>,
+    /// The byte order native execution ran under while collecting these
+    /// cases' `native_output`s, if any were collected. See
+    /// [`crate::endian`]; purely forensic, like `pinning`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_endianness: Option<Endianness>,
+    /// The auxiliary-vector capability bits and MMU mode of the host these
+    /// cases' `native_output`s were collected on, if any were collected.
+    /// See [`crate::host_info`]; purely forensic, like `pinning`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_info: Option<HostInfo>,
+}
+
+impl WholeTest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mismatches(&self) -> impl Iterator<Item = &TestCase> {
+        self.test_cases.iter().filter(|test_case| test_case.skip.is_none() && !test_case.matches())
+    }
+
+    /// Keeps at most `max_per_instr` mismatching cases for each instruction
+    /// (the first `max_per_instr` encountered, in `test_cases`' existing
+    /// order), dropping the rest -- so a badly broken model's millions of
+    /// near-identical failures don't dominate a campaign's output. Matching
+    /// and skipped cases are never dropped. Returns how many mismatches were
+    /// dropped per instruction (instructions with nothing dropped are
+    /// omitted), so the caller can report that truncation happened.
+    pub fn truncate_mismatches_per_instr(&mut self, max_per_instr: usize) -> BTreeMap<Instr, usize> {
+        let mut kept: BTreeMap<Instr, usize> = BTreeMap::new();
+        let mut dropped: BTreeMap<Instr, usize> = BTreeMap::new();
+        self.test_cases.retain(|test_case| {
+            if test_case.skip.is_some() || test_case.matches() {
+                return true;
+            }
+            let count = kept.entry(test_case.instr).or_default();
+            if *count < max_per_instr {
+                *count += 1;
+                true
+            } else {
+                *dropped.entry(test_case.instr).or_default() += 1;
+                false
+            }
+        });
+        dropped
+    }
+
+    /// Sorts `test_cases` by `(instr, input)` and normalizes `latency` away,
+    /// so that two captures of the same cases produced with different
+    /// parallelism or generator order compare equal (and diff meaningfully)
+    /// regardless of the order they were collected in.
+    pub fn canonicalize(&mut self) {
+        self.test_cases.sort_by_key(|test_case| (test_case.instr, test_case.input));
+        for test_case in &mut self.test_cases {
+            test_case.latency = None;
+        }
+        self.pinning = None;
+        self.host_endianness = None;
+        self.host_info = None;
+    }
+
+    /// Writes this test as pretty-printed JSON, rendering `ra`/`rb`/`rc`/`rt`
+    /// register values as hex strings formatted per `config` instead of
+    /// plain JSON numbers, to match the conventions of other Libre-SOC
+    /// tooling.
+    pub fn to_writer<W: io::Write>(&self, writer: W, config: &HexFormatConfig) -> io::Result<()> {
+        let mut value = serde_json::to_value(self).expect("WholeTest always serializes");
+        rewrite_hex_fields(&mut value, config);
+        serde_json::to_writer_pretty(writer, &value).map_err(io::Error::from)
+    }
+
+    /// Writes one JSONL file per instruction into `dir` (e.g. `add.jsonl`),
+    /// plus an `index.json` manifest listing each instruction's file and
+    /// case count. This keeps a selected instruction's capture small enough
+    /// to commit to git and lets downstream processing work per-file.
+    pub fn write_split(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let mut by_instr: BTreeMap<Instr, Vec<TestCase>> = BTreeMap::new();
+        for test_case in &self.test_cases {
+            by_instr.entry(test_case.instr).or_default().push(test_case.clone());
+        }
+
+        let mut manifest = BTreeMap::new();
+        for (instr, cases) in &by_instr {
+            let file_name = format!("{}.jsonl", instr);
+            let file = fs::File::create(dir.join(&file_name))?;
+            write_test_cases_streaming(file, cases.iter().cloned())?;
+            manifest.insert(instr.name(), SplitManifestEntry {
+                file: file_name,
+                count: cases.len(),
+            });
+        }
+        let manifest_file = fs::File::create(dir.join("index.json"))?;
+        serde_json::to_writer_pretty(manifest_file, &manifest)?;
+        Ok(())
+    }
+
+    /// Computes per-instruction counts, mismatch rates, and field-level
+    /// mismatch histograms across every case.
+    pub fn statistics(&self) -> Statistics {
+        let mut per_instruction: BTreeMap<Instr, InstructionStatistics> = BTreeMap::new();
+        for case in &self.test_cases {
+            let stats = per_instruction.entry(case.instr).or_default();
+            stats.total += 1;
+            if let Some(reason) = case.skip {
+                *stats.skip_counts.entry(reason).or_default() += 1;
+                continue;
+            }
+            if !case.matches() {
+                stats.mismatches += 1;
+            }
+            let mut count_field = |name: &str, matches: bool| {
+                if !matches {
+                    *stats.field_mismatch_counts.entry(name.to_string()).or_default() += 1;
+                }
+            };
+            count_field("rt", case.native_output.rt == case.model_output.rt);
+            count_field("cr0", case.native_output.cr0 == case.model_output.cr0);
+            count_field("xer", case.native_output.xer == case.model_output.xer);
+            count_field("raw_cr", case.native_output.raw_cr == case.model_output.raw_cr);
+        }
+        Statistics { per_instruction }
+    }
+
+    /// Checks every case against its own instruction's metadata, for
+    /// catching a corrupted or hand-edited capture (e.g. from a buggy
+    /// merge/replay tool) before it's trusted as golden. Two things are
+    /// checked per case:
+    ///
+    /// - that a populated `native_output`/`model_output` only sets fields
+    ///   `instr`'s [`metadata::metadata`] actually declares writing (e.g. a
+    ///   `cr0` that shows up on an instruction whose metadata doesn't list
+    ///   [`Flag::Cr0`] among `writes`);
+    /// - that [`TestCase::matches`] reporting a mismatch is backed by at
+    ///   least one field [`InstructionOutput::diff`] actually finds
+    ///   disagreeing -- the two are computed from overlapping but not
+    ///   identical field sets, so a future field added to one and not the
+    ///   other could make them drift apart silently.
+    ///
+    /// This doesn't check that `input` "has everything the instruction
+    /// requires": every [`InstructionInput`] already carries every field
+    /// (`ra`, `rb`, `xer`, ...) regardless of instruction, so there's
+    /// nothing that could be missing from it to flag.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        for (case_index, case) in self.test_cases.iter().enumerate() {
+            let declared = metadata::metadata(case.instr);
+            for (output_name, output) in [("native_output", &case.native_output), ("model_output", &case.model_output)] {
+                if output.is_empty() {
+                    continue;
+                }
+                let mut flag = |field_name: &'static str, present: bool, declared_by: &[Flag]| {
+                    if present && !declared_by.iter().any(|flag| declared.writes.contains(flag)) {
+                        issues.push(ValidationIssue {
+                            case_index,
+                            instr: case.instr,
+                            input: case.input,
+                            message: format!(
+                                "{} sets {} but {} doesn't declare writing any of {:?}",
+                                output_name, field_name, case.instr, declared_by
+                            ),
+                        });
+                    }
+                };
+                flag("cr0", output.cr0.is_some(), &[Flag::Cr0]);
+                flag("xer", output.xer.is_some(), &[Flag::So, Flag::Ov, Flag::Ca]);
+                flag("fpscr", output.fpscr.is_some(), &[Flag::Fpscr]);
+            }
+
+            if !case.matches() && case.native_output.diff(&case.model_output).is_empty() {
+                issues.push(ValidationIssue {
+                    case_index,
+                    instr: case.instr,
+                    input: case.input,
+                    message: "matches() reports a mismatch but diff() found no differing field".to_string(),
+                });
+            }
+        }
+        issues
+    }
+}
+
+/// One way a case in a [`WholeTest`] disagreed with its own instruction's
+/// declared metadata, found by [`WholeTest::validate`].
+#[derive(Debug)]
+pub struct ValidationIssue {
+    /// This case's position within [`WholeTest::test_cases`].
+    pub case_index: usize,
+    pub instr: Instr,
+    pub input: InstructionInput,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "case {} ({} {:?}): {}", self.case_index, self.instr, self.input, self.message)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SplitManifestEntry {
+    file: String,
+    count: usize,
+}
+
+/// One instruction's entry in a split capture directory, as produced by
+/// [`WholeTest::write_split`] and found by [`discover_split`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitCaptureFile {
+    pub instr_name: String,
+    pub path: std::path::PathBuf,
+    pub count: usize,
+}
+
+/// Reads `dir`'s `index.json` manifest (as written by
+/// [`WholeTest::write_split`]) and resolves each entry's file name to a
+/// path under `dir`, without reading the JSONL files themselves. Intended
+/// for callers -- e.g. a test harness discovering fixtures to parametrize
+/// over -- that want to know what's there before deciding what to load.
+pub fn discover_split(dir: &Path) -> io::Result<Vec<SplitCaptureFile>> {
+    let manifest_file = fs::File::open(dir.join("index.json"))?;
+    let manifest: BTreeMap<String, SplitManifestEntry> = serde_json::from_reader(manifest_file)?;
+    Ok(manifest
+        .into_iter()
+        .map(|(instr_name, entry)| SplitCaptureFile {
+            instr_name,
+            path: dir.join(entry.file),
+            count: entry.count,
+        })
+        .collect())
+}
+
+/// Reads every [`TestCase`] out of one entry returned by [`discover_split`].
+pub fn load_split_cases(entry: &SplitCaptureFile) -> io::Result<Vec<TestCase>> {
+    let file = fs::File::open(&entry.path)?;
+    read_test_cases_streaming(file).collect::<serde_json::Result<_>>().map_err(io::Error::from)
+}
+
+/// Per-instruction case counts, mismatch rates, and field-level mismatch
+/// histograms computed by [`WholeTest::statistics`]. Shared by the CLI
+/// summary, the HTML report, and (via the same JSON) any other consumer
+/// that wants a campaign's shape without re-deriving it from raw cases.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Statistics {
+    pub per_instruction: BTreeMap<Instr, InstructionStatistics>,
+}
+
+/// One instruction's slice of a [`Statistics`] report.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InstructionStatistics {
+    pub total: usize,
+    /// Cases where [`TestCase::matches`] was `false`. Excludes skipped
+    /// cases -- see `skip_counts`.
+    pub mismatches: usize,
+    /// How many cases disagreed on each field, keyed by [`InstructionOutput`]
+    /// field name. Includes `raw_cr`, even though [`TestCase::matches`]
+    /// doesn't count it towards `mismatches` (it's forensic-only, see
+    /// [`InstructionOutput::raw_cr`]). Excludes skipped cases.
+    pub field_mismatch_counts: BTreeMap<String, usize>,
+    /// How many cases were skipped for each [`SkipReason`], counted instead
+    /// of towards `mismatches`/`field_mismatch_counts`.
+    pub skip_counts: BTreeMap<SkipReason, usize>,
+}
+
+impl InstructionStatistics {
+    /// How many cases were actually run to comparison, i.e. `total` minus
+    /// every skipped case.
+    pub fn ran(&self) -> usize {
+        self.total - self.skip_counts.values().sum::<usize>()
+    }
+
+    /// The fraction of *run* cases where [`TestCase::matches`] was `false`,
+    /// or `0.0` if no cases were run.
+    pub fn mismatch_rate(&self) -> f64 {
+        let ran = self.ran();
+        if ran == 0 {
+            0.0
+        } else {
+            self.mismatches as f64 / ran as f64
+        }
+    }
+}
+
+/// Reads `TestCase`s one at a time from a JSONL-formatted reader (one
+/// `TestCase` per line), so that tools like replay/diff/report can process
+/// capture files too large to hold in memory as a single [`WholeTest`].
+///
+/// Uses [`serde_json::Deserializer`]'s streaming mode rather than splitting
+/// on newlines first, so it also tolerates pretty-printed, multi-line JSON
+/// values.
+pub fn read_test_cases_streaming<R: io::Read>(reader: R) -> impl Iterator<Item = serde_json::Result<TestCase>> {
+    serde_json::Deserializer::from_reader(reader).into_iter::<TestCase>()
+}
+
+/// Like [`read_test_cases_streaming`], but parses directly from `data`
+/// already held in memory (e.g. a memory-mapped or fully-read capture
+/// file) instead of going through the [`io::Read`] trait.
+///
+/// [`TestCase`] has no owned string fields of its own to borrow --
+/// `ra`/`rb`/`rc`/`rt` are plain `u64`s, and every enum
+/// ([`Instr`], [`crate::types::Aliasing`], ...) decodes straight into its
+/// variant rather than into a `String`/`Cow<str>` field -- so there's no
+/// per-field allocation for this to let a caller avoid. What it does avoid
+/// is [`serde_json::Deserializer::from_reader`]'s own internal copying: a
+/// `Read`-based deserializer can't borrow from its source (`Read` hands
+/// back bytes, not a byte slice it can hold a reference into), so it must
+/// buffer every token it parses into scratch space of its own first. A
+/// caller that already has `data` as a contiguous slice skips that copy.
+pub fn read_test_cases_from_slice(data: &[u8]) -> impl Iterator<Item = serde_json::Result<TestCase>> + '_ {
+    serde_json::Deserializer::from_slice(data).into_iter::<TestCase>()
+}
+
+/// Writes `cases` as JSONL (one `TestCase` per line), the counterpart to
+/// [`read_test_cases_streaming`].
+pub fn write_test_cases_streaming<W: Write>(
+    mut writer: W,
+    cases: impl Iterator<Item = TestCase>,
+) -> io::Result<()> {
+    for case in cases {
+        serde_json::to_writer(&mut writer, &case)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Field names whose values should be hex-formatted by [`WholeTest::to_writer`].
+const HEX_FIELD_NAMES: &[&str] = &["ra", "rb", "rc", "rt"];
+
+fn rewrite_hex_fields(value: &mut Value, config: &HexFormatConfig) {
+    match value {
+        Value::Object(map) => {
+            for (key, field_value) in map.iter_mut() {
+                if HEX_FIELD_NAMES.contains(&key.as_str()) {
+                    if let Some(n) = field_value.as_u64() {
+                        *field_value = Value::String(format_hex(n, config));
+                        continue;
+                    }
+                }
+                rewrite_hex_fields(field_value, config);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| rewrite_hex_fields(item, config)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ConditionRegister;
+
+    #[test]
+    fn write_split_is_discoverable_and_loadable() {
+        let dir = std::env::temp_dir().join(format!(
+            "pia-write-split-test-{}",
+            std::process::id()
+        ));
+        let whole_test = WholeTest {
+            test_cases: vec![
+                TestCase {
+                    instr: Instr::Add,
+                    input: InstructionInput::default(),
+                    native_output: InstructionOutput::default(),
+                    model_output: InstructionOutput::default(),
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+                TestCase {
+                    instr: Instr::Subf,
+                    input: InstructionInput { ra: 1, ..InstructionInput::default() },
+                    native_output: InstructionOutput::default(),
+                    model_output: InstructionOutput::default(),
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        whole_test.write_split(&dir).unwrap();
+
+        let mut discovered = discover_split(&dir).unwrap();
+        discovered.sort_by(|a, b| a.instr_name.cmp(&b.instr_name));
+        assert_eq!(discovered.len(), 2);
+        assert_eq!(discovered[0].instr_name, "add");
+        assert_eq!(discovered[0].count, 1);
+        let add_cases = load_split_cases(&discovered[0]).unwrap();
+        assert_eq!(add_cases.len(), 1);
+        assert_eq!(add_cases[0].instr, Instr::Add);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn streaming_round_trip() {
+        let cases = vec![
+            TestCase {
+                instr: Instr::Add,
+                input: InstructionInput::default(),
+                native_output: InstructionOutput::default(),
+                model_output: InstructionOutput::default(),
+                model_revision: 1,
+                skip: None, latency: None,
+            },
+            TestCase {
+                instr: Instr::Subf,
+                input: InstructionInput { ra: 1, ..InstructionInput::default() },
+                native_output: InstructionOutput::default(),
+                model_output: InstructionOutput::default(),
+                model_revision: 1,
+                skip: None, latency: None,
+            },
+        ];
+        let mut buf = Vec::new();
+        write_test_cases_streaming(&mut buf, cases.clone().into_iter()).unwrap();
+        let read_back: Vec<TestCase> = read_test_cases_streaming(buf.as_slice())
+            .collect::<serde_json::Result<_>>()
+            .unwrap();
+        assert_eq!(read_back.len(), cases.len());
+        assert_eq!(read_back[1].input.ra, 1);
+    }
+
+    #[test]
+    fn from_slice_round_trip_matches_from_reader() {
+        let cases = vec![TestCase {
+            instr: Instr::Add,
+            input: InstructionInput { ra: 1, ..InstructionInput::default() },
+            native_output: InstructionOutput::default(),
+            model_output: InstructionOutput::default(),
+            model_revision: 1,
+            skip: None, latency: None,
+        }];
+        let mut buf = Vec::new();
+        write_test_cases_streaming(&mut buf, cases.clone().into_iter()).unwrap();
+        let read_back: Vec<TestCase> =
+            read_test_cases_from_slice(&buf).collect::<serde_json::Result<_>>().unwrap();
+        assert_eq!(read_back.len(), cases.len());
+        assert_eq!(read_back[0].input.ra, 1);
+    }
+
+    #[test]
+    fn statistics_counts_mismatches_per_instruction_and_field() {
+        let matching = InstructionOutput { rt: Some(1), ..InstructionOutput::default() };
+        let disagreeing_rt = InstructionOutput { rt: Some(2), ..InstructionOutput::default() };
+        let whole_test = WholeTest {
+            test_cases: vec![
+                TestCase {
+                    instr: Instr::Add,
+                    input: InstructionInput::default(),
+                    native_output: matching,
+                    model_output: matching,
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+                TestCase {
+                    instr: Instr::Add,
+                    input: InstructionInput { ra: 1, ..InstructionInput::default() },
+                    native_output: matching,
+                    model_output: disagreeing_rt,
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+                TestCase {
+                    instr: Instr::Subf,
+                    input: InstructionInput::default(),
+                    native_output: matching,
+                    model_output: matching,
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+
+        let statistics = whole_test.statistics();
+        let add_stats = &statistics.per_instruction[&Instr::Add];
+        assert_eq!(add_stats.total, 2);
+        assert_eq!(add_stats.mismatches, 1);
+        assert_eq!(add_stats.field_mismatch_counts.get("rt"), Some(&1));
+        assert_eq!(add_stats.mismatch_rate(), 0.5);
+
+        let subf_stats = &statistics.per_instruction[&Instr::Subf];
+        assert_eq!(subf_stats.total, 1);
+        assert_eq!(subf_stats.mismatches, 0);
+        assert!(subf_stats.field_mismatch_counts.is_empty());
+    }
+
+    #[test]
+    fn validate_flags_an_output_field_not_declared_in_the_instructions_metadata() {
+        // `Add` (unlike `AddDot`) doesn't declare writing `Flag::Cr0`.
+        let output = InstructionOutput { cr0: Some(ConditionRegister::default()), ..InstructionOutput::default() };
+        let whole_test = WholeTest {
+            test_cases: vec![TestCase {
+                instr: Instr::Add,
+                input: InstructionInput::default(),
+                native_output: output,
+                model_output: output,
+                model_revision: 1,
+                skip: None, latency: None,
+            }],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        let issues = whole_test.validate();
+        assert_eq!(issues.len(), 2); // both native_output and model_output set it
+        assert!(issues.iter().all(|issue| issue.message.contains("cr0")));
+    }
+
+    #[test]
+    fn validate_accepts_an_output_field_the_instruction_does_declare() {
+        let output = InstructionOutput { cr0: Some(ConditionRegister::default()), ..InstructionOutput::default() };
+        let whole_test = WholeTest {
+            test_cases: vec![TestCase {
+                instr: Instr::AddDot,
+                input: InstructionInput::default(),
+                native_output: output,
+                model_output: output,
+                model_revision: 1,
+                skip: None, latency: None,
+            }],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        assert!(whole_test.validate().is_empty());
+    }
+
+    #[test]
+    fn validate_ignores_a_case_with_no_recorded_output_at_all() {
+        let whole_test = WholeTest {
+            test_cases: vec![TestCase {
+                instr: Instr::Add,
+                input: InstructionInput::default(),
+                native_output: InstructionOutput::default(),
+                model_output: InstructionOutput::default(),
+                model_revision: 1,
+                skip: None, latency: None,
+            }],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        assert!(whole_test.validate().is_empty());
+    }
+
+    fn skipped_case(reason: SkipReason) -> TestCase {
+        TestCase {
+            instr: Instr::Add,
+            input: InstructionInput::default(),
+            native_output: InstructionOutput::default(),
+            model_output: InstructionOutput::default(),
+            model_revision: 1,
+            skip: Some(reason),
+            latency: None,
+        }
+    }
+
+    #[test]
+    fn a_skipped_case_never_matches_even_with_identical_default_outputs() {
+        assert!(!skipped_case(SkipReason::Privileged).matches());
+    }
+
+    #[test]
+    fn mismatches_excludes_skipped_cases() {
+        let whole_test = WholeTest {
+            test_cases: vec![skipped_case(SkipReason::BackendTimeout)],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        assert_eq!(whole_test.mismatches().count(), 0);
+    }
+
+    #[test]
+    fn statistics_counts_skips_separately_from_mismatches_and_excludes_them_from_ran() {
+        let whole_test = WholeTest {
+            test_cases: vec![
+                skipped_case(SkipReason::UnsupportedIsaLevel),
+                skipped_case(SkipReason::UnsupportedIsaLevel),
+                skipped_case(SkipReason::FilteredUndefined),
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        let stats = &whole_test.statistics().per_instruction[&Instr::Add];
+        assert_eq!(stats.total, 3);
+        assert_eq!(stats.ran(), 0);
+        assert_eq!(stats.mismatches, 0);
+        assert_eq!(stats.skip_counts[&SkipReason::UnsupportedIsaLevel], 2);
+        assert_eq!(stats.skip_counts[&SkipReason::FilteredUndefined], 1);
+        assert_eq!(stats.mismatch_rate(), 0.0);
+    }
+
+    fn mismatching_case(instr: Instr, rt: i64) -> TestCase {
+        TestCase {
+            instr,
+            input: InstructionInput::default(),
+            native_output: InstructionOutput { rt: Some(rt as u64), ..InstructionOutput::default() },
+            model_output: InstructionOutput::default(),
+            model_revision: 1,
+            skip: None,
+            latency: None,
+        }
+    }
+
+    #[test]
+    fn truncate_mismatches_per_instr_keeps_only_the_first_n_mismatches_per_instruction() {
+        let mut whole_test = WholeTest {
+            test_cases: vec![
+                mismatching_case(Instr::Add, 1),
+                mismatching_case(Instr::Add, 2),
+                mismatching_case(Instr::Add, 3),
+                mismatching_case(Instr::Subf, 1),
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        let dropped = whole_test.truncate_mismatches_per_instr(2);
+        assert_eq!(dropped, BTreeMap::from([(Instr::Add, 1)]));
+        assert_eq!(whole_test.test_cases.len(), 3);
+        assert_eq!(whole_test.mismatches().filter(|case| case.instr == Instr::Add).count(), 2);
+        assert_eq!(whole_test.mismatches().filter(|case| case.instr == Instr::Subf).count(), 1);
+    }
+
+    #[test]
+    fn truncate_mismatches_per_instr_leaves_matching_and_skipped_cases_alone() {
+        let matching = TestCase {
+            instr: Instr::Add,
+            input: InstructionInput::default(),
+            native_output: InstructionOutput::default(),
+            model_output: InstructionOutput::default(),
+            model_revision: 1,
+            skip: None,
+            latency: None,
+        };
+        let mut whole_test = WholeTest {
+            test_cases: vec![matching.clone(), skipped_case(SkipReason::BackendTimeout), mismatching_case(Instr::Add, 1)],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        let dropped = whole_test.truncate_mismatches_per_instr(0);
+        assert_eq!(dropped, BTreeMap::from([(Instr::Add, 1)]));
+        assert_eq!(whole_test.test_cases.len(), 2);
+    }
+}
@@ -0,0 +1,155 @@
+//! A sidecar index over a JSONL capture file, for looking up cases by
+//! `(instr, ra)` without parsing every line -- e.g. `pia query`. The index
+//! itself is still built by one full pass over the file (there's no way
+//! around that for an unindexed file), but it only has to be built once;
+//! every query after that is a binary search plus reading just the
+//! matching lines, via [`memmap2`] so the OS pages in only what's touched.
+
+use crate::capture::TestCase;
+use crate::instr::Instr;
+use crate::types::InstructionInput;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// One [`TestCase`]'s position within its capture file.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub instr: Instr,
+    pub input: InstructionInput,
+    pub offset: u64,
+    pub length: u32,
+}
+
+/// An index over one capture file's [`TestCase`]s.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CaptureIndex {
+    /// Sorted by `(instr, input)`, i.e. by `(instr, ra, rb, rc, ...)` since
+    /// that's the field order [`InstructionInput`]'s derived `Ord` compares
+    /// in -- so every entry for a given `(instr, ra)` forms one contiguous
+    /// run, regardless of what else in `input` differs between them, and
+    /// [`query`] can binary-search straight to it.
+    pub entries: Vec<IndexEntry>,
+}
+
+/// Scans `capture` (the full contents of a JSONL capture file) once,
+/// recording each line's instruction, input, and byte range.
+pub fn build_index(capture: &[u8]) -> serde_json::Result<CaptureIndex> {
+    let mut entries = Vec::new();
+    let mut offset = 0usize;
+    for line in capture.split_inclusive(|&byte| byte == b'\n') {
+        let trimmed = line.strip_suffix(b"\n").unwrap_or(line);
+        if !trimmed.iter().all(u8::is_ascii_whitespace) {
+            let case: TestCase = serde_json::from_slice(trimmed)?;
+            entries.push(IndexEntry { instr: case.instr, input: case.input, offset: offset as u64, length: trimmed.len() as u32 });
+        }
+        offset += line.len();
+    }
+    entries.sort_by_key(|entry| (entry.instr, entry.input));
+    Ok(CaptureIndex { entries })
+}
+
+/// Writes `index` to `path` as JSON, the sidecar file [`read_index`] reads
+/// back.
+pub fn write_index(index: &CaptureIndex, path: &Path) -> io::Result<()> {
+    let file = fs::File::create(path)?;
+    serde_json::to_writer(file, index).map_err(io::Error::from)
+}
+
+/// Reads an index previously written by [`write_index`].
+pub fn read_index(path: &Path) -> io::Result<CaptureIndex> {
+    let file = fs::File::open(path)?;
+    serde_json::from_reader(file).map_err(io::Error::from)
+}
+
+/// The sidecar index path [`write_index`]/[`read_index`] use by default for
+/// a given capture file path, e.g. `capture.jsonl` -> `capture.jsonl.idx`.
+pub fn default_index_path(capture_path: &Path) -> std::path::PathBuf {
+    let mut path = capture_path.as_os_str().to_owned();
+    path.push(".idx");
+    path.into()
+}
+
+/// Binary-searches `index` for every entry matching `instr` and `ra`, then
+/// reads just those [`TestCase`]s out of `capture` (the full mapped/read
+/// contents of the indexed file) -- `O(log n)` to locate the matching run,
+/// `O(k)` to read the `k` matches, without parsing any other line.
+pub fn query(index: &CaptureIndex, capture: &[u8], instr: Instr, ra: u64) -> serde_json::Result<Vec<TestCase>> {
+    let key = |entry: &IndexEntry| (entry.instr, entry.input.ra);
+    let start = index.entries.partition_point(|entry| key(entry) < (instr, ra));
+    let end = index.entries.partition_point(|entry| key(entry) <= (instr, ra));
+    index.entries[start..end]
+        .iter()
+        .map(|entry| serde_json::from_slice(&capture[entry.offset as usize..entry.offset as usize + entry.length as usize]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::InstructionOutput;
+
+    fn jsonl(cases: &[TestCase]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for case in cases {
+            serde_json::to_writer(&mut buf, case).unwrap();
+            buf.push(b'\n');
+        }
+        buf
+    }
+
+    fn case(instr: Instr, ra: u64) -> TestCase {
+        TestCase {
+            instr,
+            input: InstructionInput { ra, ..InstructionInput::default() },
+            native_output: InstructionOutput::default(),
+            model_output: InstructionOutput::default(),
+            model_revision: 1,
+            skip: None, latency: None,
+        }
+    }
+
+    #[test]
+    fn build_index_covers_every_line() {
+        let cases = vec![case(Instr::Add, 1), case(Instr::Subf, 2), case(Instr::Add, 3)];
+        let index = build_index(&jsonl(&cases)).unwrap();
+        assert_eq!(index.entries.len(), 3);
+    }
+
+    #[test]
+    fn query_finds_only_the_matching_instr_and_ra() {
+        let cases = vec![case(Instr::Add, 1), case(Instr::Subf, 1), case(Instr::Add, 2), case(Instr::Add, 1)];
+        let capture = jsonl(&cases);
+        let index = build_index(&capture).unwrap();
+
+        let found = query(&index, &capture, Instr::Add, 1).unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|case| case.instr == Instr::Add && case.input.ra == 1));
+    }
+
+    #[test]
+    fn query_returns_nothing_for_an_absent_key() {
+        let capture = jsonl(&[case(Instr::Add, 1)]);
+        let index = build_index(&capture).unwrap();
+        assert!(query(&index, &capture, Instr::Add, 99).unwrap().is_empty());
+    }
+
+    #[test]
+    fn write_and_read_index_round_trips() {
+        let capture = jsonl(&[case(Instr::Add, 1), case(Instr::Subf, 2)]);
+        let index = build_index(&capture).unwrap();
+        let dir = std::env::temp_dir().join(format!("pia-capture-index-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("capture.jsonl.idx");
+        write_index(&index, &path).unwrap();
+        let read_back = read_index(&path).unwrap();
+        assert_eq!(read_back.entries.len(), index.entries.len());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn default_index_path_appends_idx() {
+        assert_eq!(default_index_path(Path::new("capture.jsonl")), Path::new("capture.jsonl.idx"));
+    }
+}
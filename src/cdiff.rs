@@ -0,0 +1,147 @@
+//! Differential testing against a C compiler's own constant folding: for
+//! each instruction [`crate::expr`] covers, compiles and runs a tiny C
+//! program that computes `rt` from constant `ra`/`rb` using the matching
+//! [`crate::expr::Expr::to_c`] expression, and checks the printed result
+//! against [`crate::expr::Expr::eval`]. Building with optimizations on
+//! means the value actually printed is (for instructions simple enough
+//! that the compiler folds them outright) the compiler's own constant-fold
+//! result, so a mismatch points at either our model or the compiler,
+//! whichever is wrong.
+
+use crate::expr;
+use crate::instr::Instr;
+use std::fmt;
+use std::io;
+use std::process::Command;
+
+#[derive(Debug)]
+pub enum Error {
+    /// `instr` isn't covered by [`crate::expr::rt_expr`], so there's no C
+    /// expression to check it against.
+    Unsupported(Instr),
+    Io(io::Error),
+    /// The compiler invocation exited non-zero; holds its stderr.
+    CompileFailed(String),
+    /// The compiled program's stdout wasn't the `u64` decimal line expected.
+    UnexpectedOutput(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Unsupported(instr) => {
+                write!(f, "{} has no C expression to differential-test against", instr)
+            }
+            Error::Io(err) => write!(f, "failed to run the C compiler: {}", err),
+            Error::CompileFailed(stderr) => write!(f, "C compiler failed:\n{}", stderr),
+            Error::UnexpectedOutput(stdout) => {
+                write!(f, "compiled program printed unexpected output: {:?}", stdout)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+fn render_c_source(instr: Instr, ra: u64, rb: u64) -> Option<String> {
+    let c_expr = expr::rt_expr(instr)?.to_c();
+    Some(format!(
+        "#include <stdint.h>\n\
+         #include <stdio.h>\n\
+         int main(void) {{\n\
+         \x20   uint64_t ra = {ra}ULL;\n\
+         \x20   uint64_t rb = {rb}ULL;\n\
+         \x20   uint64_t rt = {c_expr};\n\
+         \x20   printf(\"%llu\\n\", (unsigned long long)rt);\n\
+         \x20   return 0;\n\
+         }}\n",
+        ra = ra,
+        rb = rb,
+        c_expr = c_expr,
+    ))
+}
+
+/// Compiles and runs a tiny C program computing `instr`'s `rt` from the
+/// constants `ra`/`rb` using `compiler` (e.g. `"cc"`, `"gcc"`, `"clang"`)
+/// at `-O2`, and checks its output against [`crate::expr::Expr::eval`].
+///
+/// Returns `Ok(())` if they agree, `Err` describing the disagreement (or
+/// any compiler/IO failure) otherwise.
+pub fn check_constant_folding(instr: Instr, ra: u64, rb: u64, compiler: &str) -> Result<(), Error> {
+    let expr = expr::rt_expr(instr).ok_or(Error::Unsupported(instr))?;
+    let expected = expr.eval(ra, rb);
+    let source = render_c_source(instr, ra, rb).ok_or(Error::Unsupported(instr))?;
+
+    let dir = std::env::temp_dir().join(format!("pia-cdiff-{}-{}", std::process::id(), instr));
+    std::fs::create_dir_all(&dir)?;
+    let source_path = dir.join("case.c");
+    let binary_path = dir.join("case");
+    std::fs::write(&source_path, source)?;
+
+    let compile = Command::new(compiler)
+        .arg("-O2")
+        .arg("-o")
+        .arg(&binary_path)
+        .arg(&source_path)
+        .output()?;
+    if !compile.status.success() {
+        let _ = std::fs::remove_dir_all(&dir);
+        return Err(Error::CompileFailed(String::from_utf8_lossy(&compile.stderr).into_owned()));
+    }
+
+    let run = Command::new(&binary_path).output()?;
+    let _ = std::fs::remove_dir_all(&dir);
+    let stdout = String::from_utf8_lossy(&run.stdout).into_owned();
+    let actual: u64 = stdout
+        .trim()
+        .parse()
+        .map_err(|_| Error::UnexpectedOutput(stdout.clone()))?;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(Error::UnexpectedOutput(format!(
+            "compiler printed {}, but the model computes {}",
+            actual, expected
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cc_available() -> bool {
+        Command::new("cc").arg("--version").output().is_ok()
+    }
+
+    #[test]
+    fn unsupported_instructions_are_rejected_before_invoking_the_compiler() {
+        assert!(matches!(
+            check_constant_folding(Instr::Cfuged, 0, 0, "cc"),
+            Err(Error::Unsupported(Instr::Cfuged))
+        ));
+    }
+
+    #[test]
+    fn cc_agrees_with_the_model_on_addition() {
+        if !cc_available() {
+            return;
+        }
+        check_constant_folding(Instr::Add, 1, 2, "cc").unwrap();
+        check_constant_folding(Instr::Add, u64::MAX, 1, "cc").unwrap();
+    }
+
+    #[test]
+    fn cc_agrees_with_the_model_on_high_multiplication() {
+        if !cc_available() {
+            return;
+        }
+        check_constant_folding(Instr::Mulhdu, u64::MAX, u64::MAX, "cc").unwrap();
+    }
+}
@@ -0,0 +1,263 @@
+//! Turns an archived [`WholeTest`] capture into an executable regression
+//! suite: re-runs the current model (and, where recorded, native execution)
+//! over each case and reports any field that no longer agrees with what
+//! was recorded, i.e. a golden-output assertion mode.
+
+use crate::capture::WholeTest;
+use crate::instr::Instr;
+use crate::metadata;
+use crate::model;
+use crate::native;
+use crate::types::{InstructionInput, InstructionOutput, Xer};
+use std::fmt;
+
+/// Which fields participate in a golden-output comparison.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ComparisonProfile {
+    /// Compare every field this crate records.
+    Full,
+    /// Ignore `xer.ca32`/`xer.ov32`, for captures from hardware or
+    /// simulators that predate ISA 3.0 and never set them, so they can
+    /// still be checked meaningfully against the rest of a case's output.
+    Legacy32,
+    /// Compare NaN payloads and the sign of a zero result loosely, for
+    /// cores/simulators that legitimately differ in which NaN payload or
+    /// which zero sign they produce.
+    ///
+    /// No currently-modeled instruction has an FPR/float result to carry
+    /// either of those (this crate's only `FPSCR`-touching instructions,
+    /// [`crate::instr::Instr::Mffscrn`] and [`crate::instr::Instr::Mffsce`],
+    /// don't round or produce a float result), so this compares identically
+    /// to [`ComparisonProfile::Full`] today -- it's accepted here so a
+    /// future float-producing instruction's output only has to be
+    /// normalized in [`for_profile`], not also threaded through from
+    /// scratch.
+    FpLoose,
+}
+
+impl fmt::Display for ComparisonProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ComparisonProfile::Full => "full",
+            ComparisonProfile::Legacy32 => "legacy32",
+            ComparisonProfile::FpLoose => "fp_loose",
+        })
+    }
+}
+
+/// Which recorded output a [`FieldMismatch`] was found against.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Source {
+    Model,
+    Native,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Source::Model => "model",
+            Source::Native => "native",
+        })
+    }
+}
+
+/// One field that disagreed between the recorded output and the output
+/// recomputed against the current model/hardware.
+#[derive(Debug)]
+pub struct FieldMismatch {
+    pub instr: Instr,
+    pub input: InstructionInput,
+    pub source: Source,
+    pub field: &'static str,
+    pub recorded: String,
+    pub recomputed: String,
+    /// Set for [`Source::Model`] mismatches where the capture's
+    /// [`crate::capture::TestCase::model_revision`] no longer matches
+    /// [`metadata::model_revision`] -- the model has changed on purpose
+    /// since this case was recorded, so the mismatch may just be stale
+    /// rather than a regression. Always `None` for [`Source::Native`],
+    /// which has no revision of its own to compare.
+    pub model_revision_changed: Option<(u32, u32)>,
+}
+
+impl fmt::Display for FieldMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?}: {}.{} recorded {} but recomputed {}",
+            self.instr, self.input, self.source, self.field, self.recorded, self.recomputed
+        )?;
+        if let Some((recorded, current)) = self.model_revision_changed {
+            write!(f, " (model revision {} at capture time, now {} -- may be a deliberate change)", recorded, current)?;
+        }
+        Ok(())
+    }
+}
+
+/// Re-runs every case in `golden` and reports every field that disagrees
+/// with what was recorded, under [`ComparisonProfile::Full`]. Native
+/// outputs are only re-checked for cases that recorded one in the first
+/// place (see [`InstructionOutput::is_empty`]).
+pub fn check_golden(golden: &WholeTest) -> Vec<FieldMismatch> {
+    check_golden_with_profile(golden, ComparisonProfile::Full)
+}
+
+/// Like [`check_golden`], but compares under `profile`, e.g.
+/// [`ComparisonProfile::Legacy32`] for captures that never recorded
+/// `ca32`/`ov32`.
+pub fn check_golden_with_profile(golden: &WholeTest, profile: ComparisonProfile) -> Vec<FieldMismatch> {
+    let mut mismatches = Vec::new();
+    for case in &golden.test_cases {
+        let recomputed_model = model::model(case.instr, case.input);
+        let before = mismatches.len();
+        push_field_mismatches(&mut mismatches, case.instr, case.input, Source::Model, &case.model_output, &recomputed_model, profile);
+        let current_revision = metadata::model_revision(case.instr);
+        if case.model_revision != 0 && case.model_revision != current_revision {
+            for mismatch in &mut mismatches[before..] {
+                mismatch.model_revision_changed = Some((case.model_revision, current_revision));
+            }
+        }
+
+        if !case.native_output.is_empty() {
+            match native::execute(case.instr, case.input) {
+                Ok(recomputed_native) => push_field_mismatches(
+                    &mut mismatches,
+                    case.instr,
+                    case.input,
+                    Source::Native,
+                    &case.native_output,
+                    &recomputed_native,
+                    profile,
+                ),
+                Err(err) => eprintln!("check: skipping native re-check of {}: {}", case.instr, err),
+            }
+        }
+    }
+    mismatches
+}
+
+fn push_field_mismatches(
+    mismatches: &mut Vec<FieldMismatch>,
+    instr: Instr,
+    input: InstructionInput,
+    source: Source,
+    recorded: &InstructionOutput,
+    recomputed: &InstructionOutput,
+    profile: ComparisonProfile,
+) {
+    let recorded = InstructionOutput { xer: for_profile(recorded.xer, profile), ..*recorded };
+    let recomputed = InstructionOutput { xer: for_profile(recomputed.xer, profile), ..*recomputed };
+    for (field, (recorded, recomputed)) in recorded.diff(&recomputed) {
+        mismatches.push(FieldMismatch { instr, input, source, field, recorded, recomputed, model_revision_changed: None });
+    }
+}
+
+/// Applies `profile` to `xer` before it's compared/displayed, clearing
+/// `ca32`/`ov32` under [`ComparisonProfile::Legacy32`] so a capture that
+/// never recorded them doesn't spuriously disagree with a model/native
+/// result that does.
+fn for_profile(xer: Option<Xer>, profile: ComparisonProfile) -> Option<Xer> {
+    match profile {
+        ComparisonProfile::Full => xer,
+        ComparisonProfile::Legacy32 => xer.map(|xer| Xer { ca32: false, ov32: false, ..xer }),
+        ComparisonProfile::FpLoose => xer,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output_with_xer(xer: Xer) -> InstructionOutput {
+        InstructionOutput { xer: Some(xer), ..InstructionOutput::default() }
+    }
+
+    #[test]
+    fn legacy32_ignores_ca32_and_ov32_but_not_other_xer_bits() {
+        let recorded = output_with_xer(Xer { ca32: true, ov32: true, ..Xer::default() });
+        let recomputed = output_with_xer(Xer::default());
+        let input = InstructionInput::default();
+
+        let mut full = Vec::new();
+        push_field_mismatches(&mut full, Instr::Add, input, Source::Model, &recorded, &recomputed, ComparisonProfile::Full);
+        assert_eq!(full.len(), 1);
+
+        let mut legacy32 = Vec::new();
+        push_field_mismatches(
+            &mut legacy32,
+            Instr::Add,
+            input,
+            Source::Model,
+            &recorded,
+            &recomputed,
+            ComparisonProfile::Legacy32,
+        );
+        assert!(legacy32.is_empty());
+    }
+
+    #[test]
+    fn legacy32_still_catches_a_disagreeing_ca_bit() {
+        let recorded = output_with_xer(Xer { ca: true, ..Xer::default() });
+        let recomputed = output_with_xer(Xer::default());
+        let input = InstructionInput::default();
+
+        let mut legacy32 = Vec::new();
+        push_field_mismatches(
+            &mut legacy32,
+            Instr::Add,
+            input,
+            Source::Model,
+            &recorded,
+            &recomputed,
+            ComparisonProfile::Legacy32,
+        );
+        assert_eq!(legacy32.len(), 1);
+    }
+
+    #[test]
+    fn fp_loose_displays_as_a_stable_name() {
+        // No field this crate records carries a NaN payload or a zero's
+        // sign yet, so there's nothing for `FpLoose` to normalize away --
+        // this just pins its `Display` string, since that's what gets
+        // printed as the active profile.
+        assert_eq!(ComparisonProfile::FpLoose.to_string(), "fp_loose");
+    }
+
+    fn stale_case(model_revision: u32) -> crate::capture::TestCase {
+        crate::capture::TestCase {
+            instr: Instr::Add,
+            input: InstructionInput::default(),
+            native_output: InstructionOutput::default(),
+            model_output: InstructionOutput { rt: Some(1), ..InstructionOutput::default() },
+            model_revision,
+            skip: None, latency: None,
+        }
+    }
+
+    #[test]
+    fn a_mismatch_against_an_outdated_model_revision_is_flagged_as_possibly_deliberate() {
+        let golden = WholeTest { test_cases: vec![stale_case(metadata::model_revision(Instr::Add) + 1)], ..WholeTest::default() };
+        let mismatches = check_golden(&golden);
+        assert_eq!(mismatches.len(), 1);
+        assert!(mismatches[0].model_revision_changed.is_some());
+    }
+
+    #[test]
+    fn a_mismatch_with_the_current_model_revision_is_not_flagged() {
+        let golden = WholeTest { test_cases: vec![stale_case(metadata::model_revision(Instr::Add))], ..WholeTest::default() };
+        let mismatches = check_golden(&golden);
+        assert_eq!(mismatches.len(), 1);
+        assert!(mismatches[0].model_revision_changed.is_none());
+    }
+
+    #[test]
+    fn an_untracked_revision_of_zero_is_not_flagged_as_a_deliberate_change() {
+        // A capture predating this field deserializes `model_revision` as
+        // `0` -- it should read as "unknown", not as every current
+        // revision being a deliberate change from it.
+        let golden = WholeTest { test_cases: vec![stale_case(0)], ..WholeTest::default() };
+        let mismatches = check_golden(&golden);
+        assert_eq!(mismatches.len(), 1);
+        assert!(mismatches[0].model_revision_changed.is_none());
+    }
+}
@@ -0,0 +1,142 @@
+//! Groups mismatching cases by probable root cause -- which output fields
+//! disagree and simple sign/zero predicates on the inputs -- so a report
+//! over thousands of mismatches can show one representative example per
+//! cluster instead of making a reader scroll past near-duplicates to find
+//! the distinct bugs hiding among them.
+
+use crate::capture::{TestCase, WholeTest};
+use std::collections::BTreeMap;
+
+/// The sign of an input operand, as a coarse predicate worth clustering
+/// mismatches on (e.g. "only fails when `ra` is negative").
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Sign {
+    Zero,
+    Negative,
+    Positive,
+}
+
+impl Sign {
+    fn of(value: u64) -> Self {
+        match value as i64 {
+            0 => Sign::Zero,
+            n if n < 0 => Sign::Negative,
+            _ => Sign::Positive,
+        }
+    }
+}
+
+/// What a cluster of mismatches has in common: the set of output fields
+/// that disagreed (see [`crate::types::InstructionOutput::diff`]) and each
+/// input's sign, the two cheapest signals that tend to separate genuinely
+/// distinct bugs from repeats of the same one.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub struct ClusterKey {
+    pub differing_fields: Vec<&'static str>,
+    pub ra_sign: Sign,
+    pub rb_sign: Sign,
+}
+
+impl ClusterKey {
+    fn of(case: &TestCase) -> Self {
+        let differing_fields = case.native_output.diff(&case.model_output).into_keys().collect();
+        ClusterKey { differing_fields, ra_sign: Sign::of(case.input.ra), rb_sign: Sign::of(case.input.rb) }
+    }
+}
+
+/// One cluster of mismatches sharing a [`ClusterKey`]: how many cases fell
+/// into it, and one representative example (the first encountered, in
+/// `whole_test`'s existing order) to show in place of all of them.
+#[derive(Clone, Debug)]
+pub struct Cluster<'a> {
+    pub key: ClusterKey,
+    pub count: usize,
+    pub example: &'a TestCase,
+}
+
+/// Clusters every mismatching case in `whole_test` (see
+/// [`WholeTest::mismatches`]) by [`ClusterKey`], sorted by descending
+/// cluster size so the most common (and so most likely systemic) cause of
+/// divergence comes first.
+pub fn cluster_mismatches(whole_test: &WholeTest) -> Vec<Cluster<'_>> {
+    let mut by_key: BTreeMap<ClusterKey, Cluster<'_>> = BTreeMap::new();
+    for case in whole_test.mismatches() {
+        by_key
+            .entry(ClusterKey::of(case))
+            .and_modify(|cluster| cluster.count += 1)
+            .or_insert_with(|| Cluster { key: ClusterKey::of(case), count: 1, example: case });
+    }
+    let mut clusters: Vec<Cluster<'_>> = by_key.into_values().collect();
+    clusters.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instr::Instr;
+    use crate::types::{InstructionInput, InstructionOutput};
+
+    fn case(ra: u64, rb: u64, native_rt: u64, model_rt: u64) -> TestCase {
+        TestCase {
+            instr: Instr::Add,
+            input: InstructionInput { ra, rb, ..InstructionInput::default() },
+            native_output: InstructionOutput { rt: Some(native_rt), ..InstructionOutput::default() },
+            model_output: InstructionOutput { rt: Some(model_rt), ..InstructionOutput::default() },
+            model_revision: 1,
+            skip: None,
+            latency: None,
+        }
+    }
+
+    fn case_with_ca_divergence(ra: u64, rb: u64) -> TestCase {
+        use crate::types::Xer;
+        TestCase {
+            instr: Instr::Add,
+            input: InstructionInput { ra, rb, ..InstructionInput::default() },
+            native_output: InstructionOutput { xer: Some(Xer { ca: true, ..Xer::default() }), ..InstructionOutput::default() },
+            model_output: InstructionOutput { xer: Some(Xer::default()), ..InstructionOutput::default() },
+            model_revision: 1,
+            skip: None,
+            latency: None,
+        }
+    }
+
+    fn whole_test(test_cases: Vec<TestCase>) -> WholeTest {
+        WholeTest { test_cases, pinning: None, host_endianness: None, host_info: None }
+    }
+
+    #[test]
+    fn sign_of_classifies_zero_negative_and_positive() {
+        assert_eq!(Sign::of(0), Sign::Zero);
+        assert_eq!(Sign::of(1), Sign::Positive);
+        assert_eq!(Sign::of(u64::MAX), Sign::Negative); // -1 as i64
+    }
+
+    #[test]
+    fn identical_divergences_on_inputs_with_the_same_signs_share_a_cluster() {
+        let whole_test = whole_test(vec![
+            case(1, 2, 0, 1),
+            case(3, 4, 0, 1),
+            case_with_ca_divergence(5, 6), // disagrees on a different field -> different cluster
+        ]);
+        let clusters = cluster_mismatches(&whole_test);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].count, 2);
+        assert_eq!(clusters[1].count, 1);
+    }
+
+    #[test]
+    fn differing_ra_sign_splits_an_otherwise_identical_divergence_into_separate_clusters() {
+        let whole_test = whole_test(vec![case(1, 2, 0, 1), case(u64::MAX, 2, 0, 1)]);
+        let clusters = cluster_mismatches(&whole_test);
+        assert_eq!(clusters.len(), 2);
+        assert!(clusters.iter().all(|cluster| cluster.count == 1));
+    }
+
+    #[test]
+    fn matching_cases_are_excluded_from_every_cluster() {
+        let whole_test = whole_test(vec![case(1, 2, 5, 5)]);
+        assert!(cluster_mismatches(&whole_test).is_empty());
+    }
+}
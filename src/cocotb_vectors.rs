@@ -0,0 +1,141 @@
+//! Exports per-instruction Python test-vector modules in the
+//! `(inputs, outputs)`-dict-pair shape Libre-SOC's nmigen/cocotb ALU
+//! testbenches import directly, generated from a [`WholeTest`] capture (or,
+//! via [`crate::fill`], straight from the model) so those testbenches never
+//! hand-transcribe expected values out of a JSON capture.
+
+use crate::capture::{TestCase, WholeTest};
+use crate::instr::Instr;
+use crate::types::{ConditionRegister, InstructionOutput, Xer};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+fn python_bool(value: bool) -> &'static str {
+    if value {
+        "True"
+    } else {
+        "False"
+    }
+}
+
+fn render_cr0(cr0: Option<ConditionRegister>) -> String {
+    match cr0 {
+        None => "None".to_string(),
+        Some(cr0) => format!(
+            "{{\"lt\": {}, \"gt\": {}, \"eq\": {}, \"so\": {}}}",
+            python_bool(cr0.lt),
+            python_bool(cr0.gt),
+            python_bool(cr0.eq),
+            python_bool(cr0.so)
+        ),
+    }
+}
+
+fn render_xer(xer: Option<Xer>) -> String {
+    match xer {
+        None => "None".to_string(),
+        Some(xer) => format!(
+            "{{\"so\": {}, \"ov\": {}, \"ca\": {}, \"ov32\": {}, \"ca32\": {}}}",
+            python_bool(xer.so),
+            python_bool(xer.ov),
+            python_bool(xer.ca),
+            python_bool(xer.ov32),
+            python_bool(xer.ca32)
+        ),
+    }
+}
+
+fn render_inputs(case: &TestCase) -> String {
+    format!(
+        "{{\"ra\": {:#x}, \"rb\": {:#x}, \"rc\": {:#x}, \"cr0\": {}, \"xer\": {}}}",
+        case.input.ra,
+        case.input.rb,
+        case.input.rc,
+        render_cr0(Some(case.input.cr0)),
+        render_xer(Some(case.input.xer)),
+    )
+}
+
+fn render_outputs(output: &InstructionOutput) -> String {
+    format!(
+        "{{\"rt\": {}, \"cr0\": {}, \"xer\": {}}}",
+        output.rt.map_or_else(|| "None".to_string(), |rt| format!("{:#x}", rt)),
+        render_cr0(output.cr0),
+        render_xer(output.xer),
+    )
+}
+
+fn render_module(instr: Instr, cases: &[&TestCase]) -> String {
+    let mut module = String::new();
+    let _ = writeln!(module, "# Generated by power-instruction-analyzer. Do not edit by hand.");
+    let _ = writeln!(module, "# Test vectors for {}, as (inputs, outputs) dict pairs.", instr);
+    let _ = writeln!(module);
+    let _ = writeln!(module, "TEST_VECTORS = [");
+    for case in cases {
+        let _ = writeln!(module, "    ({}, {}),", render_inputs(case), render_outputs(&case.model_output));
+    }
+    let _ = writeln!(module, "]");
+    module
+}
+
+/// Writes one `<mnemonic>.py` module per instruction present in `golden`
+/// into `dir` (created if missing), each holding a `TEST_VECTORS` list of
+/// `(inputs, outputs)` pairs built from that instruction's model output.
+pub fn export(golden: &WholeTest, dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut by_instr: BTreeMap<Instr, Vec<&TestCase>> = BTreeMap::new();
+    for case in &golden.test_cases {
+        by_instr.entry(case.instr).or_default().push(case);
+    }
+    for (instr, cases) in by_instr {
+        let path = dir.join(format!("{}.py", instr));
+        fs::write(path, render_module(instr, &cases))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::InstructionInput;
+
+    #[test]
+    fn writes_one_module_per_instruction() {
+        let dir = std::env::temp_dir().join(format!("pia-cocotb-vectors-test-{}", std::process::id()));
+        let golden = WholeTest {
+            test_cases: vec![
+                TestCase {
+                    instr: Instr::Add,
+                    input: InstructionInput { ra: 1, ..InstructionInput::default() },
+                    native_output: InstructionOutput::default(),
+                    model_output: InstructionOutput { rt: Some(1), ..InstructionOutput::default() },
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+                TestCase {
+                    instr: Instr::Subf,
+                    input: InstructionInput::default(),
+                    native_output: InstructionOutput::default(),
+                    model_output: InstructionOutput::default(),
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+
+        export(&golden, &dir).unwrap();
+        let add_module = fs::read_to_string(dir.join("add.py")).unwrap();
+        assert!(add_module.contains("TEST_VECTORS = ["));
+        assert!(add_module.contains("\"ra\": 0x1"));
+        assert!(add_module.contains("\"rt\": 0x1"));
+        assert!(dir.join("subf.py").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
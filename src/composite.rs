@@ -0,0 +1,67 @@
+//! Microcode decompositions -- short [`Program`]s implementing an
+//! operation the POWER ISA has no single instruction for, built out of
+//! ones it does -- exported as named pseudo-instructions so Libre-SOC
+//! microcode using the same decomposition has a reference to check against
+//! instead of every call site hand-rolling (and possibly drifting from)
+//! the same [`Program`].
+
+use crate::instr::Instr;
+use crate::program::{Operand, Program, Reg, UnboundReg};
+
+/// One composite operation: a [`Program`] computing it, plus which [`Reg`]
+/// holds the final result.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompositeOp {
+    pub program: Program,
+    pub result: Reg,
+}
+
+impl CompositeOp {
+    /// Runs `self.program` through the model and reads back `self.result`.
+    pub fn run_model(&self) -> Result<u64, UnboundReg> {
+        let program_result = self.program.run_model()?;
+        Ok(program_result.registers.get(&self.result).copied().unwrap_or(0))
+    }
+}
+
+/// `imm - ra`: "subtract from immediate", the arithmetic `subfic` performs
+/// (without `subfic`'s carry-out, which this doesn't model). Built as a
+/// single `subf`, since `subf(ra, rb)` already computes `rb - ra`.
+pub fn subtract_from_immediate(ra: impl Into<Operand>, imm: impl Into<Operand>) -> CompositeOp {
+    let result = Reg(0);
+    CompositeOp { program: Program::new().push(Instr::Subf, ra, imm, result), result }
+}
+
+/// `-(ra * rb) + rc`: a negate-multiply-add, as Libre-SOC microcode
+/// decomposes it -- a plain multiply followed by a subtract-from, since
+/// there's no single POWER instruction for it.
+pub fn negate_multiply_add(ra: impl Into<Operand>, rb: impl Into<Operand>, rc: impl Into<Operand>) -> CompositeOp {
+    let product = Reg(0);
+    let result = Reg(1);
+    let program =
+        Program::new().push(Instr::Mulld, ra, rb, product).push(Instr::Subf, product, rc, result);
+    CompositeOp { program, result }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subtract_from_immediate_computes_imm_minus_ra() {
+        assert_eq!(subtract_from_immediate(3u64, 10u64).run_model().unwrap(), 7);
+    }
+
+    #[test]
+    fn negate_multiply_add_computes_rc_minus_ra_times_rb() {
+        // -(3 * 4) + 20 = 8
+        assert_eq!(negate_multiply_add(3u64, 4u64, 20u64).run_model().unwrap(), 8);
+    }
+
+    #[test]
+    fn negate_multiply_add_wraps_like_the_underlying_instructions_on_overflow() {
+        let result = negate_multiply_add(u64::MAX, 2u64, 0u64).run_model().unwrap();
+        // product = MAX * 2 wraps to u64::MAX - 1; 0 - (MAX - 1) wraps to 2.
+        assert_eq!(result, 2);
+    }
+}
@@ -0,0 +1,209 @@
+//! A curated catalog of named corner-case inputs per instruction -- the
+//! handful of boundary values (signed overflow, carry-out, divide-by-zero,
+//! `MIN`/`-1` division) known to be where HDL bugs actually hide, rather
+//! than [`crate::campaign::exhaustive_cases`]'s generic boundary sweep.
+//! Meant for `pia farm --corner-cases-only`: running just these takes
+//! seconds, for a quick hardware sanity check before committing to a full
+//! exhaustive campaign.
+
+use crate::instr::Instr;
+use crate::types::{InstructionInput, Xer};
+
+/// One named corner case: a human-readable reason it's interesting, plus
+/// the input that exercises it.
+#[derive(Clone, Copy, Debug)]
+pub struct CornerCase {
+    pub name: &'static str,
+    pub input: InstructionInput,
+}
+
+/// The curated corner cases for `instr`, or an empty list for instructions
+/// this catalog doesn't (yet) single out -- not every instruction has a
+/// boundary worth curating by hand; [`crate::campaign::exhaustive_cases`]'s
+/// generic sweep still covers them.
+pub fn corner_cases(instr: Instr) -> Vec<CornerCase> {
+    match instr {
+        Instr::Add | Instr::AddO | Instr::AddDot => vec![
+            CornerCase {
+                name: "i64::MAX + 1 overflows",
+                input: InstructionInput { ra: i64::MAX as u64, rb: 1, ..InstructionInput::default() },
+            },
+            CornerCase {
+                name: "i64::MIN + -1 overflows the other way",
+                input: InstructionInput { ra: i64::MIN as u64, rb: -1i64 as u64, ..InstructionInput::default() },
+            },
+        ],
+        Instr::Subf | Instr::SubfO => vec![CornerCase {
+            name: "i64::MIN - 1 overflows",
+            input: InstructionInput { ra: 1, rb: i64::MIN as u64, ..InstructionInput::default() },
+        }],
+        Instr::AddC | Instr::AddE => vec![
+            CornerCase {
+                name: "u64::MAX + 1 carries out",
+                input: InstructionInput { ra: u64::MAX, rb: 1, ..InstructionInput::default() },
+            },
+            CornerCase {
+                name: "zero operands with an incoming carry",
+                input: InstructionInput {
+                    ra: 0,
+                    rb: 0,
+                    xer: Xer { ca: true, ..Xer::default() },
+                    ..InstructionInput::default()
+                },
+            },
+        ],
+        Instr::Mulld => vec![CornerCase {
+            name: "i64::MIN * -1 overflows the low 64 bits",
+            input: InstructionInput { ra: i64::MIN as u64, rb: -1i64 as u64, ..InstructionInput::default() },
+        }],
+        Instr::Mulhdu => vec![CornerCase {
+            name: "u64::MAX * u64::MAX carries into the high word",
+            input: InstructionInput { ra: u64::MAX, rb: u64::MAX, ..InstructionInput::default() },
+        }],
+        Instr::Divd => vec![
+            CornerCase {
+                name: "i64::MIN / -1 overflows; the model returns 0 rather than trapping",
+                input: InstructionInput { ra: i64::MIN as u64, rb: -1i64 as u64, ..InstructionInput::default() },
+            },
+            CornerCase {
+                name: "divide by zero; the model returns 0 rather than trapping",
+                input: InstructionInput { ra: 1, rb: 0, ..InstructionInput::default() },
+            },
+        ],
+        Instr::Divdu => vec![CornerCase {
+            name: "divide by zero; the model returns 0 rather than trapping",
+            input: InstructionInput { ra: 1, rb: 0, ..InstructionInput::default() },
+        }],
+        Instr::Slw | Instr::Srw => vec![
+            CornerCase {
+                name: "shift amount one below the word width still shifts normally",
+                input: InstructionInput { ra: u64::MAX, rb: 31, ..InstructionInput::default() },
+            },
+            CornerCase {
+                name: "shift amount exactly at the word width gives zero",
+                input: InstructionInput { ra: u64::MAX, rb: 32, ..InstructionInput::default() },
+            },
+            CornerCase {
+                name: "shift amount one above the word width still gives zero",
+                input: InstructionInput { ra: u64::MAX, rb: 33, ..InstructionInput::default() },
+            },
+        ],
+        Instr::Sraw => vec![
+            CornerCase {
+                name: "shift amount one below the word width still shifts normally",
+                input: InstructionInput { ra: (-1i32) as u32 as u64, rb: 31, ..InstructionInput::default() },
+            },
+            CornerCase {
+                name: "negative value with a shift amount at the word width sign-fills and sets CA",
+                input: InstructionInput { ra: (-1i32) as u32 as u64, rb: 32, ..InstructionInput::default() },
+            },
+            CornerCase {
+                name: "positive value with a shift amount above the word width gives zero without CA",
+                input: InstructionInput { ra: 1, rb: 33, ..InstructionInput::default() },
+            },
+        ],
+        Instr::Sld | Instr::Srd => vec![
+            CornerCase {
+                name: "shift amount one below the doubleword width still shifts normally",
+                input: InstructionInput { ra: u64::MAX, rb: 63, ..InstructionInput::default() },
+            },
+            CornerCase {
+                name: "shift amount exactly at the doubleword width gives zero",
+                input: InstructionInput { ra: u64::MAX, rb: 64, ..InstructionInput::default() },
+            },
+            CornerCase {
+                name: "shift amount one above the doubleword width still gives zero",
+                input: InstructionInput { ra: u64::MAX, rb: 65, ..InstructionInput::default() },
+            },
+        ],
+        Instr::Srad => vec![
+            CornerCase {
+                name: "shift amount one below the doubleword width still shifts normally",
+                input: InstructionInput { ra: (-1i64) as u64, rb: 63, ..InstructionInput::default() },
+            },
+            CornerCase {
+                name: "negative value with a shift amount at the doubleword width sign-fills and sets CA",
+                input: InstructionInput { ra: (-1i64) as u64, rb: 64, ..InstructionInput::default() },
+            },
+            CornerCase {
+                name: "positive value with a shift amount above the doubleword width gives zero without CA",
+                input: InstructionInput { ra: 1, rb: 65, ..InstructionInput::default() },
+            },
+        ],
+        _ => vec![],
+    }
+}
+
+/// [`corner_cases`] paired with `instr`, in the same `(Instr,
+/// InstructionInput)` shape [`crate::campaign::exhaustive_cases`] returns,
+/// for generators (like `pia farm`'s case list) that don't care about the
+/// name.
+pub fn corner_case_inputs(instr: Instr) -> Vec<(Instr, InstructionInput)> {
+    corner_cases(instr).into_iter().map(|case| (instr, case.input)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model;
+
+    #[test]
+    fn addo_corner_cases_genuinely_overflow() {
+        for case in corner_cases(Instr::AddO) {
+            assert!(model::model(Instr::AddO, case.input).xer.unwrap().ov, "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn divd_min_by_negative_one_returns_zero_instead_of_trapping() {
+        let case = &corner_cases(Instr::Divd)[0];
+        assert_eq!(model::model(Instr::Divd, case.input).rt, Some(0));
+    }
+
+    #[test]
+    fn divdu_by_zero_returns_zero_instead_of_trapping() {
+        let case = &corner_cases(Instr::Divdu)[0];
+        assert_eq!(model::model(Instr::Divdu, case.input).rt, Some(0));
+    }
+
+    #[test]
+    fn instructions_without_a_curated_entry_return_an_empty_list() {
+        assert!(corner_cases(Instr::Brh).is_empty());
+    }
+
+    #[test]
+    fn sraw_and_srad_corner_cases_at_the_width_set_ca() {
+        let case = corner_cases(Instr::Sraw)
+            .into_iter()
+            .find(|case| case.name.contains("sign-fills and sets CA"))
+            .unwrap();
+        assert!(model::model(Instr::Sraw, case.input).xer.unwrap().ca);
+
+        let case = corner_cases(Instr::Srad)
+            .into_iter()
+            .find(|case| case.name.contains("sign-fills and sets CA"))
+            .unwrap();
+        assert!(model::model(Instr::Srad, case.input).xer.unwrap().ca);
+    }
+
+    #[test]
+    fn shift_corner_cases_at_or_above_the_width_give_zero() {
+        for case in corner_cases(Instr::Slw) {
+            if case.name.contains("width gives zero") {
+                assert_eq!(model::model(Instr::Slw, case.input).rt, Some(0), "{}", case.name);
+            }
+        }
+        for case in corner_cases(Instr::Sld) {
+            if case.name.contains("width gives zero") {
+                assert_eq!(model::model(Instr::Sld, case.input).rt, Some(0), "{}", case.name);
+            }
+        }
+    }
+
+    #[test]
+    fn corner_case_inputs_pairs_every_case_with_its_instr() {
+        let inputs = corner_case_inputs(Instr::AddC);
+        assert_eq!(inputs.len(), corner_cases(Instr::AddC).len());
+        assert!(inputs.iter().all(|&(instr, _)| instr == Instr::AddC));
+    }
+}
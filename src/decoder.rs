@@ -0,0 +1,175 @@
+//! Decodes 32-bit POWER ISA instruction words back into an [`Instr`] plus
+//! its operands, the inverse of [`crate::encoder::encode`].
+
+use crate::asm;
+use crate::fields;
+use crate::instr::Instr;
+use std::fmt;
+
+/// How to handle a word that sets bits this decoder doesn't expect to be
+/// set (currently: the OE bit on an instruction form that doesn't support
+/// overflow recording). Real cores differ in whether they trap, silently
+/// ignore, or (rarely) decode reserved fields meaningfully, so tooling
+/// built on this decoder needs to be able to match whichever behavior it's
+/// comparing against.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Strictness {
+    /// Reserved bits being set is a [`DecodeError::ReservedBitsSet`].
+    Strict,
+    /// Reserved bits being set is logged to stderr and otherwise ignored.
+    Warn,
+    /// Reserved bits are silently ignored.
+    Ignore,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DecodedInstr {
+    pub instr: Instr,
+    pub rt: u32,
+    pub ra: u32,
+    pub rb: u32,
+    pub rc: bool,
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    UnknownOpcode(u32),
+    ReservedBitsSet { word: u32, mask: u32 },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnknownOpcode(word) => write!(f, "unknown opcode in word {:#010x}", word),
+            DecodeError::ReservedBitsSet { word, mask } => {
+                write!(f, "reserved bits {:#x} set in word {:#010x}", mask, word)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// `(base_xo, non-overflow-recording instruction, overflow-recording variant)`.
+/// Mirrors the table implicit in [`crate::encoder::encode`].
+const XO_TABLE: &[(u32, Instr, Option<Instr>)] = &[
+    (266, Instr::Add, Some(Instr::AddO)),
+    (10, Instr::AddC, None),
+    (138, Instr::AddE, None),
+    (40, Instr::Subf, Some(Instr::SubfO)),
+    (233, Instr::Mulld, None),
+    (9, Instr::Mulhdu, None),
+    (489, Instr::Divd, None),
+    (457, Instr::Divdu, None),
+];
+
+/// [`fields::OE`]'s position relative to [`fields::XO`], for clearing it
+/// out of a just-extracted `xo10` before matching against [`XO_TABLE`].
+const OE_BIT_IN_XO: u32 = 1 << (fields::OE.lsb - fields::XO.lsb);
+
+/// Decodes `word`, applying `strictness` to any reserved-bit usage found.
+pub fn decode(word: u32, strictness: Strictness) -> Result<DecodedInstr, DecodeError> {
+    let opcd = fields::OPCD.get(word);
+    if opcd != 31 {
+        return Err(DecodeError::UnknownOpcode(word));
+    }
+    let rt = fields::RT.get(word);
+    let ra = fields::RA.get(word);
+    let rb = fields::RB.get(word);
+    let xo10 = fields::XO.get(word);
+    let rc = fields::RC.get(word) != 0;
+    let oe = fields::OE.get(word) != 0;
+    let base_xo = xo10 & !OE_BIT_IN_XO;
+
+    let (_, no_oe_instr, oe_instr) = XO_TABLE
+        .iter()
+        .find(|(base, _, _)| *base == base_xo)
+        .ok_or(DecodeError::UnknownOpcode(word))?;
+
+    let base_instr = match (oe, oe_instr) {
+        (false, _) => *no_oe_instr,
+        (true, Some(instr)) => *instr,
+        (true, None) => match strictness {
+            Strictness::Strict => {
+                return Err(DecodeError::ReservedBitsSet { word, mask: 1 << fields::OE.lsb });
+            }
+            Strictness::Warn => {
+                eprintln!(
+                    "decoder: word {:#010x} sets the OE bit on {}, which has no overflow-recording form; ignoring",
+                    word, no_oe_instr
+                );
+                *no_oe_instr
+            }
+            Strictness::Ignore => *no_oe_instr,
+        },
+    };
+
+    let instr = match (rc, base_instr.rc_form()) {
+        (false, _) => base_instr,
+        (true, Some(dot_instr)) => dot_instr,
+        (true, None) => match strictness {
+            Strictness::Strict => {
+                return Err(DecodeError::ReservedBitsSet { word, mask: 1 });
+            }
+            Strictness::Warn => {
+                eprintln!(
+                    "decoder: word {:#010x} sets the rc bit on {}, which has no modeled Rc-form; ignoring",
+                    word, base_instr
+                );
+                base_instr
+            }
+            Strictness::Ignore => base_instr,
+        },
+    };
+
+    Ok(DecodedInstr { instr, rt, ra, rb, rc })
+}
+
+/// Decodes `word` and formats it back to assembly text, for displaying
+/// offending instructions in trace-checking and report output. Uses
+/// extended/alternate mnemonics (see [`Instr::aliases`]) when `extended` is
+/// set, otherwise the canonical mnemonic.
+pub fn disassemble(word: u32, strictness: Strictness, extended: bool) -> Result<String, DecodeError> {
+    let decoded = decode(word, strictness)?;
+    Ok(if extended {
+        asm::disassemble_extended(decoded.instr, decoded.rt, decoded.ra, decoded.rb)
+    } else {
+        asm::disassemble(decoded.instr, decoded.rt, decoded.ra, decoded.rb)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::encode;
+
+    #[test]
+    fn unknown_opcode_is_rejected() {
+        // opcd 0 is never a valid instruction in this table.
+        assert!(matches!(decode(0, Strictness::Strict), Err(DecodeError::UnknownOpcode(_))));
+    }
+
+    #[test]
+    fn reserved_oe_bit_is_strict_by_default() {
+        let word = encode(Instr::Mulld, 3, 4, 5).unwrap() | (1 << fields::OE.lsb);
+        assert!(matches!(
+            decode(word, Strictness::Strict),
+            Err(DecodeError::ReservedBitsSet { .. })
+        ));
+        assert_eq!(decode(word, Strictness::Ignore).unwrap().instr, Instr::Mulld);
+    }
+
+    #[test]
+    fn disassembles_with_and_without_extended_mnemonics() {
+        let word = encode(Instr::Subf, 3, 4, 5).unwrap();
+        assert_eq!(disassemble(word, Strictness::Strict, false).unwrap(), "subf r3,r4,r5");
+        assert_eq!(disassemble(word, Strictness::Strict, true).unwrap(), "sf r3,r4,r5");
+    }
+
+    #[test]
+    fn round_trips_through_encode() {
+        let word = encode(Instr::AddO, 3, 4, 5).unwrap();
+        let decoded = decode(word, Strictness::Strict).unwrap();
+        assert_eq!(decoded, DecodedInstr { instr: Instr::AddO, rt: 3, ra: 4, rb: 5, rc: false });
+    }
+}
@@ -0,0 +1,70 @@
+//! Generates per-instruction Markdown reference pages from
+//! [`crate::metadata`] and [`crate::model`], so the human-readable docs
+//! can't drift from the executable model they describe.
+
+use crate::metadata::{self, InstrMetadata};
+use crate::model;
+use crate::types::InstructionInput;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A handful of inputs worth showing worked examples for: the all-zero
+/// case, a case that exercises carry/overflow, and a case with the
+/// operands swapped.
+fn corner_case_inputs() -> Vec<InstructionInput> {
+    vec![
+        InstructionInput::default(),
+        InstructionInput {
+            ra: u64::MAX,
+            rb: 1,
+            ..InstructionInput::default()
+        },
+        InstructionInput {
+            ra: 1,
+            rb: u64::MAX,
+            ..InstructionInput::default()
+        },
+    ]
+}
+
+fn render_page(meta: &InstrMetadata) -> String {
+    let mut page = String::new();
+    let _ = writeln!(page, "# `{}`", meta.instr);
+    let _ = writeln!(page);
+    let _ = writeln!(page, "- ISA version: {}", meta.isa_version);
+    let _ = writeln!(page, "- Operands: {}", meta.operands.join(", "));
+    let _ = writeln!(page, "- Reads: {:?}", meta.reads);
+    let _ = writeln!(page, "- Writes: {:?}", meta.writes);
+    if meta.model_only {
+        let _ = writeln!(page, "- **Model-only**: no supported native-execution host can run this instruction.");
+    }
+    let _ = writeln!(page);
+    let _ = writeln!(page, "## Corner cases");
+    let _ = writeln!(page);
+    let _ = writeln!(page, "| ra | rb | rt |");
+    let _ = writeln!(page, "| --- | --- | --- |");
+    for input in corner_case_inputs() {
+        let output = model::model(meta.instr, input);
+        let _ = writeln!(
+            page,
+            "| {:#x} | {:#x} | {} |",
+            input.ra,
+            input.rb,
+            output.rt.map_or("-".to_string(), |rt| format!("{:#x}", rt))
+        );
+    }
+    page
+}
+
+/// Writes one Markdown page per instruction into `dir` (created if
+/// missing), named `<mnemonic>.md`.
+pub fn generate_docs(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for meta in metadata::all_metadata() {
+        let path = dir.join(format!("{}.md", meta.instr));
+        fs::write(path, render_page(&meta))?;
+    }
+    Ok(())
+}
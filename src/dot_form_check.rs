@@ -0,0 +1,67 @@
+//! Independently recomputes the CR0 a dot-form (Rc) instruction should
+//! produce -- straight from its plain counterpart's `rt` and the caller's
+//! SO, via [`ConditionRegister::compare_signed`] -- and cross-checks it
+//! against what the model actually returns, across every corner-case
+//! input for every instruction with a modeled Rc-form ([`Instr::rc_form`]).
+//!
+//! This exists to catch exactly the kind of bug a generic "compute CR0
+//! from the result" wrapper can introduce: picking the wrong result width
+//! to compare against zero (e.g. truncating to `i32` when the instruction
+//! is 64-bit), or forgetting to thread the caller's SO through instead of
+//! always using `false`.
+
+use crate::corner_cases;
+use crate::instr::Instr;
+use crate::model;
+use crate::types::{ConditionRegister, InstructionInput};
+
+/// One dot-form input where the model's CR0 disagreed with the
+/// independently recomputed expectation.
+#[derive(Debug)]
+pub struct Discrepancy {
+    pub instr: Instr,
+    pub input: InstructionInput,
+    pub expected: ConditionRegister,
+    pub actual: Option<ConditionRegister>,
+}
+
+/// Cross-checks every currently-modeled Rc-form instruction's CR0 against
+/// an independent recomputation from its plain counterpart's `rt`, across
+/// every corner-case input. Returns every disagreement found.
+pub fn check_all() -> Vec<Discrepancy> {
+    let mut discrepancies = Vec::new();
+    for &instr in Instr::ALL {
+        let Some(rc_instr) = instr.rc_form() else { continue };
+        for (_, input) in corner_cases::corner_case_inputs(instr) {
+            let plain = model::model(instr, input);
+            let rt = plain.rt.expect("a plain instruction with a modeled Rc-form always produces rt");
+            let expected = ConditionRegister::compare_signed(rt as i64, 0, input.xer.so);
+            let actual = model::model(rc_instr, input).cr0;
+            if actual != Some(expected) {
+                discrepancies.push(Discrepancy { instr: rc_instr, input, expected, actual });
+            }
+        }
+    }
+    discrepancies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_modeled_dot_form_agrees_with_an_independently_recomputed_cr0() {
+        let discrepancies = check_all();
+        assert!(discrepancies.is_empty(), "{:#?}", discrepancies);
+    }
+
+    #[test]
+    fn a_deliberately_wrong_expectation_is_caught() {
+        // Sanity-check check_all() actually compares, rather than vacuously
+        // passing because no Rc-form is modeled.
+        let input = InstructionInput { ra: 5, rb: 0, ..InstructionInput::default() };
+        let wrong_expected = ConditionRegister::compare_signed(-1, 0, false);
+        let actual = model::model(Instr::AddDot, input).cr0;
+        assert_ne!(actual, Some(wrong_expected));
+    }
+}
@@ -0,0 +1,138 @@
+//! Encodes [`Instr`]s into their 32-bit POWER ISA instruction words.
+//!
+//! This only covers the instructions in [`Instr::ALL`]; unsupported forms
+//! (vector ops, immediate forms, ...) are added as the instruction set
+//! grows.
+
+use crate::fields;
+use crate::instr::Instr;
+use crate::types::Fpscr;
+
+/// Standard XO-form field layout: `opcd | rt | ra | rb | xo | rc`.
+fn xo_form(opcd: u32, rt: u32, ra: u32, rb: u32, xo: u32, rc: bool) -> u32 {
+    let word = fields::OPCD.set(0, opcd);
+    let word = fields::RT.set(word, rt);
+    let word = fields::RA.set(word, ra);
+    let word = fields::RB.set(word, rb);
+    let word = fields::XO.set(word, xo);
+    fields::RC.set(word, rc as u32)
+}
+
+/// `FPSCR` bit numbers (in the classic 32-bit numbering `mtfsb0`/`mtfsb1`'s
+/// `BT` operand addresses) for the fields [`Fpscr`] models.
+mod fpscr_bits {
+    pub const VE: u32 = 23;
+    pub const OE: u32 = 24;
+    pub const UE: u32 = 25;
+    pub const ZE: u32 = 26;
+    pub const XE: u32 = 27;
+    pub const RN_HI: u32 = 30;
+    pub const RN_LO: u32 = 31;
+}
+
+/// Encodes `mtfsb1 bt`, which sets `FPSCR` bit `bt` to 1.
+fn mtfsb1(bt: u32) -> u32 {
+    xo_form(63, bt, 0, 0, 38, false)
+}
+
+/// Encodes `mtfsb0 bt`, which clears `FPSCR` bit `bt` to 0.
+fn mtfsb0(bt: u32) -> u32 {
+    xo_form(63, bt, 0, 0, 70, false)
+}
+
+/// One `mtfsb0`/`mtfsb1` per bit, setting the `FPSCR` fields [`Fpscr`]
+/// models to exactly the given value regardless of whatever they held
+/// before -- so a native FP test case doesn't inherit rounding
+/// mode/exception-enable state left over from whichever case ran before
+/// it. `mtfsb0`/`mtfsb1` address individual bits directly, so this needs
+/// no scratch FPR or memory the way loading a full `FPSCR` word would.
+pub fn fpscr_setup_words(fpscr: Fpscr) -> Vec<u32> {
+    let bit = |set: bool, bt: u32| if set { mtfsb1(bt) } else { mtfsb0(bt) };
+    vec![
+        bit(fpscr.ve, fpscr_bits::VE),
+        bit(fpscr.oe, fpscr_bits::OE),
+        bit(fpscr.ue, fpscr_bits::UE),
+        bit(fpscr.ze, fpscr_bits::ZE),
+        bit(fpscr.xe, fpscr_bits::XE),
+        bit(fpscr.rn.to_bits() & 0b10 != 0, fpscr_bits::RN_HI),
+        bit(fpscr.rn.to_bits() & 0b01 != 0, fpscr_bits::RN_LO),
+    ]
+}
+
+/// The inverse of [`fpscr_setup_words`]: clears every `FPSCR` field
+/// [`Fpscr`] models back to its architectural default (all enables off,
+/// round-to-nearest), so one case's environment can't leak into the next
+/// if its native wrapper forgets to call [`fpscr_setup_words`] itself.
+pub fn fpscr_restore_words() -> Vec<u32> {
+    fpscr_setup_words(Fpscr::default())
+}
+
+/// Encodes `instr` with the given GPR operand numbers (0..=31) into its
+/// 32-bit instruction word, or `None` for instructions this framework has
+/// no native encoding for (currently: every [`Instr::is_model_only`]
+/// instruction).
+pub fn encode(instr: Instr, rt: u32, ra: u32, rb: u32) -> Option<u32> {
+    if instr.is_model_only() {
+        return None;
+    }
+    Some(match instr {
+        Instr::Add => xo_form(31, rt, ra, rb, 266, false),
+        Instr::AddO => xo_form(31, rt, ra, rb, 266 | (1 << 9), false),
+        Instr::AddDot => xo_form(31, rt, ra, rb, 266, true),
+        Instr::AddC => xo_form(31, rt, ra, rb, 10, false),
+        Instr::AddE => xo_form(31, rt, ra, rb, 138, false),
+        Instr::Subf => xo_form(31, rt, ra, rb, 40, false),
+        Instr::SubfO => xo_form(31, rt, ra, rb, 40 | (1 << 9), false),
+        Instr::Mulld => xo_form(31, rt, ra, rb, 233, false),
+        Instr::Mulhdu => xo_form(31, rt, ra, rb, 9, false),
+        Instr::Divd => xo_form(31, rt, ra, rb, 489, false),
+        Instr::Divdu => xo_form(31, rt, ra, rb, 457, false),
+        Instr::Brh | Instr::Brw | Instr::Brd | Instr::Cfuged | Instr::Cntlzdm | Instr::Cnttzdm | Instr::Pdepd
+        | Instr::Pextd | Instr::Mffscrn | Instr::Mffsce | Instr::Slw | Instr::Srw | Instr::Sraw | Instr::Sld
+        | Instr::Srd | Instr::Srad => unreachable!("model-only instructions returned above"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RoundingMode;
+
+    #[test]
+    fn add_matches_known_encoding() {
+        // `add r3, r4, r5` == 0x7c642a14
+        assert_eq!(encode(Instr::Add, 3, 4, 5), Some(0x7c64_2a14));
+    }
+
+    #[test]
+    fn rc_form_sets_the_low_bit() {
+        // `add. r3, r4, r5` == `add r3, r4, r5` with the rc bit set.
+        assert_eq!(encode(Instr::AddDot, 3, 4, 5), Some(0x7c64_2a15));
+    }
+
+    #[test]
+    fn model_only_instructions_have_no_encoding() {
+        assert_eq!(encode(Instr::Cfuged, 3, 4, 5), None);
+    }
+
+    #[test]
+    fn fpscr_setup_words_emits_one_instruction_per_modeled_bit() {
+        let fpscr = Fpscr { ve: true, oe: false, ue: true, ze: false, xe: true, rn: RoundingMode::TowardZero };
+        let words = fpscr_setup_words(fpscr);
+        assert_eq!(words.len(), 7);
+        // `mtfsb1 23` (sets VE): opcd 63, BT 23, XO 38.
+        assert_eq!(words[0], (63 << 26) | (23 << 21) | (38 << 1));
+        // `mtfsb0 24` (clears OE): opcd 63, BT 24, XO 70.
+        assert_eq!(words[1], (63 << 26) | (24 << 21) | (70 << 1));
+    }
+
+    #[test]
+    fn fpscr_restore_words_clears_every_bit() {
+        let setup = fpscr_setup_words(Fpscr::default());
+        let restore = fpscr_restore_words();
+        assert_eq!(setup, restore);
+        // Every encoded bit is a `mtfsb0` (XO 70), since the default leaves
+        // every modeled field cleared.
+        assert!(restore.iter().all(|&word| word & (0x3ff << 1) == (70 << 1)));
+    }
+}
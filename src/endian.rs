@@ -0,0 +1,53 @@
+//! Host byte order, recorded alongside native-execution results.
+//!
+//! Memory-accessing (load/store) instructions aren't modeled in this crate
+//! yet (see [`crate::instr::Instr`]), so there's no LE/BE-sensitive
+//! semantics for [`crate::model`] to select between. This module exists so
+//! a future load/store model can thread an explicit [`Endianness`] through
+//! the same way [`Endianness::host`] already records which byte order
+//! native execution actually ran under.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// The byte order native execution on this host actually runs under.
+    pub const fn host() -> Endianness {
+        #[cfg(target_endian = "little")]
+        {
+            Endianness::Little
+        }
+        #[cfg(target_endian = "big")]
+        {
+            Endianness::Big
+        }
+    }
+}
+
+impl fmt::Display for Endianness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Endianness::Little => "little",
+            Endianness::Big => "big",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_matches_the_compiled_target_endian() {
+        #[cfg(target_endian = "little")]
+        assert_eq!(Endianness::host(), Endianness::Little);
+        #[cfg(target_endian = "big")]
+        assert_eq!(Endianness::host(), Endianness::Big);
+    }
+}
@@ -0,0 +1,171 @@
+//! A small pure expression IR over 64-bit words, for instruction semantics
+//! simple enough to express as one expression tree instead of arbitrary
+//! Rust code. [`Expr::eval`] interprets a tree directly; [`Expr::to_pseudocode`],
+//! [`Expr::to_verilog`], and [`Expr::to_smt_lib`] lower the same tree to
+//! three different backends, so none of them can drift from what the
+//! interpreter actually computes and from each other.
+//!
+//! [`rt_expr`] is the IR's only connection to [`Instr`]: it covers the
+//! instructions whose `rt` output reduces to one pure function of `ra`/`rb`
+//! (see its doc comment for what's deliberately left out).
+
+use crate::instr::Instr;
+
+/// One node of the expression tree. All operations are over 64-bit words,
+/// matching [`crate::types::InstructionInput`]'s `ra`/`rb`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Expr {
+    Ra,
+    Rb,
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    /// The high 64 bits of the full 128-bit product of two *unsigned* 64-bit
+    /// operands.
+    MulHighUnsigned(Box<Expr>, Box<Expr>),
+    ByteReverse(Box<Expr>),
+}
+
+impl Expr {
+    /// Interprets this expression directly, the IR's ground truth: every
+    /// lowering below exists to match this, not the other way around.
+    pub fn eval(&self, ra: u64, rb: u64) -> u64 {
+        match self {
+            Expr::Ra => ra,
+            Expr::Rb => rb,
+            Expr::Add(a, b) => a.eval(ra, rb).wrapping_add(b.eval(ra, rb)),
+            Expr::Sub(a, b) => a.eval(ra, rb).wrapping_sub(b.eval(ra, rb)),
+            Expr::Mul(a, b) => a.eval(ra, rb).wrapping_mul(b.eval(ra, rb)),
+            Expr::MulHighUnsigned(a, b) => {
+                (((a.eval(ra, rb) as u128) * (b.eval(ra, rb) as u128)) >> 64) as u64
+            }
+            Expr::ByteReverse(a) => a.eval(ra, rb).swap_bytes(),
+        }
+    }
+
+    /// Renders as English-ish pseudocode, e.g. `"ra + rb"`.
+    pub fn to_pseudocode(&self) -> String {
+        match self {
+            Expr::Ra => "ra".to_string(),
+            Expr::Rb => "rb".to_string(),
+            Expr::Add(a, b) => format!("{} + {}", a.to_pseudocode(), b.to_pseudocode()),
+            Expr::Sub(a, b) => format!("{} - {}", a.to_pseudocode(), b.to_pseudocode()),
+            Expr::Mul(a, b) => format!("{} * {}", a.to_pseudocode(), b.to_pseudocode()),
+            Expr::MulHighUnsigned(a, b) => {
+                format!("high64(unsigned({}) * unsigned({}))", a.to_pseudocode(), b.to_pseudocode())
+            }
+            Expr::ByteReverse(a) => format!("byte_reverse({})", a.to_pseudocode()),
+        }
+    }
+
+    /// Renders as a SystemVerilog expression suitable for e.g. [`crate::sva`].
+    pub fn to_verilog(&self) -> String {
+        match self {
+            Expr::Ra => "ra".to_string(),
+            Expr::Rb => "rb".to_string(),
+            Expr::Add(a, b) => format!("({} + {})", a.to_verilog(), b.to_verilog()),
+            Expr::Sub(a, b) => format!("({} - {})", a.to_verilog(), b.to_verilog()),
+            Expr::Mul(a, b) => format!("({} * {})", a.to_verilog(), b.to_verilog()),
+            Expr::MulHighUnsigned(a, b) => {
+                format!("(({{64'b0, {}}} * {{64'b0, {}}}) >> 64)", a.to_verilog(), b.to_verilog())
+            }
+            Expr::ByteReverse(a) => format!("{{<<8{{{}}}}}", a.to_verilog()),
+        }
+    }
+
+    /// Renders as a C expression over `uint64_t ra, rb`, for [`crate::cdiff`].
+    pub fn to_c(&self) -> String {
+        match self {
+            Expr::Ra => "ra".to_string(),
+            Expr::Rb => "rb".to_string(),
+            Expr::Add(a, b) => format!("({} + {})", a.to_c(), b.to_c()),
+            Expr::Sub(a, b) => format!("({} - {})", a.to_c(), b.to_c()),
+            Expr::Mul(a, b) => format!("({} * {})", a.to_c(), b.to_c()),
+            Expr::MulHighUnsigned(a, b) => {
+                format!(
+                    "(uint64_t)(((unsigned __int128)({}) * (unsigned __int128)({})) >> 64)",
+                    a.to_c(),
+                    b.to_c()
+                )
+            }
+            Expr::ByteReverse(a) => format!("__builtin_bswap64({})", a.to_c()),
+        }
+    }
+
+    /// Renders as an SMT-LIB (`QF_BV`) term over 64-bit bitvectors.
+    pub fn to_smt_lib(&self) -> String {
+        match self {
+            Expr::Ra => "ra".to_string(),
+            Expr::Rb => "rb".to_string(),
+            Expr::Add(a, b) => format!("(bvadd {} {})", a.to_smt_lib(), b.to_smt_lib()),
+            Expr::Sub(a, b) => format!("(bvsub {} {})", a.to_smt_lib(), b.to_smt_lib()),
+            Expr::Mul(a, b) => format!("(bvmul {} {})", a.to_smt_lib(), b.to_smt_lib()),
+            Expr::MulHighUnsigned(a, b) => format!(
+                "((_ extract 127 64) (bvmul ((_ zero_extend 64) {}) ((_ zero_extend 64) {})))",
+                a.to_smt_lib(),
+                b.to_smt_lib()
+            ),
+            Expr::ByteReverse(a) => {
+                let byte = |i: u32| format!("((_ extract {} {}) {})", i * 8 + 7, i * 8, a.to_smt_lib());
+                format!("(concat {})", (0..8).map(byte).collect::<Vec<_>>().join(" "))
+            }
+        }
+    }
+}
+
+/// The expression tree computing `rt` for instructions simple enough to
+/// express this way, or `None` for everything else: instructions with
+/// flags that depend on more than `ra`/`rb` (e.g. an incoming carry or
+/// `xer.so`), multi-step bit permutations, or control flow/faulting
+/// behavior (e.g. divide-by-zero).
+pub fn rt_expr(instr: Instr) -> Option<Expr> {
+    match instr {
+        Instr::Add | Instr::AddO | Instr::AddDot | Instr::AddC => {
+            Some(Expr::Add(Box::new(Expr::Ra), Box::new(Expr::Rb)))
+        }
+        Instr::Subf | Instr::SubfO => Some(Expr::Sub(Box::new(Expr::Rb), Box::new(Expr::Ra))),
+        Instr::Mulld => Some(Expr::Mul(Box::new(Expr::Ra), Box::new(Expr::Rb))),
+        Instr::Mulhdu => Some(Expr::MulHighUnsigned(Box::new(Expr::Ra), Box::new(Expr::Rb))),
+        Instr::Brd => Some(Expr::ByteReverse(Box::new(Expr::Ra))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model;
+    use crate::types::InstructionInput;
+
+    #[test]
+    fn eval_agrees_with_the_model_for_every_covered_instruction() {
+        let inputs = [
+            InstructionInput::default(),
+            InstructionInput { ra: 1, rb: 2, ..InstructionInput::default() },
+            InstructionInput { ra: u64::MAX, rb: 1, ..InstructionInput::default() },
+            InstructionInput { ra: 0x0123_4567_89ab_cdef, rb: 7, ..InstructionInput::default() },
+        ];
+        for instr in Instr::ALL.iter().copied() {
+            let Some(expr) = rt_expr(instr) else { continue };
+            for input in inputs {
+                let expected = model::model(instr, input).rt;
+                assert_eq!(Some(expr.eval(input.ra, input.rb)), expected, "{} with {:?}", instr, input);
+            }
+        }
+    }
+
+    #[test]
+    fn lowerings_render_the_same_tree_shape() {
+        let expr = Expr::Add(Box::new(Expr::Ra), Box::new(Expr::Rb));
+        assert_eq!(expr.to_pseudocode(), "ra + rb");
+        assert_eq!(expr.to_verilog(), "(ra + rb)");
+        assert_eq!(expr.to_c(), "(ra + rb)");
+        assert_eq!(expr.to_smt_lib(), "(bvadd ra rb)");
+    }
+
+    #[test]
+    fn byte_reverse_round_trips_through_eval() {
+        let expr = Expr::ByteReverse(Box::new(Expr::Ra));
+        assert_eq!(expr.eval(0x0102_0304_0506_0708, 0), 0x0807_0605_0403_0201);
+    }
+}
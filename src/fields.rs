@@ -0,0 +1,105 @@
+//! Bit positions and widths of the fixed-point instruction word fields
+//! this crate's [`crate::encoder`] and [`crate::decoder`] agree on, as
+//! typed accessors instead of each side separately hard-coding its own
+//! shift/mask arithmetic.
+
+/// A fixed-width bitfield within a 32-bit instruction word, addressed by
+/// its least-significant bit and width.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Field {
+    pub lsb: u32,
+    pub width: u32,
+}
+
+impl Field {
+    const fn mask(self) -> u32 {
+        if self.width >= 32 {
+            u32::MAX
+        } else {
+            (1 << self.width) - 1
+        }
+    }
+
+    /// Extracts this field's value from `word`.
+    pub const fn get(self, word: u32) -> u32 {
+        (word >> self.lsb) & self.mask()
+    }
+
+    /// Returns `word` with this field overwritten by `value`, truncated to
+    /// this field's width the same way the hardware would silently drop
+    /// any higher bits of an over-wide value.
+    pub const fn set(self, word: u32, value: u32) -> u32 {
+        (word & !(self.mask() << self.lsb)) | ((value & self.mask()) << self.lsb)
+    }
+}
+
+/// The primary opcode field (`OPCD`), the top 6 bits of the word.
+pub const OPCD: Field = Field { lsb: 26, width: 6 };
+
+/// The `RT`/`RS` GPR field.
+pub const RT: Field = Field { lsb: 21, width: 5 };
+
+/// The `RA` GPR field.
+pub const RA: Field = Field { lsb: 16, width: 5 };
+
+/// The `RB` GPR field.
+pub const RB: Field = Field { lsb: 11, width: 5 };
+
+/// The full 10-bit extended-opcode field used by XO-form instructions.
+pub const XO: Field = Field { lsb: 1, width: 10 };
+
+/// The overflow-enable bit that `add`/`subf`'s overflow-recording forms
+/// set, as an absolute position in the word (it falls within [`XO`], but
+/// [`crate::decoder`] needs to test it independently of the rest of XO).
+pub const OE: Field = Field { lsb: 10, width: 1 };
+
+/// The record-condition bit, set by every instruction's Rc-form.
+pub const RC: Field = Field { lsb: 0, width: 1 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_extracts_the_right_bits() {
+        // `add r3, r4, r5` == 0x7c642a14.
+        let word = 0x7c64_2a14;
+        assert_eq!(OPCD.get(word), 31);
+        assert_eq!(RT.get(word), 3);
+        assert_eq!(RA.get(word), 4);
+        assert_eq!(RB.get(word), 5);
+        assert_eq!(XO.get(word), 266);
+        assert_eq!(RC.get(word), 0);
+    }
+
+    #[test]
+    fn set_overwrites_only_its_own_bits() {
+        let word = RT.set(0, 3);
+        let word = RA.set(word, 4);
+        let word = RB.set(word, 5);
+        assert_eq!(RT.get(word), 3);
+        assert_eq!(RA.get(word), 4);
+        assert_eq!(RB.get(word), 5);
+    }
+
+    #[test]
+    fn set_truncates_values_wider_than_the_field() {
+        assert_eq!(RT.set(0, 0xff), RT.set(0, 0x1f));
+    }
+
+    #[test]
+    fn oe_sits_inside_the_xo_field() {
+        let word = XO.set(0, 266 | (1 << 9));
+        assert_eq!(OE.get(word), 1);
+        let word = XO.set(0, 266);
+        assert_eq!(OE.get(word), 0);
+    }
+
+    #[test]
+    fn get_and_set_round_trip() {
+        let word = OPCD.set(0, 31);
+        let word = RT.set(word, 17);
+        assert_eq!(RT.get(word), 17);
+        assert_eq!(OPCD.get(word), 31);
+    }
+}
@@ -0,0 +1,370 @@
+//! Turns hand-crafted inputs (no outputs) into full [`WholeTest`] captures
+//! by running the model and, optionally, native execution -- so an HDL
+//! engineer can write down a few interesting `{instr, ra, rb, ...}` cases
+//! and get authoritative expected outputs back.
+
+use crate::affinity::Pinning;
+use crate::capture::{TestCase, WholeTest};
+use crate::endian::Endianness;
+use crate::host_info::HostInfo;
+use crate::instr::Instr;
+use crate::model::{self, Variant, VariantOverrides};
+use crate::native;
+use crate::types::InstructionInput;
+use serde::{Deserialize, Serialize};
+use std::thread;
+
+/// One case with inputs but no recorded outputs yet.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InputOnlyCase {
+    pub instr: Instr,
+    pub input: InstructionInput,
+}
+
+/// Computes model (and, if `use_native`, native) outputs for each of
+/// `cases`, producing a full [`WholeTest`].
+///
+/// `variants` selects an alternate model implementation for specific
+/// instructions (see [`crate::model::model_with_variant`]); instructions
+/// not mentioned use [`Variant::Default`]. A variant rejecting a case (e.g.
+/// `isa_strict` on a divide-by-zero) doesn't abort the batch: that case's
+/// `model_output` is left as the default, matching how a native-execution
+/// failure is handled below.
+///
+/// A native-execution failure for one case (e.g. running on a non-POWER
+/// host) doesn't abort the batch: that case's `native_output` is left as
+/// the default, matching how an absent/`None` field is represented
+/// elsewhere in this crate.
+pub fn fill(cases: Vec<InputOnlyCase>, use_native: bool, variants: &VariantOverrides) -> WholeTest {
+    let test_cases = cases
+        .into_iter()
+        .map(|InputOnlyCase { instr, input }| compute_case(instr, input, use_native, variants))
+        .collect();
+    let host_endianness = if use_native { Some(Endianness::host()) } else { None };
+    let host_info = if use_native { Some(HostInfo::probe()) } else { None };
+    WholeTest { test_cases, pinning: None, host_endianness, host_info }
+}
+
+/// The per-case work shared by [`fill`] and [`run_batch`]: computes the
+/// model (and, if `use_native`, native) output for one `(instr, input)`
+/// pair. A model or native-execution failure is reported but doesn't
+/// panic -- the failing half of the comparison is just left as the
+/// default, matching how an absent/`None` field is represented elsewhere
+/// in this crate.
+fn compute_case(instr: Instr, input: InstructionInput, use_native: bool, variants: &VariantOverrides) -> TestCase {
+    let variant = variants.get(&instr).copied().unwrap_or(Variant::Default);
+    let model_output = match model::model_with_variant(instr, input, variant) {
+        Ok(output) => output,
+        Err(err) => {
+            eprintln!("fill: model of {} failed: {}", instr, err);
+            Default::default()
+        }
+    };
+    let native_output = if use_native {
+        match native::execute(instr, input) {
+            Ok(output) => output,
+            Err(err) => {
+                eprintln!("fill: native execution of {} failed: {}", instr, err);
+                Default::default()
+            }
+        }
+    } else {
+        Default::default()
+    };
+    TestCase {
+        instr,
+        input,
+        native_output,
+        model_output,
+        model_revision: crate::metadata::model_revision(instr),
+        skip: None,
+        latency: None,
+    }
+}
+
+/// Like [`fill`], but takes bare `(instr, input)` pairs and returns the
+/// resulting [`TestCase`]s as a lazy iterator instead of collecting them
+/// into a [`WholeTest`] -- for an embedding application (or the Python
+/// batch API) that wants to stream results onward and has no use for a
+/// capture file's bookkeeping (`pinning`/`host_endianness`).
+pub fn run_batch<'a>(
+    cases: impl IntoIterator<Item = (Instr, InstructionInput)> + 'a,
+    use_native: bool,
+    variants: &'a VariantOverrides,
+) -> impl Iterator<Item = TestCase> + 'a {
+    cases.into_iter().map(move |(instr, input)| compute_case(instr, input, use_native, variants))
+}
+
+/// Picks how many worker threads [`fill_parallel`]/[`run_batch_parallel`]
+/// should use: `requested` if given, else the host's available parallelism
+/// (falling back to a single thread if that can't be determined), so a
+/// multi-core POWER9 host can be told to use fewer threads than it has
+/// cores (e.g. to leave some for an unrelated process) or, conversely, to
+/// confirm the default already saturates it.
+///
+/// Treats `Some(0)` the same as `None` rather than dividing the batch into
+/// zero chunks -- a caller that means "use every available CPU" shouldn't
+/// have to omit the argument instead of passing its count through
+/// directly (the CLI rejects `0` outright for the same reason; see
+/// `pia fill --threads`'s parser).
+fn resolve_num_threads(requested: Option<usize>) -> usize {
+    match requested {
+        Some(0) | None => thread::available_parallelism().map_or(1, |n| n.get()),
+        Some(n) => n,
+    }
+}
+
+/// Like [`run_batch`], but splits `cases` into `num_threads` chunks (or one
+/// per available CPU if `num_threads` is `None`) and fills each chunk on
+/// its own thread, like [`fill_parallel`]. Unlike [`run_batch`], this
+/// collects `cases` eagerly (each worker thread needs its own chunk up
+/// front), so there's no streaming benefit over [`fill_parallel`] beyond
+/// not paying for a [`WholeTest`]; the returned iterator preserves `cases`'
+/// input order.
+pub fn run_batch_parallel(
+    cases: impl IntoIterator<Item = (Instr, InstructionInput)>,
+    use_native: bool,
+    variants: &VariantOverrides,
+    num_threads: Option<usize>,
+) -> impl Iterator<Item = TestCase> {
+    let cases: Vec<(Instr, InstructionInput)> = cases.into_iter().collect();
+    let num_chunks = resolve_num_threads(num_threads).min(cases.len().max(1));
+    let chunk_len = cases.len().div_ceil(num_chunks).max(1);
+    let chunks: Vec<Vec<(Instr, InstructionInput)>> = cases.chunks(chunk_len).map(<[(Instr, InstructionInput)]>::to_vec).collect();
+
+    thread::scope(|scope| {
+        chunks
+            .into_iter()
+            .map(|chunk| scope.spawn(|| run_batch(chunk, use_native, variants).collect::<Vec<_>>()))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("run_batch_parallel worker thread panicked"))
+            .collect::<Vec<_>>()
+            .into_iter()
+    })
+}
+
+/// One case whose native execution disagreed with itself across repeats
+/// during [`fill_checking_flakiness`].
+#[derive(Debug)]
+pub struct FlakinessReport {
+    pub instr: Instr,
+    pub input: InstructionInput,
+    pub outputs: Vec<Result<crate::types::InstructionOutput, native::Error>>,
+}
+
+/// Like [`fill`], but always runs native execution and repeats it
+/// `repeat_count` times per case, flagging any case whose native outputs
+/// disagree across repeats (pointing at a generator/asm bug or
+/// environmental interference, not a model bug). The filled-in
+/// `native_output` of each [`TestCase`] is its first observed output,
+/// matching what a single unrepeated run would have recorded.
+pub fn fill_checking_flakiness(
+    cases: Vec<InputOnlyCase>,
+    repeat_count: usize,
+    variants: &VariantOverrides,
+) -> (WholeTest, Vec<FlakinessReport>) {
+    let mut flakiness_reports = Vec::new();
+    let test_cases = cases
+        .into_iter()
+        .map(|InputOnlyCase { instr, input }| {
+            let variant = variants.get(&instr).copied().unwrap_or(Variant::Default);
+            let model_output = match model::model_with_variant(instr, input, variant) {
+                Ok(output) => output,
+                Err(err) => {
+                    eprintln!("fill: model of {} failed: {}", instr, err);
+                    Default::default()
+                }
+            };
+            let outputs = native::execute_repeated(instr, input, repeat_count);
+            let deterministic = native::outputs_agree(&outputs);
+            let native_output = match outputs.first() {
+                Some(Ok(output)) => *output,
+                _ => Default::default(),
+            };
+            if !deterministic {
+                flakiness_reports.push(FlakinessReport { instr, input, outputs });
+            }
+            TestCase {
+                instr,
+                input,
+                native_output,
+                model_output,
+                model_revision: crate::metadata::model_revision(instr),
+                skip: None,
+                latency: None,
+            }
+        })
+        .collect();
+    (
+        WholeTest { test_cases, pinning: None, host_endianness: Some(Endianness::host()), host_info: Some(HostInfo::probe()) },
+        flakiness_reports,
+    )
+}
+
+/// Like [`fill`], but splits `cases` into `num_threads` chunks (or one per
+/// available CPU if `num_threads` is `None`) and fills each chunk on its
+/// own thread, for batches large enough that the model/native work (not
+/// process startup) dominates. Model execution is a pure function of its
+/// input and native execution touches no shared state (see the note atop
+/// [`crate::native`]), so chunks need no coordination; results are
+/// concatenated back in the order their chunk was handed out, matching
+/// `fill`'s input order.
+pub fn fill_parallel(
+    cases: Vec<InputOnlyCase>,
+    use_native: bool,
+    variants: &VariantOverrides,
+    num_threads: Option<usize>,
+) -> WholeTest {
+    let num_chunks = resolve_num_threads(num_threads).min(cases.len().max(1));
+    let chunk_len = cases.len().div_ceil(num_chunks).max(1);
+    let chunks: Vec<Vec<InputOnlyCase>> = cases.chunks(chunk_len).map(<[InputOnlyCase]>::to_vec).collect();
+
+    let test_cases = thread::scope(|scope| {
+        chunks
+            .into_iter()
+            .map(|chunk| scope.spawn(|| fill(chunk, use_native, variants)))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("fill worker thread panicked").test_cases)
+            .collect()
+    });
+    let host_endianness = if use_native { Some(Endianness::host()) } else { None };
+    let host_info = if use_native { Some(HostInfo::probe()) } else { None };
+    WholeTest { test_cases, pinning: None, host_endianness, host_info }
+}
+
+/// Like [`fill_parallel`], but first applies `pinning` (CPU affinity/nice
+/// priority) to the current process and records it in the returned
+/// [`WholeTest`], so a capture documents how reproducible its native
+/// measurements should have been. A `pinning` apply failure (e.g.
+/// unsupported platform) is reported but doesn't abort the batch, matching
+/// how a per-case native-execution failure is handled elsewhere in this
+/// module.
+pub fn fill_pinned(
+    cases: Vec<InputOnlyCase>,
+    use_native: bool,
+    variants: &VariantOverrides,
+    pinning: Pinning,
+    num_threads: Option<usize>,
+) -> WholeTest {
+    if let Err(err) = pinning.apply() {
+        eprintln!("fill: failed to apply {:?}: {}", pinning, err);
+    }
+    let mut whole_test = fill_parallel(cases, use_native, variants, num_threads);
+    whole_test.pinning = Some(pinning);
+    whole_test
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::InstructionInput;
+
+    #[test]
+    fn fill_checking_flakiness_reports_nothing_off_powerpc64() {
+        // Native execution isn't supported on this host, so every case
+        // fails deterministically (as an `Err`, not a disagreement) and
+        // none should be reported flaky.
+        let cases = vec![InputOnlyCase { instr: Instr::Add, input: InstructionInput::default() }];
+        let (whole_test, reports) = fill_checking_flakiness(cases, 3, &VariantOverrides::default());
+        assert_eq!(whole_test.test_cases.len(), 1);
+        assert!(reports.is_empty());
+    }
+
+    #[test]
+    fn fill_records_host_endianness_only_when_native_execution_was_requested() {
+        let cases = vec![InputOnlyCase { instr: Instr::Add, input: InstructionInput::default() }];
+        let variants = VariantOverrides::default();
+        assert_eq!(fill(cases.clone(), false, &variants).host_endianness, None);
+        assert_eq!(fill(cases, true, &variants).host_endianness, Some(Endianness::host()));
+    }
+
+    #[test]
+    fn fill_pinned_records_the_requested_pinning_even_if_applying_it_failed() {
+        let cases = vec![InputOnlyCase { instr: Instr::Add, input: InstructionInput::default() }];
+        let pinning = Pinning { cpu: Some(0), nice: None };
+        let whole_test = fill_pinned(cases, false, &VariantOverrides::default(), pinning, None);
+        assert_eq!(whole_test.pinning, Some(pinning));
+    }
+
+    #[test]
+    fn fill_parallel_agrees_with_fill_and_preserves_order() {
+        let cases: Vec<InputOnlyCase> = (0..37)
+            .map(|ra| InputOnlyCase {
+                instr: Instr::Add,
+                input: InstructionInput { ra, ..InstructionInput::default() },
+            })
+            .collect();
+        let variants = VariantOverrides::default();
+
+        let sequential = fill(cases.clone(), false, &variants);
+        let parallel = fill_parallel(cases.clone(), false, &variants, None);
+        let single_threaded = fill_parallel(cases, false, &variants, Some(1));
+
+        assert_eq!(parallel.test_cases.len(), sequential.test_cases.len());
+        for (a, b) in parallel.test_cases.iter().zip(&sequential.test_cases) {
+            assert_eq!(a.input.ra, b.input.ra);
+            assert_eq!(a.model_output, b.model_output);
+        }
+        assert_eq!(single_threaded.test_cases.len(), sequential.test_cases.len());
+        for (a, b) in single_threaded.test_cases.iter().zip(&sequential.test_cases) {
+            assert_eq!(a.input.ra, b.input.ra);
+            assert_eq!(a.model_output, b.model_output);
+        }
+    }
+
+    #[test]
+    fn run_batch_agrees_with_fill() {
+        let pairs: Vec<(Instr, InstructionInput)> = (0..5)
+            .map(|ra| (Instr::Add, InstructionInput { ra, ..InstructionInput::default() }))
+            .collect();
+        let cases: Vec<InputOnlyCase> =
+            pairs.iter().map(|&(instr, input)| InputOnlyCase { instr, input }).collect();
+        let variants = VariantOverrides::default();
+
+        let expected = fill(cases, false, &variants);
+        let batched: Vec<TestCase> = run_batch(pairs, false, &variants).collect();
+
+        assert_eq!(batched.len(), expected.test_cases.len());
+        for (a, b) in batched.iter().zip(&expected.test_cases) {
+            assert_eq!(a.input.ra, b.input.ra);
+            assert_eq!(a.model_output, b.model_output);
+        }
+    }
+
+    #[test]
+    fn run_batch_parallel_preserves_order_and_agrees_with_run_batch() {
+        let pairs: Vec<(Instr, InstructionInput)> = (0..37)
+            .map(|ra| (Instr::Add, InstructionInput { ra, ..InstructionInput::default() }))
+            .collect();
+        let variants = VariantOverrides::default();
+
+        let sequential: Vec<TestCase> = run_batch(pairs.clone(), false, &variants).collect();
+        let parallel: Vec<TestCase> = run_batch_parallel(pairs, false, &variants, None).collect();
+
+        assert_eq!(parallel.len(), sequential.len());
+        for (a, b) in parallel.iter().zip(&sequential) {
+            assert_eq!(a.input.ra, b.input.ra);
+            assert_eq!(a.model_output, b.model_output);
+        }
+    }
+
+    #[test]
+    fn resolve_num_threads_honors_an_explicit_request_over_the_hosts_parallelism() {
+        assert_eq!(resolve_num_threads(Some(1)), 1);
+        assert_eq!(resolve_num_threads(Some(7)), 7);
+    }
+
+    #[test]
+    fn resolve_num_threads_treats_an_explicit_zero_the_same_as_unset() {
+        assert_eq!(resolve_num_threads(Some(0)), resolve_num_threads(None));
+    }
+
+    #[test]
+    fn fill_parallel_does_not_panic_when_asked_for_zero_threads() {
+        let cases = vec![InputOnlyCase { instr: Instr::Add, input: InstructionInput::default() }];
+        let whole_test = fill_parallel(cases, false, &VariantOverrides::default(), Some(0));
+        assert_eq!(whole_test.test_cases.len(), 1);
+    }
+}
@@ -0,0 +1,367 @@
+//! A small boolean expression language for selecting which generated
+//! cases to keep (`pia fill --filter`), e.g. `ov32 != ov && ra < 0`, so a
+//! targeted corpus can be built directly instead of generating everything
+//! and post-processing gigabytes of JSON afterward.
+//!
+//! Expressions are evaluated against one case's [`InstructionInput`]
+//! (`ra`/`rb`/`rc`) and the model's computed [`InstructionOutput`] (`rt`,
+//! and the XER bits `so`/`ov`/`ca`/`ov32`/`ca32`, read as `false` if the
+//! instruction doesn't set XER at all) -- the fields available before
+//! native execution ever runs, since the point is trimming the corpus down
+//! before paying for that.
+
+use crate::types::{InstructionInput, InstructionOutput};
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed filter predicate. Build one via [`FromStr`] (what the CLI's
+/// `--filter` uses) or directly; [`FilterExpr::matches`] is a plain
+/// function of `(InstructionInput, InstructionOutput)`, so a Rust caller
+/// that would rather write a closure than a string doesn't have to go
+/// through this type at all.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FilterExpr {
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Compare(Field, CmpOp, Operand),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Field {
+    Ra,
+    Rb,
+    Rc,
+    Rt,
+    So,
+    Ov,
+    Ca,
+    Ov32,
+    Ca32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operand {
+    Field(Field),
+    Literal(i64),
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "ra" => Some(Field::Ra),
+            "rb" => Some(Field::Rb),
+            "rc" => Some(Field::Rc),
+            "rt" => Some(Field::Rt),
+            "so" => Some(Field::So),
+            "ov" => Some(Field::Ov),
+            "ca" => Some(Field::Ca),
+            "ov32" => Some(Field::Ov32),
+            "ca32" => Some(Field::Ca32),
+            _ => None,
+        }
+    }
+
+    /// This field's value for `(input, output)`, as a signed 64-bit word
+    /// (booleans read as `0`/`1`).
+    fn value(self, input: &InstructionInput, output: &InstructionOutput) -> i64 {
+        match self {
+            Field::Ra => input.ra as i64,
+            Field::Rb => input.rb as i64,
+            Field::Rc => input.rc as i64,
+            Field::Rt => output.rt.unwrap_or(0) as i64,
+            Field::So => output.xer.is_some_and(|xer| xer.so) as i64,
+            Field::Ov => output.xer.is_some_and(|xer| xer.ov) as i64,
+            Field::Ca => output.xer.is_some_and(|xer| xer.ca) as i64,
+            Field::Ov32 => output.xer.is_some_and(|xer| xer.ov32) as i64,
+            Field::Ca32 => output.xer.is_some_and(|xer| xer.ca32) as i64,
+        }
+    }
+}
+
+impl Operand {
+    fn value(self, input: &InstructionInput, output: &InstructionOutput) -> i64 {
+        match self {
+            Operand::Field(field) => field.value(input, output),
+            Operand::Literal(value) => value,
+        }
+    }
+}
+
+impl CmpOp {
+    fn apply(self, a: i64, b: i64) -> bool {
+        match self {
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+            CmpOp::Lt => a < b,
+            CmpOp::Le => a <= b,
+            CmpOp::Gt => a > b,
+            CmpOp::Ge => a >= b,
+        }
+    }
+}
+
+impl FilterExpr {
+    /// Whether `input`/`output` satisfy this predicate.
+    pub fn matches(&self, input: &InstructionInput, output: &InstructionOutput) -> bool {
+        match self {
+            FilterExpr::And(a, b) => a.matches(input, output) && b.matches(input, output),
+            FilterExpr::Or(a, b) => a.matches(input, output) || b.matches(input, output),
+            FilterExpr::Not(a) => !a.matches(input, output),
+            FilterExpr::Compare(field, op, rhs) => {
+                op.apply(field.value(input, output), rhs.value(input, output))
+            }
+        }
+    }
+}
+
+/// Returned by [`FromStr::from_str`] for [`FilterExpr`] when `source`
+/// isn't a well-formed expression.
+#[derive(Debug)]
+pub struct ParseFilterError(String);
+
+impl fmt::Display for ParseFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid filter expression: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseFilterError {}
+
+impl FromStr for FilterExpr {
+    type Err = ParseFilterError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(source).map_err(ParseFilterError)?;
+        let mut parser = Parser { tokens: &tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        if parser.pos != parser.tokens.len() {
+            return Err(ParseFilterError(format!("unexpected trailing input at token {}", parser.pos)));
+        }
+        Ok(expr)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(i64),
+    And,
+    Or,
+    Not,
+    Op(CmpOp),
+    LParen,
+    RParen,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = source.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if chars[i..].starts_with(&['&', '&']) {
+            tokens.push(Token::And);
+            i += 2;
+        } else if chars[i..].starts_with(&['|', '|']) {
+            tokens.push(Token::Or);
+            i += 2;
+        } else if chars[i..].starts_with(&['=', '=']) {
+            tokens.push(Token::Op(CmpOp::Eq));
+            i += 2;
+        } else if chars[i..].starts_with(&['!', '=']) {
+            tokens.push(Token::Op(CmpOp::Ne));
+            i += 2;
+        } else if chars[i..].starts_with(&['<', '=']) {
+            tokens.push(Token::Op(CmpOp::Le));
+            i += 2;
+        } else if chars[i..].starts_with(&['>', '=']) {
+            tokens.push(Token::Op(CmpOp::Ge));
+            i += 2;
+        } else if c == '<' {
+            tokens.push(Token::Op(CmpOp::Lt));
+            i += 1;
+        } else if c == '>' {
+            tokens.push(Token::Op(CmpOp::Gt));
+            i += 1;
+        } else if c == '!' {
+            tokens.push(Token::Not);
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())) {
+            let start = i;
+            i += 1;
+            while chars.get(i).is_some_and(|c| c.is_ascii_alphanumeric() || *c == 'x') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = if let Some(hex) = text.strip_prefix("0x") {
+                i64::from_str_radix(hex, 16).map_err(|err| err.to_string())?
+            } else if let Some(hex) = text.strip_prefix("-0x") {
+                -i64::from_str_radix(hex, 16).map_err(|err| err.to_string())?
+            } else {
+                text.parse().map_err(|_| format!("invalid number: {:?}", text))?
+            };
+            tokens.push(Token::Number(value));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while chars.get(i).is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            return Err(format!("unexpected character: {:?}", c));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, ParseFilterError> {
+        let mut lhs = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            let rhs = self.parse_and()?;
+            lhs = FilterExpr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, ParseFilterError> {
+        let mut lhs = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            let rhs = self.parse_unary()?;
+            lhs = FilterExpr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterExpr, ParseFilterError> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(FilterExpr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<FilterExpr, ParseFilterError> {
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let inner = self.parse_or()?;
+            if self.peek() != Some(&Token::RParen) {
+                return Err(ParseFilterError("expected closing parenthesis".to_string()));
+            }
+            self.pos += 1;
+            return Ok(inner);
+        }
+        let field = self.parse_field()?;
+        let op = match self.peek() {
+            Some(Token::Op(op)) => *op,
+            other => return Err(ParseFilterError(format!("expected a comparison operator, found {:?}", other))),
+        };
+        self.pos += 1;
+        let rhs = self.parse_operand()?;
+        Ok(FilterExpr::Compare(field, op, rhs))
+    }
+
+    fn parse_field(&mut self) -> Result<Field, ParseFilterError> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                Field::parse(name).ok_or_else(|| ParseFilterError(format!("unknown field: {:?}", name)))
+            }
+            other => Err(ParseFilterError(format!("expected a field name, found {:?}", other))),
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, ParseFilterError> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Number(value)) => {
+                self.pos += 1;
+                Ok(Operand::Literal(*value))
+            }
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                Field::parse(name).map(Operand::Field).ok_or_else(|| ParseFilterError(format!("unknown field: {:?}", name)))
+            }
+            other => Err(ParseFilterError(format!("expected a field or a number, found {:?}", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Xer;
+
+    fn case(ra: u64, xer: Xer) -> (InstructionInput, InstructionOutput) {
+        (
+            InstructionInput { ra, ..InstructionInput::default() },
+            InstructionOutput { xer: Some(xer), ..InstructionOutput::default() },
+        )
+    }
+
+    #[test]
+    fn parses_and_evaluates_a_compound_expression() {
+        let filter: FilterExpr = "ov32 != ov && ra < 0".parse().unwrap();
+        let (input, output) = case((-1i64) as u64, Xer { ov: true, ov32: false, ..Xer::default() });
+        assert!(filter.matches(&input, &output));
+
+        let (input, output) = case(1, Xer { ov: true, ov32: false, ..Xer::default() });
+        assert!(!filter.matches(&input, &output));
+    }
+
+    #[test]
+    fn parses_parentheses_and_or_and_not() {
+        let filter: FilterExpr = "!(ra == 0) || rb == 1".parse().unwrap();
+        let (input, output) = case(0, Xer::default());
+        assert!(!filter.matches(&input, &output));
+
+        let (input, output) = case(5, Xer::default());
+        assert!(filter.matches(&input, &output));
+    }
+
+    #[test]
+    fn parses_hex_literals() {
+        let filter: FilterExpr = "ra == 0xff".parse().unwrap();
+        let (input, output) = case(0xff, Xer::default());
+        assert!(filter.matches(&input, &output));
+    }
+
+    #[test]
+    fn rejects_an_unknown_field() {
+        assert!("bogus == 0".parse::<FilterExpr>().is_err());
+    }
+
+    #[test]
+    fn rejects_trailing_garbage() {
+        assert!("ra == 0 ra".parse::<FilterExpr>().is_err());
+    }
+}
@@ -0,0 +1,74 @@
+//! Configurable hex formatting for `u64` register values in output, so
+//! generated capture files can match the conventions other Libre-SOC
+//! tooling expects (padding width, case, digit grouping).
+
+/// How to render a `u64` as a hex string.
+#[derive(Clone, Copy, Debug)]
+pub struct HexFormatConfig {
+    /// Zero-pad to this many hex digits (0 to disable padding).
+    pub width: usize,
+    pub lowercase: bool,
+    /// Insert `_` between groups of this many digits, counted from the
+    /// right (`None` to disable grouping).
+    pub group_by: Option<usize>,
+}
+
+impl Default for HexFormatConfig {
+    fn default() -> Self {
+        Self {
+            width: 16,
+            lowercase: true,
+            group_by: None,
+        }
+    }
+}
+
+/// Formats `value` as `0x...` according to `config`.
+pub fn format_hex(value: u64, config: &HexFormatConfig) -> String {
+    let digits = if config.lowercase {
+        format!("{:0width$x}", value, width = config.width)
+    } else {
+        format!("{:0width$X}", value, width = config.width)
+    };
+    let digits = match config.group_by {
+        Some(n) if n > 0 => group_digits(&digits, n),
+        _ => digits,
+    };
+    format!("0x{}", digits)
+}
+
+fn group_digits(digits: &str, group_by: usize) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / group_by);
+    for (index, ch) in digits.chars().rev().enumerate() {
+        if index != 0 && index % group_by == 0 {
+            grouped.push('_');
+        }
+        grouped.push(ch);
+    }
+    grouped.chars().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_and_groups() {
+        let config = HexFormatConfig {
+            width: 8,
+            lowercase: true,
+            group_by: Some(4),
+        };
+        assert_eq!(format_hex(0xabcd, &config), "0x0000_abcd");
+    }
+
+    #[test]
+    fn uppercase_no_padding() {
+        let config = HexFormatConfig {
+            width: 0,
+            lowercase: false,
+            group_by: None,
+        };
+        assert_eq!(format_hex(0xabcd, &config), "0xABCD");
+    }
+}
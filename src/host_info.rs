@@ -0,0 +1,85 @@
+//! Host capability/environment fingerprinting beyond `cpu_model` (see
+//! [`crate::cache::CacheKey`]): the auxiliary vector capability bits
+//! (`AT_HWCAP`/`AT_HWCAP2`) and `/proc/cpuinfo`'s `MMU` field, captured
+//! alongside a [`crate::capture::WholeTest`] so behavioral differences
+//! between machines -- a DD revision that fixed an erratum, firmware that
+//! disabled a feature, radix vs. hash MMU -- can be correlated with
+//! capture divergences instead of just blamed on "some other machine".
+//!
+//! Linux-only, and only meaningful paired with the `powerpc64` native
+//! backend -- see [`crate::native`].
+
+use serde::{Deserialize, Serialize};
+
+/// A snapshot of host capability/environment info, taken once per native
+/// execution batch. Every field is `None` off Linux/powerpc64, or if the
+/// underlying probe failed -- purely forensic, like [`crate::affinity::Pinning`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct HostInfo {
+    /// `getauxval(AT_HWCAP)`.
+    pub hwcap: Option<u64>,
+    /// `getauxval(AT_HWCAP2)`.
+    pub hwcap2: Option<u64>,
+    /// `/proc/cpuinfo`'s `MMU` field (e.g. `"Radix"` or `"Hash"`).
+    pub mmu: Option<String>,
+}
+
+impl HostInfo {
+    /// Probes the current host.
+    pub fn probe() -> Self {
+        #[cfg(all(target_os = "linux", target_arch = "powerpc64"))]
+        {
+            linux::probe()
+        }
+        #[cfg(not(all(target_os = "linux", target_arch = "powerpc64")))]
+        {
+            Self::default()
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "powerpc64"))]
+mod linux {
+    use super::HostInfo;
+    use std::fs;
+
+    pub fn probe() -> HostInfo {
+        HostInfo { hwcap: read_auxval(libc::AT_HWCAP), hwcap2: read_auxval(libc::AT_HWCAP2), mmu: read_mmu_field() }
+    }
+
+    fn read_auxval(at: libc::c_ulong) -> Option<u64> {
+        // SAFETY: `getauxval` just reads the process's already-populated
+        // auxiliary vector; it takes no pointers and has no preconditions
+        // beyond `at` being a recognized AT_* constant.
+        let value = unsafe { libc::getauxval(at) };
+        // glibc returns 0 for both "the real value is 0" and "this AT_*
+        // type isn't present"; there's no way to tell those apart, so a
+        // genuine zero is reported as `None` rather than a misleadingly
+        // precise one.
+        (value != 0).then_some(value as u64)
+    }
+
+    fn read_mmu_field() -> Option<String> {
+        let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+        cpuinfo.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == "MMU").then(|| value.trim().to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_host_info_leaves_every_field_unset() {
+        assert_eq!(HostInfo::default(), HostInfo { hwcap: None, hwcap2: None, mmu: None });
+    }
+
+    #[cfg(not(all(target_os = "linux", target_arch = "powerpc64")))]
+    #[test]
+    fn probe_off_linux_powerpc64_leaves_every_field_unset() {
+        assert_eq!(HostInfo::probe(), HostInfo::default());
+    }
+}
@@ -0,0 +1,73 @@
+//! An independently-written, host-compiled reimplementation of the
+//! multiply/divide family (`mulld`/`mulhdu`/`divd`/`divdu`), selectable via
+//! [`crate::model::Variant::HostIntrinsics`].
+//!
+//! Native execution ([`crate::native`]) and the default model
+//! ([`crate::model::dispatch`]) are already two opinions on this family's
+//! results; when they disagree, it's not obvious from that alone whether
+//! the asm harness or the model's arithmetic is at fault. This module is a
+//! third, deliberately independent opinion: plain 128-bit Rust arithmetic
+//! compiled for the host rather than the POWER target -- on a host with no
+//! native 128-bit multiply/divide, this lowers to the same
+//! `__multi3`/`__udivti3`-style compiler builtins any other crate doing
+//! 128-bit math would use. It's written without looking at `dispatch`'s
+//! implementation, so the two are unlikely to share a mistake.
+
+use crate::instr::Instr;
+use crate::types::{InstructionInput, InstructionOutput};
+
+/// The instructions this module has an independent implementation for.
+pub const COVERED: &[Instr] = &[Instr::Mulld, Instr::Mulhdu, Instr::Divd, Instr::Divdu];
+
+/// Computes `instr`'s result via this module's independent implementation,
+/// or `None` if `instr` isn't in [`COVERED`].
+pub fn model(instr: Instr, input: InstructionInput) -> Option<InstructionOutput> {
+    let rt = match instr {
+        Instr::Mulld => ((input.ra as i128).wrapping_mul(input.rb as i128)) as u64,
+        Instr::Mulhdu => ((input.ra as u128 * input.rb as u128) >> 64) as u64,
+        Instr::Divd => {
+            let (a, b) = (input.ra as i64, input.rb as i64);
+            if b == 0 || (a == i64::MIN && b == -1) {
+                0
+            } else {
+                (a as i128 / b as i128) as u64
+            }
+        }
+        Instr::Divdu => {
+            if input.rb == 0 {
+                0
+            } else {
+                (input.ra as u128 / input.rb as u128) as u64
+            }
+        }
+        _ => return None,
+    };
+    Some(InstructionOutput { rt: Some(rt), ..InstructionOutput::default() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agrees_with_the_default_model_for_every_covered_instruction_across_corner_cases() {
+        for &instr in COVERED {
+            for (_, input) in crate::corner_cases::corner_case_inputs(instr) {
+                let expected = crate::model::model(instr, input);
+                let actual = model(instr, input).expect("instr is in COVERED");
+                assert_eq!(actual.rt, expected.rt, "{}: {:?}", instr, input);
+            }
+        }
+    }
+
+    #[test]
+    fn divd_by_zero_returns_zero_like_the_default_model() {
+        let input = InstructionInput { ra: 42, rb: 0, ..InstructionInput::default() };
+        assert_eq!(model(Instr::Divd, input).unwrap().rt, Some(0));
+    }
+
+    #[test]
+    fn uncovered_instructions_return_none() {
+        assert_eq!(model(Instr::Add, InstructionInput::default()), None);
+    }
+}
@@ -0,0 +1,429 @@
+//! The set of instructions known to the analyzer.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// An instruction that the analyzer knows how to execute natively and/or
+/// model in software.
+///
+/// This is hand-written for now; later work extends generation of the
+/// per-instruction dispatch (see the `pia-proc-macro` crate once it lands).
+///
+/// Serializes as its canonical mnemonic (see [`Instr::name`]) rather than
+/// the Rust variant name, and parses (both via [`FromStr`] and serde) any
+/// of [`Instr::aliases`] too, so captures and CLI input don't have to match
+/// this crate's naming exactly.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Instr {
+    Add,
+    AddO,
+    /// Rc-form of `add` (`add.`): like [`Instr::Add`] but also sets CR0
+    /// from the signed result, following the same per-variant convention
+    /// already used for [`Instr::AddO`].
+    AddDot,
+    AddC,
+    AddE,
+    Subf,
+    SubfO,
+    Mulld,
+    Mulhdu,
+    Divd,
+    Divdu,
+    /// Byte-reverse halfwords (ISA 3.1). Model-only: no POWER9 can execute
+    /// it natively.
+    Brh,
+    /// Byte-reverse words (ISA 3.1). Model-only: no POWER9 can execute it
+    /// natively.
+    Brw,
+    /// Byte-reverse the doubleword (ISA 3.1). Model-only: no POWER9 can
+    /// execute it natively.
+    Brd,
+    /// Centrifuge doubleword (ISA 3.1). Model-only: no POWER9 can execute
+    /// it natively.
+    Cfuged,
+    /// Count leading zeros doubleword under mask (ISA 3.1). Model-only: no
+    /// POWER9 can execute it natively.
+    Cntlzdm,
+    /// Count trailing zeros doubleword under mask (ISA 3.1). Model-only: no
+    /// POWER9 can execute it natively.
+    Cnttzdm,
+    /// Parallel bit deposit doubleword (ISA 3.1). Model-only: no POWER9
+    /// can execute it natively.
+    Pdepd,
+    /// Parallel bit extract doubleword (ISA 3.1). Model-only: no POWER9
+    /// can execute it natively.
+    Pextd,
+    /// Move from FPSCR and set the rounding mode (ISA 3.0). Model-only:
+    /// this crate doesn't model the FPR file, so there's no native
+    /// wrapper to load/read an `FRT`/`FRB` through (see
+    /// [`crate::types::Fpscr`]).
+    Mffscrn,
+    /// Move from FPSCR and clear the exception enables (ISA 3.0).
+    /// Model-only for the same reason as [`Instr::Mffscrn`].
+    Mffsce,
+    /// Shift left word, zero-filled, shift count taken from the low 6 bits
+    /// of `rb` (shift amounts `>= 32` give a result of `0`). Model-only:
+    /// unlike the rest of this crate's XO-form instructions, shift
+    /// instructions swap which field holds the destination (`RA`, not
+    /// `RT`) versus the shifted value (`RS`, in the field encode/decode
+    /// otherwise treat as `RT`), and [`crate::native`]'s jit-lite backend
+    /// doesn't yet support that field swap.
+    Slw,
+    /// Shift right word, zero-filled, shift count taken from the low 6
+    /// bits of `rb` (shift amounts `>= 32` give a result of `0`).
+    /// Model-only for the same reason as [`Instr::Slw`].
+    Srw,
+    /// Shift right algebraic word: like [`Instr::Srw`], but sign-filled
+    /// (shift amounts `>= 32` give a result of `0` or `-1` depending on
+    /// the sign of `ra`'s low 32 bits) and sets `CA`/`CA32` to whether any
+    /// `1` bits were shifted out of a negative value. Model-only for the
+    /// same reason as [`Instr::Slw`].
+    Sraw,
+    /// Shift left doubleword, zero-filled, shift count taken from the low
+    /// 7 bits of `rb` (shift amounts `>= 64` give a result of `0`).
+    /// Model-only for the same reason as [`Instr::Slw`].
+    Sld,
+    /// Shift right doubleword, zero-filled, shift count taken from the low
+    /// 7 bits of `rb` (shift amounts `>= 64` give a result of `0`).
+    /// Model-only for the same reason as [`Instr::Slw`].
+    Srd,
+    /// Shift right algebraic doubleword: like [`Instr::Srd`], but
+    /// sign-filled (shift amounts `>= 64` give a result of `0` or `-1`
+    /// depending on the sign of `ra`) and sets `CA`/`CA32` to whether any
+    /// `1` bits were shifted out of a negative value. Model-only for the
+    /// same reason as [`Instr::Slw`].
+    Srad,
+}
+
+/// The privilege level the POWER ISA requires to execute an instruction.
+/// Ordered from least to most privileged so a range check (`>= Privileged`)
+/// is meaningful.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum Privilege {
+    /// Runs in problem state, i.e. ordinary userspace code.
+    Problem,
+    /// Requires supervisor state (the OS kernel).
+    Privileged,
+    /// Requires hypervisor state.
+    Hypervisor,
+}
+
+impl Instr {
+    /// All instructions known to the analyzer, in a stable order.
+    pub const ALL: &'static [Instr] = &[
+        Instr::Add,
+        Instr::AddO,
+        Instr::AddDot,
+        Instr::AddC,
+        Instr::AddE,
+        Instr::Subf,
+        Instr::SubfO,
+        Instr::Mulld,
+        Instr::Mulhdu,
+        Instr::Divd,
+        Instr::Divdu,
+        Instr::Brh,
+        Instr::Brw,
+        Instr::Brd,
+        Instr::Cfuged,
+        Instr::Cntlzdm,
+        Instr::Cnttzdm,
+        Instr::Pdepd,
+        Instr::Pextd,
+        Instr::Mffscrn,
+        Instr::Mffsce,
+        Instr::Slw,
+        Instr::Srw,
+        Instr::Sraw,
+        Instr::Sld,
+        Instr::Srd,
+        Instr::Srad,
+    ];
+
+    /// The lower-case mnemonic used in assembly and in the CLI.
+    ///
+    /// Looked up from [`NAMES`] by discriminant rather than matched, since
+    /// this is consulted on every assemble/disassemble/serialize call; the
+    /// tradeoff is that [`NAMES`] (and [`MODEL_ONLY`]) have to be kept in
+    /// the same order as the enum declaration by hand -- unlike a match,
+    /// nothing here makes the compiler reject a table that's fallen out of
+    /// sync, so [`tests::tables_cover_every_instruction_in_declaration_order`]
+    /// stands in for that check.
+    #[inline]
+    pub fn name(self) -> &'static str {
+        NAMES[self as usize]
+    }
+
+    /// Whether no currently-supported native-execution host can run this
+    /// instruction, so only the software model can be consulted for it
+    /// (e.g. ISA 3.1 instructions on POWER9).
+    #[inline]
+    pub fn is_model_only(self) -> bool {
+        MODEL_ONLY[self as usize]
+    }
+
+    /// The privilege level the POWER ISA requires to execute this
+    /// instruction. Everything this crate currently models runs in
+    /// problem state; this exists so SPR and other privileged instructions
+    /// can be tagged accordingly as they're added, without every caller
+    /// that already assumes userspace execution having to change (see
+    /// [`Instr::is_model_only`] for the same reasoning applied to native
+    /// support).
+    #[inline]
+    pub fn required_privilege(self) -> Privilege {
+        PRIVILEGE[self as usize]
+    }
+
+    /// Whether this instruction has no word-sized (32-bit) form in the
+    /// POWER ISA, unlike `add`/`subf`/... which run (at their native GPR
+    /// width) on both 32-bit and 64-bit implementations. Consulted by
+    /// [`crate::native`] to reject these on a 32-bit `powerpc`
+    /// native-execution host, which has no 64-bit GPRs to hold their
+    /// operands.
+    #[inline]
+    pub fn requires_doubleword_gprs(self) -> bool {
+        REQUIRES_DOUBLEWORD_GPRS[self as usize]
+    }
+
+    /// A stable numeric ID for this instruction (its position in
+    /// declaration order, see [`Instr::ALL`]), for binary capture formats
+    /// and FFI callers that would rather not carry the mnemonic string
+    /// around. Stable across a given crate version; a new instruction is
+    /// always appended to the end of the enum so existing IDs don't shift.
+    #[inline]
+    pub fn id(self) -> u16 {
+        self as u16
+    }
+
+    /// The Rc-form (CR0-setting) variant of this instruction, if this crate
+    /// models one, e.g. `Instr::Add.rc_form() == Some(Instr::AddDot)`. Used
+    /// by [`crate::decoder::decode`] to resolve a decoded word's rc bit;
+    /// instructions with no modeled Rc-form (the common case, for now)
+    /// return `None`, and that bit is treated as reserved.
+    pub fn rc_form(self) -> Option<Instr> {
+        match self {
+            Instr::Add => Some(Instr::AddDot),
+            _ => None,
+        }
+    }
+
+    /// Whether executing `self` updates `CR0` from the signed result, i.e.
+    /// this *is* an Rc-form (the inverse question from [`Self::rc_form`]).
+    /// Used by [`crate::native::jit`] to decide whether a native capture's
+    /// [`crate::types::InstructionOutput::cr0`] should be `Some` or `None`,
+    /// matching [`crate::model`]'s per-instruction dispatch -- every
+    /// Rc-form mnemonic ends in `.` (see [`NAMES`]), so this just checks
+    /// that rather than re-deriving [`Self::rc_form`]'s mapping in reverse.
+    pub fn writes_cr0(self) -> bool {
+        self.name().ends_with('.')
+    }
+
+    /// Whether executing `self` always updates some `XER` field (see
+    /// [`crate::types::Xer`]), matching [`crate::model`]'s per-instruction
+    /// dispatch: the OE-form overflow-recording variants, plus the
+    /// carry-producing non-O forms that always touch `ca`/`ca32`
+    /// regardless of `OE`. Used by [`crate::native::jit`] for the same
+    /// reason as [`Self::writes_cr0`]. A newly-added [`Instr`] variant with
+    /// XER-writing semantics needs an arm here, the same way it needs one
+    /// in [`crate::model::dispatch`]; [`crate::model::tests::writes_cr0_and_writes_xer_match_model_dispatch`]
+    /// catches the two falling out of sync.
+    pub fn writes_xer(self) -> bool {
+        matches!(self, Instr::AddO | Instr::AddC | Instr::AddE | Instr::SubfO)
+    }
+
+    /// Historical/alternate mnemonics that should also parse to this
+    /// instruction, e.g. names used by older assemblers or documents.
+    pub fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Instr::Subf => &["sf"],
+            Instr::SubfO => &["sfo"],
+            Instr::AddC => &["a"],
+            _ => &[],
+        }
+    }
+}
+
+/// Mnemonics indexed by discriminant, parallel to the enum declaration (and
+/// to [`Instr::ALL`]). Backs [`Instr::name`].
+const NAMES: &[&str] = &[
+    "add", "addo", "add.", "addc", "adde", "subf", "subfo", "mulld", "mulhdu", "divd", "divdu", "brh", "brw", "brd",
+    "cfuged", "cntlzdm", "cnttzdm", "pdepd", "pextd", "mffscrn", "mffsce", "slw", "srw", "sraw", "sld", "srd",
+    "srad",
+];
+
+/// Whether each instruction is model-only, indexed by discriminant in the
+/// same order as [`NAMES`]. Backs [`Instr::is_model_only`].
+const MODEL_ONLY: &[bool] = &[
+    false, false, false, false, false, false, false, false, false, false, false, true, true, true, true, true,
+    true, true, true, true, true, true, true, true, true, true, true,
+];
+
+/// Required privilege level for each instruction, indexed by discriminant
+/// in the same order as [`NAMES`]. Backs [`Instr::required_privilege`].
+const PRIVILEGE: &[Privilege] = &[
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+    Privilege::Problem,
+];
+
+/// Whether each instruction requires 64-bit-wide GPRs, indexed by
+/// discriminant in the same order as [`NAMES`]. Backs
+/// [`Instr::requires_doubleword_gprs`]. True for the doubleword-only
+/// arithmetic opcodes (`mulld`, `mulhdu`, `divd`, `divdu`) and the
+/// doubleword shifts (`sld`, `srd`, `srad`); every other currently-modeled
+/// instruction either runs at whatever width the host GPRs are (`add`,
+/// `subf`, the word shifts, ...) or is model-only, so the distinction
+/// doesn't apply to it.
+const REQUIRES_DOUBLEWORD_GPRS: &[bool] = &[
+    false, false, false, false, false, false, false, true, true, true, true, false, false, false, false, false,
+    false, false, false, false, false, false, false, false, true, true, true,
+];
+
+impl fmt::Display for Instr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Returned by [`FromStr::from_str`]/[`TryFrom<String>`] for [`Instr`] when
+/// the mnemonic isn't recognized.
+#[derive(Debug)]
+pub struct ParseInstrError(String);
+
+impl fmt::Display for ParseInstrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unrecognized instruction mnemonic: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseInstrError {}
+
+impl FromStr for Instr {
+    type Err = ParseInstrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase();
+        Instr::ALL
+            .iter()
+            .copied()
+            .find(|instr| instr.name() == normalized || instr.aliases().contains(&normalized.as_str()))
+            .ok_or_else(|| ParseInstrError(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for Instr {
+    type Error = ParseInstrError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Returned by `TryFrom<u16> for Instr` when the ID doesn't name an
+/// instruction in [`Instr::ALL`].
+#[derive(Debug)]
+pub struct UnknownInstrId(pub u16);
+
+impl fmt::Display for UnknownInstrId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unrecognized instruction ID: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownInstrId {}
+
+impl TryFrom<u16> for Instr {
+    type Error = UnknownInstrId;
+
+    fn try_from(id: u16) -> Result<Self, Self::Error> {
+        Instr::ALL.get(id as usize).copied().ok_or(UnknownInstrId(id))
+    }
+}
+
+impl From<Instr> for String {
+    fn from(instr: Instr) -> String {
+        instr.name().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_canonical_and_alias_case_insensitively() {
+        assert_eq!("ADDO".parse::<Instr>().unwrap(), Instr::AddO);
+        assert_eq!("  sf ".parse::<Instr>().unwrap(), Instr::Subf);
+    }
+
+    #[test]
+    fn rejects_unknown_mnemonic() {
+        assert!("not-a-real-instr".parse::<Instr>().is_err());
+    }
+
+    #[test]
+    fn id_round_trips_through_try_from() {
+        for &instr in Instr::ALL {
+            assert_eq!(Instr::try_from(instr.id()).unwrap(), instr);
+        }
+        assert!(Instr::try_from(u16::MAX).is_err());
+    }
+
+    #[test]
+    fn tables_cover_every_instruction_in_declaration_order() {
+        assert_eq!(NAMES.len(), Instr::ALL.len());
+        assert_eq!(MODEL_ONLY.len(), Instr::ALL.len());
+        assert_eq!(PRIVILEGE.len(), Instr::ALL.len());
+        assert_eq!(REQUIRES_DOUBLEWORD_GPRS.len(), Instr::ALL.len());
+    }
+
+    #[test]
+    fn only_the_64bit_only_arithmetic_opcodes_require_doubleword_gprs() {
+        for &instr in Instr::ALL {
+            let expected = matches!(
+                instr,
+                Instr::Mulld | Instr::Mulhdu | Instr::Divd | Instr::Divdu | Instr::Sld | Instr::Srd | Instr::Srad
+            );
+            assert_eq!(instr.requires_doubleword_gprs(), expected, "{} disagrees with the expected word/doubleword split", instr);
+        }
+    }
+
+    #[test]
+    fn every_currently_modeled_instruction_runs_in_problem_state() {
+        // No SPR or other privileged instruction is modeled yet; this
+        // pins that assumption down so adding one is a deliberate,
+        // visible change to this table rather than a silent default.
+        for &instr in Instr::ALL {
+            assert_eq!(instr.required_privilege(), Privilege::Problem, "{} unexpectedly privileged", instr);
+        }
+        for (i, &instr) in Instr::ALL.iter().enumerate() {
+            assert_eq!(instr as usize, i, "Instr::ALL must list variants in declaration order");
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Renders [`crate::check`] comparison results as JUnit XML, so a Jenkins
+//! or GitLab pipeline can show model/native mismatches as ordinary test
+//! failures instead of parsed-out CLI output.
+
+use crate::capture::WholeTest;
+use crate::check::FieldMismatch;
+use crate::instr::Instr;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+/// How finely to split comparison results into `<testcase>` elements.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Granularity {
+    /// One `<testcase>` per distinct [`Instr`] present in the capture.
+    PerInstr,
+    /// One `<testcase>` per [`crate::capture::TestCase`], named by its
+    /// position in `golden.test_cases`.
+    PerTestCase,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_testcase(out: &mut String, name: &str, failures: &[&FieldMismatch]) {
+    if failures.is_empty() {
+        let _ = writeln!(out, "  <testcase classname=\"power_instruction_analyzer\" name=\"{}\"/>", escape(name));
+        return;
+    }
+    let message = failures.iter().map(|m| m.to_string()).collect::<Vec<_>>().join("; ");
+    let _ = writeln!(out, "  <testcase classname=\"power_instruction_analyzer\" name=\"{}\">", escape(name));
+    let _ = writeln!(out, "    <failure message=\"{}\">{}</failure>", escape(&message), escape(&message));
+    let _ = writeln!(out, "  </testcase>");
+}
+
+/// Groups `mismatches` per [`Granularity`] and renders one `<testsuite>`
+/// covering every case in `golden`.
+pub fn render(golden: &WholeTest, mismatches: &[FieldMismatch], granularity: Granularity) -> String {
+    let cases: Vec<(String, Vec<&FieldMismatch>)> = match granularity {
+        Granularity::PerInstr => {
+            let mut by_instr: BTreeMap<Instr, Vec<&FieldMismatch>> = BTreeMap::new();
+            let mut present = Vec::new();
+            for case in &golden.test_cases {
+                if !present.contains(&case.instr) {
+                    present.push(case.instr);
+                }
+            }
+            for mismatch in mismatches {
+                by_instr.entry(mismatch.instr).or_default().push(mismatch);
+            }
+            present.into_iter().map(|instr| (instr.to_string(), by_instr.remove(&instr).unwrap_or_default())).collect()
+        }
+        Granularity::PerTestCase => golden
+            .test_cases
+            .iter()
+            .enumerate()
+            .map(|(index, case)| {
+                let name = format!("{}[{}]", case.instr, index);
+                let failures = mismatches.iter().filter(|m| m.instr == case.instr && m.input == case.input).collect();
+                (name, failures)
+            })
+            .collect(),
+    };
+
+    let failure_count = cases.iter().filter(|(_, failures)| !failures.is_empty()).count();
+    let mut out = String::new();
+    let _ = writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+    let _ = writeln!(
+        out,
+        "<testsuite name=\"power-instruction-analyzer\" tests=\"{}\" failures=\"{}\">",
+        cases.len(),
+        failure_count
+    );
+    for (name, failures) in &cases {
+        render_testcase(&mut out, name, failures);
+    }
+    out.push_str("</testsuite>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::TestCase;
+    use crate::types::{InstructionInput, InstructionOutput};
+
+    fn golden() -> WholeTest {
+        WholeTest {
+            test_cases: vec![
+                TestCase {
+                    instr: Instr::Add,
+                    input: InstructionInput { ra: 1, ..InstructionInput::default() },
+                    native_output: InstructionOutput::default(),
+                    model_output: InstructionOutput { rt: Some(1), ..InstructionOutput::default() },
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+                TestCase {
+                    instr: Instr::Subf,
+                    input: InstructionInput::default(),
+                    native_output: InstructionOutput::default(),
+                    model_output: InstructionOutput::default(),
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        }
+    }
+
+    fn one_mismatch() -> Vec<FieldMismatch> {
+        vec![FieldMismatch {
+            instr: Instr::Add,
+            input: InstructionInput { ra: 1, ..InstructionInput::default() },
+            source: crate::check::Source::Model,
+            field: "rt",
+            recorded: "Some(1)".to_string(),
+            recomputed: "Some(2)".to_string(),
+            model_revision_changed: None,
+        }]
+    }
+
+    #[test]
+    fn per_instr_reports_one_testcase_per_distinct_instruction() {
+        let xml = render(&golden(), &one_mismatch(), Granularity::PerInstr);
+        assert!(xml.contains("tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("name=\"add\""));
+        assert!(xml.contains("<failure"));
+        assert!(xml.contains("name=\"subf\""));
+    }
+
+    #[test]
+    fn per_test_case_only_fails_the_matching_case() {
+        let xml = render(&golden(), &one_mismatch(), Granularity::PerTestCase);
+        assert!(xml.contains("tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("name=\"add[0]\""));
+        assert!(xml.contains("name=\"subf[1]\"/>"));
+    }
+
+    #[test]
+    fn clean_runs_have_no_failures() {
+        let xml = render(&golden(), &[], Granularity::PerInstr);
+        assert!(xml.contains("failures=\"0\""));
+        assert!(!xml.contains("<failure"));
+    }
+}
@@ -0,0 +1,62 @@
+//! `power-instruction-analyzer`: compares the results of native execution of
+//! POWER instructions against software models of those instructions, to help
+//! find and diagnose bugs in both the models and the HDL they're meant to
+//! describe.
+
+pub mod affinity;
+pub mod asm;
+pub mod audit;
+pub mod bigint;
+pub mod cache;
+pub mod campaign;
+pub mod capture;
+pub mod capture_index;
+pub mod cdiff;
+pub mod check;
+pub mod cluster;
+pub mod cocotb_vectors;
+pub mod composite;
+pub mod corner_cases;
+pub mod decoder;
+pub mod div_report;
+pub mod docgen;
+pub mod dot_form_check;
+pub mod encoder;
+pub mod endian;
+pub mod expr;
+pub mod fields;
+pub mod fill;
+pub mod filter;
+pub mod hex_format;
+pub mod host_info;
+pub mod host_intrinsics;
+pub mod instr;
+pub mod junit;
+pub mod metadata;
+pub mod metrics;
+pub mod model;
+pub mod native;
+pub mod neighborhood;
+pub mod parquet_export;
+pub mod pmu;
+pub mod prng;
+pub mod program;
+pub mod registry;
+pub mod remote;
+pub mod rerun;
+pub mod sequence;
+pub mod solve;
+pub mod sqlite_export;
+#[cfg(feature = "stable-api")]
+pub mod stable_api;
+pub mod sva;
+pub mod taint;
+pub mod timebox;
+pub mod timing;
+pub mod types;
+pub mod vcd;
+pub mod vector;
+
+pub use capture::{TestCase, WholeTest};
+pub use instr::Instr;
+pub use types::{Aliasing, ConditionRegister, InstructionInput, InstructionOutput, Xer};
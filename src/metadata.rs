@@ -0,0 +1,117 @@
+//! Machine-readable metadata about each instruction: operands, which flags
+//! it reads/writes, which ISA version introduced it, and a rough category.
+//! Consumed by `pia dump-isa` and by documentation/decoder generators that
+//! shouldn't have to duplicate this by hand.
+
+use crate::instr::{Instr, Privilege};
+use serde::{Deserialize, Serialize};
+
+/// A fixed-point exception or condition register flag an instruction may
+/// read or write.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Flag {
+    Cr0,
+    So,
+    Ov,
+    Ca,
+    /// `FPSCR`, as modeled by [`crate::types::Fpscr`].
+    Fpscr,
+}
+
+/// A rough grouping used for documentation and table generation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Category {
+    FixedPointArithmetic,
+    ByteManipulation,
+    FloatingPointControl,
+    Shift,
+}
+
+/// Everything the analyzer knows about one instruction, independent of any
+/// particular input.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InstrMetadata {
+    pub instr: Instr,
+    /// This instruction's stable numeric ID (see [`Instr::id`]).
+    pub id: u16,
+    /// GPR operands used, in assembly order (e.g. `["rt", "ra", "rb"]`).
+    pub operands: Vec<&'static str>,
+    pub reads: Vec<Flag>,
+    pub writes: Vec<Flag>,
+    pub isa_version: &'static str,
+    pub category: Category,
+    /// Whether no currently-supported native-execution host can run this
+    /// instruction (see [`Instr::is_model_only`]).
+    pub model_only: bool,
+    /// The privilege level required to execute this instruction (see
+    /// [`Instr::required_privilege`]).
+    pub required_privilege: Privilege,
+    /// The current revision of this instruction's [`crate::model`]
+    /// implementation (see [`model_revision`]).
+    pub model_revision: u32,
+}
+
+/// Looks up the metadata for `instr`.
+pub fn metadata(instr: Instr) -> InstrMetadata {
+    let (operands, reads, writes, isa_version, category): (&[&str], &[Flag], &[Flag], &str, Category) = match instr
+    {
+        Instr::Add => (&["rt", "ra", "rb"], &[], &[], "3.0", Category::FixedPointArithmetic),
+        Instr::AddO => (&["rt", "ra", "rb"], &[Flag::So], &[Flag::So, Flag::Ov], "3.0", Category::FixedPointArithmetic),
+        Instr::AddDot => (&["rt", "ra", "rb"], &[Flag::So], &[Flag::Cr0], "3.0", Category::FixedPointArithmetic),
+        Instr::AddC => (&["rt", "ra", "rb"], &[], &[Flag::Ca], "3.0", Category::FixedPointArithmetic),
+        Instr::AddE => (&["rt", "ra", "rb"], &[Flag::Ca], &[Flag::Ca], "3.0", Category::FixedPointArithmetic),
+        Instr::Subf => (&["rt", "ra", "rb"], &[], &[], "3.0", Category::FixedPointArithmetic),
+        Instr::SubfO => (&["rt", "ra", "rb"], &[Flag::So], &[Flag::So, Flag::Ov], "3.0", Category::FixedPointArithmetic),
+        Instr::Mulld => (&["rt", "ra", "rb"], &[], &[], "3.0", Category::FixedPointArithmetic),
+        Instr::Mulhdu => (&["rt", "ra", "rb"], &[], &[], "3.0", Category::FixedPointArithmetic),
+        Instr::Divd => (&["rt", "ra", "rb"], &[], &[], "3.0", Category::FixedPointArithmetic),
+        Instr::Divdu => (&["rt", "ra", "rb"], &[], &[], "3.0", Category::FixedPointArithmetic),
+        Instr::Brh => (&["rt", "ra"], &[], &[], "3.1", Category::ByteManipulation),
+        Instr::Brw => (&["rt", "ra"], &[], &[], "3.1", Category::ByteManipulation),
+        Instr::Brd => (&["rt", "ra"], &[], &[], "3.1", Category::ByteManipulation),
+        Instr::Cfuged => (&["rt", "ra", "rb"], &[], &[], "3.1", Category::ByteManipulation),
+        Instr::Cntlzdm => (&["rt", "ra", "rb"], &[], &[], "3.1", Category::ByteManipulation),
+        Instr::Cnttzdm => (&["rt", "ra", "rb"], &[], &[], "3.1", Category::ByteManipulation),
+        Instr::Pdepd => (&["rt", "ra", "rb"], &[], &[], "3.1", Category::ByteManipulation),
+        Instr::Pextd => (&["rt", "ra", "rb"], &[], &[], "3.1", Category::ByteManipulation),
+        Instr::Mffscrn => (&["rt", "rb"], &[Flag::Fpscr], &[Flag::Fpscr], "3.0", Category::FloatingPointControl),
+        Instr::Mffsce => (&["rt"], &[Flag::Fpscr], &[Flag::Fpscr], "3.0", Category::FloatingPointControl),
+        Instr::Slw => (&["rt", "ra", "rb"], &[], &[], "3.0", Category::Shift),
+        Instr::Srw => (&["rt", "ra", "rb"], &[], &[], "3.0", Category::Shift),
+        Instr::Sraw => (&["rt", "ra", "rb"], &[], &[Flag::Ca], "3.0", Category::Shift),
+        Instr::Sld => (&["rt", "ra", "rb"], &[], &[], "3.0", Category::Shift),
+        Instr::Srd => (&["rt", "ra", "rb"], &[], &[], "3.0", Category::Shift),
+        Instr::Srad => (&["rt", "ra", "rb"], &[], &[Flag::Ca], "3.0", Category::Shift),
+    };
+    InstrMetadata {
+        instr,
+        id: instr.id(),
+        operands: operands.to_vec(),
+        reads: reads.to_vec(),
+        writes: writes.to_vec(),
+        isa_version,
+        category,
+        model_only: instr.is_model_only(),
+        required_privilege: instr.required_privilege(),
+        model_revision: model_revision(instr),
+    }
+}
+
+/// The current revision of `instr`'s model implementation in
+/// [`crate::model`], bumped whenever that implementation's logic changes
+/// in a way that could produce different output for some input than it
+/// used to. Recorded alongside captured [`crate::capture::TestCase`]s so a
+/// mismatch against an old capture can be attributed to a deliberate model
+/// change instead of a regression -- see [`crate::check`].
+///
+/// Every instruction starts at revision 1; bump an instruction's entry here
+/// (adding a specific match arm above the wildcard) in the same commit that
+/// changes its model.
+pub fn model_revision(_instr: Instr) -> u32 {
+    1
+}
+
+/// Metadata for every instruction in [`Instr::ALL`], in the same order.
+pub fn all_metadata() -> Vec<InstrMetadata> {
+    Instr::ALL.iter().copied().map(metadata).collect()
+}
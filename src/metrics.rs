@@ -0,0 +1,139 @@
+//! A tiny hand-rolled Prometheus exposition endpoint (`pia farm
+//! --metrics-addr`), so an operator running a multi-day farm campaign can
+//! point Grafana/Prometheus at progress/mismatch counts instead of
+//! tailing stderr.
+//!
+//! Like [`crate::remote`]'s `pia serve`, this is a minimal HTTP responder
+//! built directly on `std::net` rather than pulling in an HTTP framework
+//! -- the only client that matters is Prometheus's scraper, which just
+//! wants `GET /metrics` to return the exposition text format.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+/// Progress/mismatch counters for one campaign, updated as cases complete
+/// (see [`crate::remote::Farm::run_sharded`]) and scraped by [`spawn`]'s
+/// endpoint. Cheap to update from multiple farm worker threads: every
+/// field is a plain atomic, so no lock is needed.
+#[derive(Default)]
+pub struct CampaignMetrics {
+    cases_total: AtomicU64,
+    cases_completed: AtomicU64,
+    mismatches: AtomicU64,
+}
+
+impl CampaignMetrics {
+    /// Records how many cases this campaign is expected to run, for the
+    /// `pia_campaign_cases_total` gauge. Safe to call more than once if a
+    /// mismatch-expansion pass (`--expand-mismatches`) adds more cases
+    /// later.
+    pub fn set_total(&self, total: u64) {
+        self.cases_total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn record_case(&self, is_mismatch: bool) {
+        self.cases_completed.fetch_add(1, Ordering::Relaxed);
+        if is_mismatch {
+            self.mismatches.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn render(&self) -> String {
+        format!(
+            "# HELP pia_campaign_cases_total Cases this campaign is expected to run.\n\
+             # TYPE pia_campaign_cases_total gauge\n\
+             pia_campaign_cases_total {total}\n\
+             # HELP pia_campaign_cases_completed Cases run so far.\n\
+             # TYPE pia_campaign_cases_completed counter\n\
+             pia_campaign_cases_completed {completed}\n\
+             # HELP pia_campaign_mismatches Cases where native and model output disagreed.\n\
+             # TYPE pia_campaign_mismatches counter\n\
+             pia_campaign_mismatches {mismatches}\n",
+            total = self.cases_total.load(Ordering::Relaxed),
+            completed = self.cases_completed.load(Ordering::Relaxed),
+            mismatches = self.mismatches.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Binds `addr` and, on success, spawns a background thread serving
+/// `GET /metrics` (and anything else -- the path is ignored, there's only
+/// one thing to serve) from `metrics` for as long as the process runs.
+pub fn spawn(addr: impl ToSocketAddrs, metrics: Arc<CampaignMetrics>) -> io::Result<thread::JoinHandle<()>> {
+    let listener = TcpListener::bind(addr)?;
+    Ok(thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+            let _ = handle_connection(stream, &metrics);
+        }
+    }))
+}
+
+fn handle_connection(stream: TcpStream, metrics: &CampaignMetrics) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+    let body = metrics.render();
+    let mut writer = stream;
+    write!(
+        writer,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpStream as ClientStream;
+
+    #[test]
+    fn render_reports_recorded_cases_and_mismatches() {
+        let metrics = CampaignMetrics::default();
+        metrics.set_total(10);
+        metrics.record_case(false);
+        metrics.record_case(true);
+
+        let body = metrics.render();
+        assert!(body.contains("pia_campaign_cases_total 10"));
+        assert!(body.contains("pia_campaign_cases_completed 2"));
+        assert!(body.contains("pia_campaign_mismatches 1"));
+    }
+
+    #[test]
+    fn a_real_http_request_gets_a_200_with_the_rendered_body() {
+        let metrics = Arc::new(CampaignMetrics::default());
+        metrics.record_case(false);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let metrics_for_thread = Arc::clone(&metrics);
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            handle_connection(stream, &metrics_for_thread).unwrap();
+        });
+
+        let mut client = ClientStream::connect(addr).unwrap();
+        client.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        handle.join().unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("pia_campaign_cases_completed 1"));
+    }
+}
@@ -0,0 +1,1099 @@
+//! Software models of instruction semantics, used as the source of truth
+//! that native execution is checked against.
+
+use crate::instr::Instr;
+use crate::types::{Aliasing, ConditionRegister, Fpscr, InstructionInput, InstructionOutput, RoundingMode, Xer};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Alternate model implementations, selected per-instruction for A/B
+/// testing against different ISA readings (see [`model_with_variant`] and
+/// `pia fill --model-variant`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Variant {
+    /// This instruction's default, POWER-ISA-compliant behavior.
+    Default,
+    /// Divide-by-zero is treated as an error instead of POWER's actual
+    /// non-trapping "result is 0" behavior, for comparing against stricter
+    /// ISA readings.
+    IsaStrict,
+    /// Route this instruction's model through a bit-accurate softfloat
+    /// implementation instead of the host's native floating-point unit, so
+    /// a model bug can be triangulated between host-float, softfloat, and
+    /// hardware results.
+    ///
+    /// No currently-modeled instruction performs floating-point arithmetic
+    /// (this crate has no FPR file; see [`Instr::Mffscrn`] and
+    /// [`Instr::Mffsce`] for the only `FPSCR`-touching instructions it
+    /// models, neither of which rounds anything), so selecting this variant
+    /// has no observable effect on any instruction today -- it's accepted
+    /// here so callers can start picking it by name, and so the first real
+    /// floating-point instruction only has to add a `SoftFloat` arm to its
+    /// own dispatch rather than also plumbing the variant through from
+    /// scratch.
+    SoftFloat,
+    /// Route this instruction through [`crate::host_intrinsics`]'s
+    /// independently-written 128-bit host arithmetic instead of
+    /// [`dispatch`], as a third opinion on the multiply/divide family for
+    /// triangulating whether a mismatch against native execution is in the
+    /// asm harness or the default model. Only
+    /// [`crate::host_intrinsics::COVERED`] instructions are affected;
+    /// every other instruction behaves as [`Variant::Default`].
+    HostIntrinsics,
+}
+
+impl FromStr for Variant {
+    type Err = ParseVariantError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Variant::Default),
+            "isa_strict" => Ok(Variant::IsaStrict),
+            "softfloat" => Ok(Variant::SoftFloat),
+            "host_intrinsics" => Ok(Variant::HostIntrinsics),
+            _ => Err(ParseVariantError(s.to_string())),
+        }
+    }
+}
+
+/// Returned by [`FromStr::from_str`] for [`Variant`] when the name isn't
+/// recognized.
+#[derive(Debug)]
+pub struct ParseVariantError(String);
+
+impl fmt::Display for ParseVariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unrecognized model variant: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseVariantError {}
+
+/// Per-instruction variant selection, e.g. `{Divdu: IsaStrict}` to use the
+/// strict divide-by-zero behavior only for `divdu`, leaving every other
+/// instruction on [`Variant::Default`].
+pub type VariantOverrides = HashMap<Instr, Variant>;
+
+/// A model-level error, as opposed to a field disagreeing with native
+/// execution; currently only raised by [`Variant::IsaStrict`].
+#[derive(Debug)]
+pub enum ModelError {
+    DivideByZero(Instr),
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::DivideByZero(instr) => write!(f, "{}: division by zero (isa_strict variant)", instr),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+/// Computes the software-model result for `instr` given `input`, using
+/// [`Variant::Default`] for every instruction.
+pub fn model(instr: Instr, input: InstructionInput) -> InstructionOutput {
+    model_with_variant(instr, input, Variant::Default).expect("the default variant never errors")
+}
+
+/// Computes the software-model result for `instr` given `input`, using
+/// `variant` instead of the default behavior.
+///
+/// `input.aliasing` is honored by forcing the aliased operands to agree
+/// before computing the result, so a generator can produce `ra == rb` (or
+/// `rt == ra`) cases without also having to keep `ra`/`rb` in sync by hand.
+pub fn model_with_variant(
+    instr: Instr,
+    input: InstructionInput,
+    variant: Variant,
+) -> Result<InstructionOutput, ModelError> {
+    let input = canonicalize_aliasing(input);
+    if variant == Variant::IsaStrict
+        && matches!(instr, Instr::Divd | Instr::Divdu)
+        && input.rb == 0
+    {
+        return Err(ModelError::DivideByZero(instr));
+    }
+    if variant == Variant::HostIntrinsics {
+        if let Some(output) = crate::host_intrinsics::model(instr, input) {
+            return Ok(output);
+        }
+    }
+    Ok(dispatch(instr, input))
+}
+
+/// Looks up and runs the model function for `instr`.
+///
+/// This is a plain exhaustive match rather than a generated name-based
+/// lookup, so there's no equivalent of a "`#[model = path]` override" to
+/// support, and a newly-added [`Instr`] variant with no arm here is already
+/// a compile error (non-exhaustive match) pointing straight at this
+/// function, rather than a diagnostic that would need to be generated.
+fn dispatch(instr: Instr, input: InstructionInput) -> InstructionOutput {
+    match instr {
+        Instr::Add => add(input, false, None),
+        Instr::AddO => add(input, true, None),
+        Instr::AddDot => add(input, false, Some(input.xer.so)),
+        Instr::AddC => addc(input),
+        Instr::AddE => adde(input),
+        Instr::Subf => subf(input, false),
+        Instr::SubfO => subf(input, true),
+        Instr::Mulld => InstructionOutput {
+            rt: Some(input.ra.wrapping_mul(input.rb)),
+            ..InstructionOutput::default()
+        },
+        Instr::Mulhdu => InstructionOutput {
+            rt: Some(((input.ra as u128 * input.rb as u128) >> 64) as u64),
+            ..InstructionOutput::default()
+        },
+        Instr::Divd => InstructionOutput {
+            rt: Some(divide_signed(input.ra as i64, input.rb as i64) as u64),
+            ..InstructionOutput::default()
+        },
+        Instr::Divdu => InstructionOutput {
+            rt: Some(input.ra.checked_div(input.rb).unwrap_or(0)),
+            ..InstructionOutput::default()
+        },
+        Instr::Brh => InstructionOutput { rt: Some(brh(input.ra)), ..InstructionOutput::default() },
+        Instr::Brw => InstructionOutput { rt: Some(brw(input.ra)), ..InstructionOutput::default() },
+        Instr::Brd => InstructionOutput { rt: Some(input.ra.swap_bytes()), ..InstructionOutput::default() },
+        Instr::Cfuged => InstructionOutput {
+            rt: Some(cfuged(input.ra, input.rb)),
+            ..InstructionOutput::default()
+        },
+        Instr::Cntlzdm => InstructionOutput {
+            rt: Some(cntlzdm(input.ra, input.rb)),
+            ..InstructionOutput::default()
+        },
+        Instr::Cnttzdm => InstructionOutput {
+            rt: Some(cnttzdm(input.ra, input.rb)),
+            ..InstructionOutput::default()
+        },
+        Instr::Pdepd => InstructionOutput {
+            rt: Some(pdepd(input.ra, input.rb)),
+            ..InstructionOutput::default()
+        },
+        Instr::Pextd => InstructionOutput {
+            rt: Some(pextd(input.ra, input.rb)),
+            ..InstructionOutput::default()
+        },
+        Instr::Mffscrn => mffscrn(input),
+        Instr::Mffsce => mffsce(input),
+        Instr::Slw => InstructionOutput {
+            rt: Some(shift_word(input.ra, input.rb, ShiftDirection::Left, false).0),
+            ..InstructionOutput::default()
+        },
+        Instr::Srw => InstructionOutput {
+            rt: Some(shift_word(input.ra, input.rb, ShiftDirection::Right, false).0),
+            ..InstructionOutput::default()
+        },
+        Instr::Sraw => {
+            let (rt, carried) = shift_word(input.ra, input.rb, ShiftDirection::Right, true);
+            let mut xer = input.xer;
+            xer.ca = carried;
+            xer.ca32 = carried;
+            InstructionOutput { rt: Some(rt), xer: Some(xer), ..InstructionOutput::default() }
+        }
+        Instr::Sld => InstructionOutput {
+            rt: Some(shift_doubleword(input.ra, input.rb, ShiftDirection::Left, false).0),
+            ..InstructionOutput::default()
+        },
+        Instr::Srd => InstructionOutput {
+            rt: Some(shift_doubleword(input.ra, input.rb, ShiftDirection::Right, false).0),
+            ..InstructionOutput::default()
+        },
+        Instr::Srad => {
+            let (rt, carried) = shift_doubleword(input.ra, input.rb, ShiftDirection::Right, true);
+            let mut xer = input.xer;
+            xer.ca = carried;
+            xer.ca32 = carried;
+            InstructionOutput { rt: Some(rt), xer: Some(xer), ..InstructionOutput::default() }
+        }
+    }
+}
+
+/// Which way [`shift_word`]/[`shift_doubleword`] shift.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ShiftDirection {
+    Left,
+    Right,
+}
+
+/// `slw`/`srw`/`sraw`'s shared shift logic: shifts `rs`'s low 32 bits by
+/// the count in `rb`'s low 6 bits (POWER ISA's word-form shift-amount
+/// field), returning `(result, carried)`.
+///
+/// A shift count `>= 32` -- representable in the 6-bit field but outside
+/// the 32-bit word's range -- gives a result of all zero bits (`algebraic`
+/// false) or all sign bits (`algebraic` true), per the ISA rather than
+/// relying on Rust's shift operators, which panic on a shift amount
+/// `>= 32` instead of saturating.
+///
+/// `carried` is always `false` for a left shift or a non-algebraic right
+/// shift (`CA` is untouched by those forms; the caller still has to choose
+/// not to report it). For `sraw`, it's whether any `1` bits of a negative
+/// `rs` were shifted out -- `CA`'s actual ISA-defined meaning there.
+fn shift_word(rs: u64, rb: u64, direction: ShiftDirection, algebraic: bool) -> (u64, bool) {
+    let rs = rs as u32;
+    let count = (rb & 0x3f) as u32;
+    match direction {
+        ShiftDirection::Left => {
+            let result = if count >= 32 { 0 } else { rs << count };
+            (result as u64, false)
+        }
+        ShiftDirection::Right if !algebraic => {
+            let result = if count >= 32 { 0 } else { rs >> count };
+            (result as u64, false)
+        }
+        ShiftDirection::Right => {
+            let rs = rs as i32;
+            let shifted_out_count = count.min(32);
+            let shifted_out_mask = if shifted_out_count == 0 { 0 } else { u32::MAX >> (32 - shifted_out_count) };
+            let carried = rs < 0 && (rs as u32) & shifted_out_mask != 0;
+            let result = if count >= 32 { rs >> 31 } else { rs >> count };
+            (result as u32 as u64, carried)
+        }
+    }
+}
+
+/// Like [`shift_word`], but for `sld`/`srd`/`srad`: shifts the full 64-bit
+/// `rs` by the count in `rb`'s low 7 bits, saturating shift counts `>= 64`
+/// the same way.
+fn shift_doubleword(rs: u64, rb: u64, direction: ShiftDirection, algebraic: bool) -> (u64, bool) {
+    let count = (rb & 0x7f) as u32;
+    match direction {
+        ShiftDirection::Left => {
+            let result = if count >= 64 { 0 } else { rs << count };
+            (result, false)
+        }
+        ShiftDirection::Right if !algebraic => {
+            let result = if count >= 64 { 0 } else { rs >> count };
+            (result, false)
+        }
+        ShiftDirection::Right => {
+            let signed = rs as i64;
+            let shifted_out_count = count.min(64);
+            let shifted_out_mask =
+                if shifted_out_count == 0 { 0 } else { u64::MAX >> (64 - shifted_out_count) };
+            let carried = signed < 0 && rs & shifted_out_mask != 0;
+            let result = if count >= 64 { signed >> 63 } else { signed >> count };
+            (result as u64, carried)
+        }
+    }
+}
+
+/// Reverses the bytes within each of the four halfwords of `value`.
+fn brh(value: u64) -> u64 {
+    let mut result = 0u64;
+    for halfword in 0..4 {
+        let shift = halfword * 16;
+        let bytes = ((value >> shift) as u16).swap_bytes();
+        result |= (bytes as u64) << shift;
+    }
+    result
+}
+
+/// Reverses the bytes within each of the two words of `value`.
+fn brw(value: u64) -> u64 {
+    let lo = (value as u32).swap_bytes() as u64;
+    let hi = ((value >> 32) as u32).swap_bytes() as u64;
+    lo | (hi << 32)
+}
+
+/// Centrifuge: gathers the bits of `rs` selected by `mask` into the low
+/// bits of the result (preserving their relative order), then the
+/// unselected bits above them.
+///
+/// Bit numbering here follows this crate's convention of treating `ra`/
+/// `rb` as plain little-endian-bit-order `u64`s (bit 0 is the least
+/// significant), not the POWER ISA's big-endian bit numbering.
+fn cfuged(rs: u64, mask: u64) -> u64 {
+    let mut result = 0u64;
+    let mut pos = 0u32;
+    for bit in 0..64 {
+        if (mask >> bit) & 1 == 1 {
+            result |= ((rs >> bit) & 1) << pos;
+            pos += 1;
+        }
+    }
+    for bit in 0..64 {
+        if (mask >> bit) & 1 == 0 {
+            result |= ((rs >> bit) & 1) << pos;
+            pos += 1;
+        }
+    }
+    result
+}
+
+/// Counts leading zeros of `rs`, considering only the bit positions
+/// selected by `mask` (scanning from the highest selected position down).
+fn cntlzdm(rs: u64, mask: u64) -> u64 {
+    let mut count = 0u64;
+    for bit in (0..64).rev() {
+        if (mask >> bit) & 1 == 0 {
+            continue;
+        }
+        if (rs >> bit) & 1 == 1 {
+            break;
+        }
+        count += 1;
+    }
+    count
+}
+
+/// Counts trailing zeros of `rs`, considering only the bit positions
+/// selected by `mask` (scanning from the lowest selected position up).
+fn cnttzdm(rs: u64, mask: u64) -> u64 {
+    let mut count = 0u64;
+    for bit in 0..64 {
+        if (mask >> bit) & 1 == 0 {
+            continue;
+        }
+        if (rs >> bit) & 1 == 1 {
+            break;
+        }
+        count += 1;
+    }
+    count
+}
+
+/// Parallel bit deposit: consumes the bits of `rs` from the low end,
+/// depositing them in order into the positions selected by `mask`.
+fn pdepd(rs: u64, mask: u64) -> u64 {
+    let mut result = 0u64;
+    let mut src_bit = 0u32;
+    for bit in 0..64 {
+        if (mask >> bit) & 1 == 1 {
+            result |= ((rs >> src_bit) & 1) << bit;
+            src_bit += 1;
+        }
+    }
+    result
+}
+
+/// Parallel bit extract: the inverse of [`pdepd`], gathering the bits of
+/// `rs` selected by `mask` into the low end of the result, in order.
+fn pextd(rs: u64, mask: u64) -> u64 {
+    let mut result = 0u64;
+    let mut dst_bit = 0u32;
+    for bit in 0..64 {
+        if (mask >> bit) & 1 == 1 {
+            result |= ((rs >> bit) & 1) << dst_bit;
+            dst_bit += 1;
+        }
+    }
+    result
+}
+
+/// `mffscrn`: sets `FPSCR.RN` from the low 2 bits of `rb`, leaving the
+/// other modeled `FPSCR` fields untouched. The hardware instruction also
+/// returns the prior `FPSCR` in `FRT`; this crate doesn't model the FPR
+/// file (see [`Instr::Mffscrn`]), so that part of its behavior isn't
+/// represented here.
+fn mffscrn(input: InstructionInput) -> InstructionOutput {
+    let fpscr = Fpscr { rn: RoundingMode::from_bits(input.rb as u8 & 0b11), ..input.fpscr };
+    InstructionOutput { fpscr: Some(fpscr), ..InstructionOutput::default() }
+}
+
+/// `mffsce`: clears `FPSCR`'s five IEEE exception enable bits (`VE`, `OE`,
+/// `UE`, `ZE`, `XE`), leaving the rounding mode untouched. Like
+/// [`mffscrn`], the prior-`FPSCR`-in-`FRT` half of the real instruction
+/// isn't represented, for the same reason.
+fn mffsce(input: InstructionInput) -> InstructionOutput {
+    let fpscr = Fpscr { ve: false, oe: false, ue: false, ze: false, xe: false, ..input.fpscr };
+    InstructionOutput { fpscr: Some(fpscr), ..InstructionOutput::default() }
+}
+
+/// `rt == ra` aliasing doesn't change a model's result (the model has no
+/// notion of a register being read back before it's written), but
+/// `ra == rb` aliasing does, so force `rb` to equal `ra` whenever the
+/// aliasing spec requires it rather than trusting the two fields to already
+/// agree.
+fn canonicalize_aliasing(mut input: InstructionInput) -> InstructionInput {
+    if matches!(input.aliasing, Aliasing::RaEqRb | Aliasing::RtEqRaEqRb) {
+        input.rb = input.ra;
+    }
+    input
+}
+
+fn divide_signed(a: i64, b: i64) -> i64 {
+    if b == 0 || (a == i64::MIN && b == -1) {
+        0
+    } else {
+        a / b
+    }
+}
+
+/// The carry-out and signed overflow of `a + b + carry_in` computed at
+/// 32-bit width instead of this crate's native 64 bits -- exactly what
+/// `XER`'s `CA32`/`OV32` are defined to report (ISA v3.0B 3.3.3/3.3.9),
+/// and not something derivable from the 64-bit carry/overflow of the same
+/// inputs, so every add/subtract-family instruction that sets `CA32`/`OV32`
+/// goes through this one recompute instead of re-deriving it per
+/// instruction. `a`/`b` are truncated to their low 32 bits before adding;
+/// `subf`'s family gets its subtraction by passing `!ra` (32-bit complement)
+/// and `carry_in: true`, the same two's-complement identity the 64-bit
+/// path already uses.
+///
+/// # Examples
+///
+/// ```
+/// use power_instruction_analyzer::model::add_32bit;
+///
+/// // 0x7fff_ffff + 1: a positive 32-bit value plus one wraps to negative
+/// // (signed-overflowing) without the unsigned sum passing 0xffff_ffff, so
+/// // it doesn't also carry.
+/// let result = add_32bit(0x7fff_ffff, 1, false);
+/// assert!(!result.carried);
+/// assert!(result.overflowed);
+///
+/// // 0xffff_ffff + 1 (i.e. -1 + 1): carries out but doesn't overflow,
+/// // since the true signed sum (0) fits in 32 bits.
+/// let result = add_32bit(0xffff_ffff, 1, false);
+/// assert!(result.carried);
+/// assert!(!result.overflowed);
+///
+/// // 1 + 1 with no carry-in: neither carries nor overflows.
+/// let result = add_32bit(1, 1, false);
+/// assert!(!result.carried);
+/// assert!(!result.overflowed);
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Add32BitResult {
+    pub carried: bool,
+    pub overflowed: bool,
+}
+
+pub fn add_32bit(a: u64, b: u64, carry_in: bool) -> Add32BitResult {
+    let a = a as u32;
+    let b = b as u32;
+    let sum = a as u64 + b as u64 + carry_in as u64;
+    let signed_sum = (a as i32 as i64) + (b as i32 as i64) + (carry_in as i64);
+    Add32BitResult {
+        carried: sum > u32::MAX as u64,
+        overflowed: !(i32::MIN as i64..=i32::MAX as i64).contains(&signed_sum),
+    }
+}
+
+fn overflow_xer(mut xer: Xer, overflowed: bool) -> Xer {
+    xer.ov = overflowed;
+    xer.so |= overflowed;
+    xer
+}
+
+/// The raw signed-64-bit result and overflow decision behind an o-form
+/// instruction, computed separately from any XER mutation.
+///
+/// `add`/`subf` build their `InstructionOutput` from this plus
+/// [`overflow_xer`], but a caller outside this module (e.g. a future
+/// SVP64-saturation layer, which clamps to `i64::MIN`/`i64::MAX` on
+/// overflow instead of setting OV) can use the same core arithmetic
+/// without pulling in XER semantics it doesn't want.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OverflowingResult {
+    pub result: i64,
+    pub overflowed: bool,
+}
+
+/// The raw result and overflow decision for `add`'s arithmetic
+/// (`ra + rb` as signed 64-bit), with no XER or CR0 side effects.
+pub fn add_overflowing(ra: u64, rb: u64) -> OverflowingResult {
+    let (result, overflowed) = (ra as i64).overflowing_add(rb as i64);
+    OverflowingResult { result, overflowed }
+}
+
+/// The raw result and overflow decision for `subf`'s arithmetic
+/// (`rb - ra` as signed 64-bit), with no XER or CR0 side effects.
+pub fn subf_overflowing(ra: u64, rb: u64) -> OverflowingResult {
+    let (result, overflowed) = (rb as i64).overflowing_sub(ra as i64);
+    OverflowingResult { result, overflowed }
+}
+
+/// `add`'s arithmetic (`ra + rb` as signed 64-bit), clamped to
+/// `i64::MIN`/`i64::MAX` on overflow instead of wrapping.
+///
+/// This isn't real POWER ISA `add`/`addo` behavior -- hardware always
+/// wraps and signals overflow via XER.OV, which [`add_overflowing`] and
+/// [`dispatch`]'s `add` keep doing. This exists for the proposed SVP64
+/// per-element saturation mode, which an out-of-this-crate vector layer
+/// can apply on top of [`add_overflowing`]'s same decision; this function
+/// is just that policy spelled out, so two SVP64 implementations don't
+/// have to re-derive "which bound did we overflow past" independently.
+pub fn add_saturating(ra: u64, rb: u64) -> u64 {
+    let OverflowingResult { result, overflowed } = add_overflowing(ra, rb);
+    if !overflowed {
+        return result as u64;
+    }
+    // Add only overflows when `ra`/`rb` have the same sign, so either
+    // operand's sign says which bound the true (unclamped) result passed.
+    (if (ra as i64) >= 0 { i64::MAX } else { i64::MIN }) as u64
+}
+
+/// `subf`'s arithmetic (`rb - ra` as signed 64-bit), clamped to
+/// `i64::MIN`/`i64::MAX` on overflow instead of wrapping. See
+/// [`add_saturating`] for why this isn't real `subf`/`subfo` behavior.
+pub fn subf_saturating(ra: u64, rb: u64) -> u64 {
+    let OverflowingResult { result, overflowed } = subf_overflowing(ra, rb);
+    if !overflowed {
+        return result as u64;
+    }
+    // `rb - ra` only overflows when `rb`/`-ra` have the same sign, so
+    // `rb`'s sign alone says which bound the true result passed.
+    (if (rb as i64) >= 0 { i64::MAX } else { i64::MIN }) as u64
+}
+
+/// Which CR0 a Dot-form divide reports for its zero/overflow corner cases
+/// (`rb == 0`, or the signed `i64::MIN / -1` overflow): the ISA leaves RT
+/// undefined there, so whatever CR0 a Dot-form derives from it is also
+/// implementation-defined, and real cores are known to disagree. Picking a
+/// profile instead of hard-coding one assumption turns a mismatch on these
+/// inputs into signal -- "the wrong profile is configured" -- instead of
+/// noise: an unexplained divide-by-zero diff on every single run.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CoreProfile {
+    /// CR0 is derived from whatever RT the model actually produced (`0`
+    /// for [`Instr::Divd`]/[`Instr::Divdu`]'s divide-by-zero/overflow
+    /// handling) -- as if RT were architected rather than left undefined.
+    #[default]
+    DerivedFromRt,
+    /// CR0 is forced to all-zero (`lt`/`gt`/`eq` false, `so` still copied
+    /// through), reflecting cores observed to leave CR0 in a don't-care
+    /// state on these inputs rather than deriving it from the undefined
+    /// RT.
+    ForcedZero,
+}
+
+/// Computes the CR0 [`CoreProfile::DerivedFromRt`] or
+/// [`CoreProfile::ForcedZero`] would report for a Dot-form divide's
+/// zero/overflow corner case, given the RT the model actually produced.
+///
+/// Not reached through [`dispatch`]/[`model_with_variant`]: no Dot-form
+/// divide is modeled yet (see [`Instr::rc_form`]), so there's nowhere in
+/// the normal model path for this to plug into. It exists so
+/// [`crate::div_report`] -- the one place that currently reasons about
+/// this corner case -- can compare profiles side by side without that
+/// comparison waiting on a full `divd.`/`divdu.` implementation.
+pub fn divide_undefined_cr0(rt: u64, so: bool, profile: CoreProfile) -> ConditionRegister {
+    match profile {
+        CoreProfile::DerivedFromRt => ConditionRegister::compare_signed(rt as i64, 0, so),
+        CoreProfile::ForcedZero => ConditionRegister { lt: false, gt: false, eq: false, so },
+    }
+}
+
+/// `add`'s shared implementation. `cr0_so` is the already-resolved SO bit
+/// to report in `cr0` (or `None` for non-Rc forms): the caller picks where
+/// it comes from -- `input.xer.so` for `add.`, or this same call's computed
+/// overflow for a hypothetical combined OE+Rc form -- rather than `add`
+/// guessing from whether `overflow` happens to be set.
+fn add(input: InstructionInput, overflow: bool, cr0_so: Option<bool>) -> InstructionOutput {
+    let OverflowingResult { result, overflowed } = add_overflowing(input.ra, input.rb);
+    let xer = overflow.then(|| {
+        let mut xer = overflow_xer(input.xer, overflowed);
+        xer.ov32 = add_32bit(input.ra, input.rb, false).overflowed;
+        xer
+    });
+    InstructionOutput {
+        rt: Some(result as u64),
+        cr0: cr0_so.map(|so| ConditionRegister::compare_signed(result, 0, so)),
+        xer,
+        ..InstructionOutput::default()
+    }
+}
+
+fn addc(input: InstructionInput) -> InstructionOutput {
+    let (result, carried) = input.ra.overflowing_add(input.rb);
+    let mut xer = input.xer;
+    xer.ca = carried;
+    xer.ca32 = add_32bit(input.ra, input.rb, false).carried;
+    InstructionOutput {
+        rt: Some(result),
+        xer: Some(xer),
+        ..InstructionOutput::default()
+    }
+}
+
+fn adde(input: InstructionInput) -> InstructionOutput {
+    let carry_in = input.xer.ca;
+    let (partial, carry0) = input.ra.overflowing_add(input.rb);
+    let (result, carry1) = partial.overflowing_add(carry_in as u64);
+    let mut xer = input.xer;
+    xer.ca = carry0 || carry1;
+    xer.ca32 = add_32bit(input.ra, input.rb, carry_in).carried;
+    InstructionOutput {
+        rt: Some(result),
+        xer: Some(xer),
+        ..InstructionOutput::default()
+    }
+}
+
+fn subf(input: InstructionInput, record_overflow: bool) -> InstructionOutput {
+    let OverflowingResult { result, overflowed } = subf_overflowing(input.ra, input.rb);
+    let xer = record_overflow.then(|| {
+        let mut xer = overflow_xer(input.xer, overflowed);
+        xer.ov32 = add_32bit(input.rb, !(input.ra as u32) as u64, true).overflowed;
+        xer
+    });
+    InstructionOutput {
+        rt: Some(result as u64),
+        xer,
+        ..InstructionOutput::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Aliasing;
+
+    #[test]
+    fn every_instruction_has_a_working_model() {
+        for &instr in Instr::ALL {
+            model(instr, InstructionInput::default());
+        }
+    }
+
+    /// [`crate::native::jit`] needs to know, for each native-executable
+    /// instruction, whether a native capture's `cr0`/`xer` should come
+    /// back `Some` or `None` -- and it answers that from
+    /// [`Instr::writes_cr0`]/[`Instr::writes_xer`] rather than from this
+    /// module, to avoid depending on the model for a native-only decision.
+    /// This guards against those two falling out of sync with the
+    /// per-instruction choices made below (which are fixed for a given
+    /// instruction, not data-dependent, so `InstructionInput::default()`
+    /// is representative).
+    #[test]
+    fn writes_cr0_and_writes_xer_match_model_dispatch() {
+        for &instr in Instr::ALL {
+            if instr.is_model_only() {
+                continue;
+            }
+            let output = model(instr, InstructionInput::default());
+            assert_eq!(instr.writes_cr0(), output.cr0.is_some(), "{:?}.writes_cr0()", instr);
+            assert_eq!(instr.writes_xer(), output.xer.is_some(), "{:?}.writes_xer()", instr);
+        }
+    }
+
+    #[test]
+    fn add_overflowing_agrees_with_addo_on_both_result_and_the_overflow_bit() {
+        for &(ra, rb) in &[(0u64, 0u64), (i64::MAX as u64, 1), (i64::MIN as u64, i64::MIN as u64)] {
+            let input = InstructionInput { ra, rb, ..InstructionInput::default() };
+            let expected = model(Instr::AddO, input);
+            let OverflowingResult { result, overflowed } = add_overflowing(ra, rb);
+            assert_eq!(expected.rt, Some(result as u64));
+            assert_eq!(expected.xer.map(|xer| xer.ov), Some(overflowed));
+        }
+    }
+
+    #[test]
+    fn subf_overflowing_agrees_with_subfo_on_both_result_and_the_overflow_bit() {
+        for &(ra, rb) in &[(0u64, 0u64), (1, i64::MIN as u64), (i64::MIN as u64, i64::MAX as u64)] {
+            let input = InstructionInput { ra, rb, ..InstructionInput::default() };
+            let expected = model(Instr::SubfO, input);
+            let OverflowingResult { result, overflowed } = subf_overflowing(ra, rb);
+            assert_eq!(expected.rt, Some(result as u64));
+            assert_eq!(expected.xer.map(|xer| xer.ov), Some(overflowed));
+        }
+    }
+
+    #[test]
+    fn add_saturating_clamps_to_max_on_positive_overflow_and_min_on_negative_overflow() {
+        assert_eq!(add_saturating(i64::MAX as u64, 1), i64::MAX as u64);
+        assert_eq!(add_saturating(i64::MIN as u64, i64::MIN as u64), i64::MIN as u64);
+    }
+
+    #[test]
+    fn addo_sets_ov32_independently_of_the_64bit_ov_it_shares_a_result_with() {
+        // 64-bit OV, but the low 32 bits (0 + -1) don't carry past bit 31.
+        let input = InstructionInput { ra: i64::MAX as u64, rb: i64::MIN as u64, ..InstructionInput::default() };
+        let xer = model(Instr::AddO, input).xer.unwrap();
+        assert!(!xer.ov);
+        // 32-bit OV, with no 64-bit OV: 0x7fff_ffff + 1 overflows in 32 bits only.
+        let input = InstructionInput { ra: 0x7fff_ffff, rb: 1, ..InstructionInput::default() };
+        let xer = model(Instr::AddO, input).xer.unwrap();
+        assert!(!xer.ov);
+        assert!(xer.ov32);
+    }
+
+    #[test]
+    fn addc_and_adde_set_ca32_independently_of_ca() {
+        // Carries past bit 63 but not bit 31.
+        let input = InstructionInput { ra: 1u64 << 63, rb: 1u64 << 63, ..InstructionInput::default() };
+        let xer = model(Instr::AddC, input).xer.unwrap();
+        assert!(xer.ca);
+        assert!(!xer.ca32);
+        // Carries past bit 31 but not bit 63.
+        let input = InstructionInput { ra: 0xffff_ffff, rb: 1, ..InstructionInput::default() };
+        let xer = model(Instr::AddC, input).xer.unwrap();
+        assert!(!xer.ca);
+        assert!(xer.ca32);
+        // adde folds the incoming CA into the 32-bit recompute too.
+        let input = InstructionInput {
+            ra: 0xffff_ffff,
+            rb: 0,
+            xer: Xer { ca: true, ..Xer::default() },
+            ..InstructionInput::default()
+        };
+        let xer = model(Instr::AddE, input).xer.unwrap();
+        assert!(xer.ca32);
+    }
+
+    #[test]
+    fn subfo_derives_ov32_through_the_same_add_32bit_helper_as_the_add_family() {
+        // rb - ra = 0 - i32::MIN overflows the low 32 bits but not 64.
+        let input = InstructionInput { ra: i32::MIN as u32 as u64, rb: 0, ..InstructionInput::default() };
+        let xer = model(Instr::SubfO, input).xer.unwrap();
+        assert!(!xer.ov);
+        assert!(xer.ov32);
+    }
+
+    #[test]
+    fn add_saturating_agrees_with_wrapping_add_when_there_is_no_overflow() {
+        assert_eq!(add_saturating(5, 7), 12);
+        assert_eq!(add_saturating((-5i64) as u64, 2), (-3i64) as u64);
+    }
+
+    #[test]
+    fn subf_saturating_clamps_to_max_on_positive_overflow_and_min_on_negative_overflow() {
+        // subf computes rb - ra.
+        assert_eq!(subf_saturating(i64::MIN as u64, 1), i64::MAX as u64);
+        assert_eq!(subf_saturating(1, i64::MIN as u64), i64::MIN as u64);
+    }
+
+    #[test]
+    fn subf_saturating_agrees_with_wrapping_subf_when_there_is_no_overflow() {
+        assert_eq!(subf_saturating(3, 10), 7);
+    }
+
+    #[test]
+    fn addo_and_subfo_still_wrap_rather_than_saturate() {
+        // The saturating helpers are an opt-in extension point, not a
+        // change to `add`/`subf`'s own (hardware-matching) wrap behavior.
+        let input = InstructionInput { ra: i64::MAX as u64, rb: 1, ..InstructionInput::default() };
+        assert_eq!(model(Instr::AddO, input).rt, Some((i64::MIN) as u64));
+
+        let input = InstructionInput { ra: i64::MIN as u64, rb: 1, ..InstructionInput::default() };
+        assert_eq!(model(Instr::SubfO, input).rt, Some((i64::MIN + 1) as u64));
+    }
+
+    #[test]
+    fn derived_from_rt_profile_compares_rt_against_zero() {
+        assert_eq!(
+            divide_undefined_cr0(0, false, CoreProfile::DerivedFromRt),
+            ConditionRegister { lt: false, gt: false, eq: true, so: false }
+        );
+        assert_eq!(
+            divide_undefined_cr0(1, true, CoreProfile::DerivedFromRt),
+            ConditionRegister { lt: false, gt: true, eq: false, so: true }
+        );
+    }
+
+    #[test]
+    fn forced_zero_profile_ignores_rt_but_still_copies_so() {
+        assert_eq!(
+            divide_undefined_cr0(1, true, CoreProfile::ForcedZero),
+            ConditionRegister { lt: false, gt: false, eq: false, so: true }
+        );
+    }
+
+    #[test]
+    fn default_profile_is_derived_from_rt() {
+        assert_eq!(CoreProfile::default(), CoreProfile::DerivedFromRt);
+    }
+
+    #[test]
+    fn ra_eq_rb_aliasing_ignores_mismatched_rb() {
+        let input = InstructionInput {
+            ra: 5,
+            rb: 123, // ignored: aliasing forces rb == ra
+            aliasing: Aliasing::RaEqRb,
+            ..InstructionInput::default()
+        };
+        assert_eq!(model(Instr::Add, input).rt, Some(10));
+    }
+
+    #[test]
+    fn isa_strict_variant_rejects_divide_by_zero() {
+        let input = InstructionInput { ra: 5, rb: 0, ..InstructionInput::default() };
+        assert!(matches!(
+            model_with_variant(Instr::Divdu, input, Variant::IsaStrict),
+            Err(ModelError::DivideByZero(Instr::Divdu))
+        ));
+        assert_eq!(model_with_variant(Instr::Divdu, input, Variant::Default).unwrap().rt, Some(0));
+    }
+
+    #[test]
+    fn softfloat_variant_matches_default_for_every_currently_modeled_instruction() {
+        // No instruction this crate models actually rounds anything, so
+        // `SoftFloat` is accepted but behaves identically to `Default`
+        // everywhere -- this pins that down so the day an instruction's
+        // dispatch arm starts actually branching on the variant, someone
+        // has to come update this test rather than finding out by surprise.
+        for &instr in Instr::ALL {
+            let default = model_with_variant(instr, InstructionInput::default(), Variant::Default);
+            let softfloat = model_with_variant(instr, InstructionInput::default(), Variant::SoftFloat);
+            assert_eq!(default.ok(), softfloat.ok());
+        }
+    }
+
+    #[test]
+    fn host_intrinsics_variant_agrees_with_default_for_every_currently_modeled_instruction() {
+        for &instr in Instr::ALL {
+            let default = model_with_variant(instr, InstructionInput::default(), Variant::Default);
+            let host_intrinsics = model_with_variant(instr, InstructionInput::default(), Variant::HostIntrinsics);
+            assert_eq!(default.unwrap().rt, host_intrinsics.unwrap().rt, "{}", instr);
+        }
+    }
+
+    #[test]
+    fn dot_form_sets_cr0_from_the_signed_result() {
+        let negative = InstructionInput { ra: (-5i64) as u64, rb: 0, ..InstructionInput::default() };
+        assert_eq!(
+            model(Instr::AddDot, negative).cr0,
+            Some(ConditionRegister { lt: true, gt: false, eq: false, so: false })
+        );
+        let zero = InstructionInput { ra: 5, rb: (-5i64) as u64, ..InstructionInput::default() };
+        assert_eq!(
+            model(Instr::AddDot, zero).cr0,
+            Some(ConditionRegister { lt: false, gt: false, eq: true, so: false })
+        );
+        // non-dot add doesn't touch cr0 at all.
+        assert_eq!(model(Instr::Add, negative).cr0, None);
+    }
+
+    #[test]
+    fn byte_reverse_variants() {
+        let input = InstructionInput { ra: 0x0123_4567_89ab_cdef, ..InstructionInput::default() };
+        assert_eq!(model(Instr::Brh, input).rt, Some(0x2301_6745_ab89_efcd));
+        assert_eq!(model(Instr::Brw, input).rt, Some(0x6745_2301_efcd_ab89));
+        assert_eq!(model(Instr::Brd, input).rt, Some(0xefcd_ab89_6745_2301));
+    }
+
+    #[test]
+    fn pdepd_and_pextd_round_trip() {
+        let mask = 0b1011_0100;
+        let bits = 0b1101; // one bit per set mask position, low-to-high
+        let deposited = pdepd(bits, mask);
+        assert_eq!(deposited, 0b1010_0100);
+        assert_eq!(pextd(deposited, mask), bits);
+    }
+
+    #[test]
+    fn mffscrn_sets_rounding_mode_and_leaves_enables_alone() {
+        let input = InstructionInput {
+            rb: 0b10,
+            fpscr: Fpscr { rn: RoundingMode::Nearest, ve: true, oe: false, ue: false, ze: false, xe: false },
+            ..InstructionInput::default()
+        };
+        let fpscr = model(Instr::Mffscrn, input).fpscr.unwrap();
+        assert_eq!(fpscr.rn, RoundingMode::TowardPositiveInfinity);
+        assert!(fpscr.ve);
+        assert!(!fpscr.oe);
+    }
+
+    #[test]
+    fn mffsce_clears_enables_and_leaves_rounding_mode_alone() {
+        let input = InstructionInput {
+            fpscr: Fpscr { rn: RoundingMode::TowardZero, ve: true, oe: true, ue: true, ze: true, xe: true },
+            ..InstructionInput::default()
+        };
+        let fpscr = model(Instr::Mffsce, input).fpscr.unwrap();
+        assert_eq!(fpscr.rn, RoundingMode::TowardZero);
+        assert!(!fpscr.ve);
+        assert!(!fpscr.oe);
+        assert!(!fpscr.ue);
+        assert!(!fpscr.ze);
+        assert!(!fpscr.xe);
+    }
+
+    #[test]
+    fn cfuged_gathers_masked_bits_low() {
+        let rs = 0b1010_1100u64;
+        let mask = 0b0011_1100u64;
+        assert_eq!(cfuged(rs, mask), 0b1000_1011);
+    }
+
+    #[test]
+    fn cntlzdm_and_cnttzdm_ignore_unmasked_bits() {
+        let rs = 0b0001_0000u64;
+        let mask = 0b0011_1100u64;
+        assert_eq!(cntlzdm(rs, mask), 1); // highest masked bit (bit 5) is 0
+        assert_eq!(cnttzdm(rs, mask), 2); // two lowest masked bits (2,3) are 0
+    }
+
+    #[test]
+    fn word_shifts_behave_normally_below_the_width() {
+        let input = InstructionInput { ra: 0b1010, rb: 2, ..InstructionInput::default() };
+        assert_eq!(model(Instr::Slw, input).rt, Some(0b101000));
+        assert_eq!(model(Instr::Srw, input).rt, Some(0b10));
+        assert_eq!(model(Instr::Sraw, input).rt, Some(0b10));
+    }
+
+    #[test]
+    fn word_shifts_saturate_at_shift_amounts_at_or_above_32() {
+        let at_width = InstructionInput { ra: 1, rb: 32, ..InstructionInput::default() };
+        assert_eq!(model(Instr::Slw, at_width).rt, Some(0));
+        assert_eq!(model(Instr::Srw, at_width).rt, Some(0));
+        let past_width = InstructionInput { ra: 1, rb: 63, ..InstructionInput::default() };
+        assert_eq!(model(Instr::Slw, past_width).rt, Some(0));
+        assert_eq!(model(Instr::Srw, past_width).rt, Some(0));
+    }
+
+    #[test]
+    fn sraw_sign_fills_and_sets_ca_when_a_negative_value_saturates() {
+        let negative = (-8i32) as u32 as u64;
+        let below_width = InstructionInput { ra: negative, rb: 2, ..InstructionInput::default() };
+        let output = model(Instr::Sraw, below_width);
+        assert_eq!(output.rt, Some((-2i32) as u32 as u64));
+        assert!(!output.xer.unwrap().ca);
+
+        let at_width = InstructionInput { ra: negative, rb: 32, ..InstructionInput::default() };
+        let output = model(Instr::Sraw, at_width);
+        assert_eq!(output.rt, Some(u32::MAX as u64)); // sign-filled to all 1s
+        let xer = output.xer.unwrap();
+        assert!(xer.ca);
+        assert!(xer.ca32);
+
+        let positive = InstructionInput { ra: 8, rb: 32, ..InstructionInput::default() };
+        let output = model(Instr::Sraw, positive);
+        assert_eq!(output.rt, Some(0));
+        assert!(!output.xer.unwrap().ca);
+    }
+
+    #[test]
+    fn doubleword_shifts_behave_normally_below_the_width() {
+        let input = InstructionInput { ra: 0b1010, rb: 2, ..InstructionInput::default() };
+        assert_eq!(model(Instr::Sld, input).rt, Some(0b101000));
+        assert_eq!(model(Instr::Srd, input).rt, Some(0b10));
+        assert_eq!(model(Instr::Srad, input).rt, Some(0b10));
+    }
+
+    #[test]
+    fn doubleword_shifts_saturate_at_shift_amounts_at_or_above_64() {
+        let at_width = InstructionInput { ra: 1, rb: 64, ..InstructionInput::default() };
+        assert_eq!(model(Instr::Sld, at_width).rt, Some(0));
+        assert_eq!(model(Instr::Srd, at_width).rt, Some(0));
+        let past_width = InstructionInput { ra: 1, rb: 127, ..InstructionInput::default() };
+        assert_eq!(model(Instr::Sld, past_width).rt, Some(0));
+        assert_eq!(model(Instr::Srd, past_width).rt, Some(0));
+    }
+
+    #[test]
+    fn srad_sign_fills_and_sets_ca_when_a_negative_value_saturates() {
+        let negative = (-8i64) as u64;
+        let below_width = InstructionInput { ra: negative, rb: 2, ..InstructionInput::default() };
+        let output = model(Instr::Srad, below_width);
+        assert_eq!(output.rt, Some((-2i64) as u64));
+        assert!(!output.xer.unwrap().ca);
+
+        let at_width = InstructionInput { ra: negative, rb: 64, ..InstructionInput::default() };
+        let output = model(Instr::Srad, at_width);
+        assert_eq!(output.rt, Some(u64::MAX)); // sign-filled to all 1s
+        let xer = output.xer.unwrap();
+        assert!(xer.ca);
+        assert!(xer.ca32);
+
+        let positive = InstructionInput { ra: 8, rb: 64, ..InstructionInput::default() };
+        let output = model(Instr::Srad, positive);
+        assert_eq!(output.rt, Some(0));
+        assert!(!output.xer.unwrap().ca);
+    }
+
+    #[test]
+    fn shift_amount_only_uses_the_low_bits_of_rb() {
+        // A shift count of 32 + 0x40 for the word forms, or 64 + 0x80 for
+        // the doubleword forms, still masks down to 0 -- the extra high
+        // bits of `rb` outside the 6-/7-bit field aren't part of the count.
+        let word = InstructionInput { ra: 0b1010, rb: 0x40, ..InstructionInput::default() };
+        assert_eq!(model(Instr::Slw, word).rt, Some(0b1010));
+        let doubleword = InstructionInput { ra: 0b1010, rb: 0x80, ..InstructionInput::default() };
+        assert_eq!(model(Instr::Sld, doubleword).rt, Some(0b1010));
+    }
+
+    // `divide_signed` and the multiply family below are structural copies
+    // of `dispatch`'s real logic at a width small enough (8 bits, 65536
+    // pairs) to brute-force exhaustively, checked against a reference
+    // computed a different way -- bit-serial shift-and-subtract/add
+    // instead of the host `/`/`*` operators. A bug in the *shape* of the
+    // edge-case guards (divide-by-zero, `MIN / -1` overflow) or in a
+    // multi-step algorithm's bit count would show up here even though the
+    // production code only ever runs at 64-bit widths where an exhaustive
+    // sweep is infeasible. The same approach applies to multi-step
+    // algorithms this crate doesn't model yet, such as `divdeu`'s
+    // 128-bit-by-64-bit extended divide.
+
+    fn divide_signed_8bit(a: i8, b: i8) -> i8 {
+        if b == 0 || (a == i8::MIN && b == -1) {
+            0
+        } else {
+            a / b
+        }
+    }
+
+    fn divide_unsigned_8bit(a: u8, b: u8) -> u8 {
+        a.checked_div(b).unwrap_or(0)
+    }
+
+    /// Unsigned long division via repeated shift-and-subtract -- the
+    /// bit-serial shape a hardware divider (and `divdeu`'s 128-bit
+    /// extended divide) actually takes -- as an independent reference for
+    /// [`divide_unsigned_8bit`].
+    fn long_divide_unsigned_8bit(a: u8, b: u8) -> u8 {
+        if b == 0 {
+            return 0;
+        }
+        let mut remainder = 0u16;
+        let mut quotient = 0u8;
+        for bit in (0..8).rev() {
+            remainder = (remainder << 1) | u16::from((a >> bit) & 1);
+            quotient <<= 1;
+            if remainder >= u16::from(b) {
+                remainder -= u16::from(b);
+                quotient |= 1;
+            }
+        }
+        quotient
+    }
+
+    /// Unsigned multiplication via repeated shift-and-add -- the same
+    /// schoolbook shape `mulld`/`mulhdu`'s 64x64->128 decomposition
+    /// builds on -- as an independent reference for the host `*` operator.
+    fn long_multiply_unsigned_8bit(a: u8, b: u8) -> u16 {
+        let mut product = 0u16;
+        for bit in 0..8 {
+            if (b >> bit) & 1 == 1 {
+                product += u16::from(a) << bit;
+            }
+        }
+        product
+    }
+
+    #[test]
+    fn divide_signed_8bit_agrees_with_checked_division_off_the_overflow_guard() {
+        for a in i8::MIN..=i8::MAX {
+            for b in i8::MIN..=i8::MAX {
+                let expected = if b == 0 || (a == i8::MIN && b == -1) { 0 } else { a.checked_div(b).unwrap() };
+                assert_eq!(divide_signed_8bit(a, b), expected, "{} / {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn divide_unsigned_8bit_agrees_with_long_division_exhaustively() {
+        for a in 0..=u8::MAX {
+            for b in 0..=u8::MAX {
+                assert_eq!(divide_unsigned_8bit(a, b), long_divide_unsigned_8bit(a, b), "{} / {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn host_multiply_agrees_with_long_multiplication_exhaustively() {
+        for a in 0..=u8::MAX {
+            for b in 0..=u8::MAX {
+                assert_eq!(u16::from(a) * u16::from(b), long_multiply_unsigned_8bit(a, b), "{} * {}", a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,403 @@
+//! Native execution of instructions on real POWER hardware.
+//!
+//! This only works when compiled for a `powerpc64`/`powerpc64le` target, or,
+//! for the subset of instructions that fit in a 32-bit GPR (see
+//! [`Instr::requires_doubleword_gprs`]), a 32-bit `powerpc` target; elsewhere
+//! [`execute`] always returns [`Error::UnsupportedPlatform`] so that the rest
+//! of the crate (and the model-only parts of the CLI) still build and run on
+//! development machines.
+//!
+//! [`execute`] (and every other function here with no explicit backend
+//! parameter) runs on [`Backend::JitLite`], the only backend with a real
+//! implementation. That path ([`execute_with_backend`] with that backend,
+//! or [`jit::execute`] directly) touches no state beyond its own arguments
+//! and the CPU registers it runs its per-call code buffer with -- that
+//! buffer is allocated fresh per call -- so concurrent calls from multiple
+//! threads, as [`crate::fill::fill_parallel`] does, need no synchronization
+//! between them; they only ever contend for the CPU itself.
+//! [`Backend::Compiled`] (selectable via [`execute_with_backend`]) makes no
+//! such promise yet: it has no implementation at all, and always reports
+//! an error.
+
+use crate::instr::{Instr, Privilege};
+use crate::types::InstructionInput;
+use crate::types::InstructionOutput;
+use std::fmt;
+
+#[cfg(target_arch = "powerpc64")]
+pub mod jit;
+
+/// A choice of which GPRs an instruction's `rt`/`ra`/`rb` operands are
+/// assigned to, used by [`jit::execute_with_regs`] to probe for
+/// register-aliasing bugs (`ra == rb`, `rt == ra`, ...).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RegisterAssignment {
+    pub rt: u32,
+    pub ra: u32,
+    pub rb: u32,
+}
+
+impl RegisterAssignment {
+    /// The non-aliased assignment used by default.
+    pub const DEFAULT: Self = Self { rt: 3, ra: 4, rb: 5 };
+
+    /// Assignments worth stress-testing: the default plus every way the
+    /// three operands can alias each other.
+    pub const STRESS_SET: &'static [Self] = &[
+        Self::DEFAULT,
+        Self { rt: 3, ra: 4, rb: 4 },  // ra == rb
+        Self { rt: 3, ra: 3, rb: 4 },  // rt == ra
+        Self { rt: 3, ra: 4, rb: 3 },  // rt == rb
+        Self { rt: 3, ra: 3, rb: 3 },  // rt == ra == rb
+    ];
+
+    /// The assignment that realizes a given [`crate::types::Aliasing`]
+    /// spec as actual shared GPR numbers.
+    pub fn for_aliasing(aliasing: crate::types::Aliasing) -> Self {
+        use crate::types::Aliasing;
+        match aliasing {
+            Aliasing::None => Self::DEFAULT,
+            Aliasing::RaEqRb => Self { rt: 3, ra: 4, rb: 4 },
+            Aliasing::RtEqRa => Self { rt: 3, ra: 3, rb: 4 },
+            Aliasing::RtEqRaEqRb => Self { rt: 3, ra: 3, rb: 3 },
+        }
+    }
+}
+
+/// The result of running one instruction across every entry in
+/// [`RegisterAssignment::STRESS_SET`].
+#[derive(Debug)]
+pub struct RegisterStressReport {
+    pub results: Vec<(RegisterAssignment, InstructionOutput)>,
+}
+
+impl RegisterStressReport {
+    /// Assignments whose output disagreed with the non-aliased default,
+    /// i.e. likely register-aliasing bugs.
+    pub fn divergences(&self) -> impl Iterator<Item = &(RegisterAssignment, InstructionOutput)> {
+        let baseline = self.results[0].1;
+        self.results.iter().skip(1).filter(move |(_, output)| *output != baseline)
+    }
+}
+
+/// The outcome of [`check_xer_hazard`]: which `XER` bits outside the ones
+/// this crate models ([`crate::types::Xer`]'s fields) came back changed
+/// after running an instruction that shouldn't have touched them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct XerHazardReport {
+    pub disturbed_bits: u32,
+}
+
+impl XerHazardReport {
+    /// Whether none of the unrelated `XER` bits moved.
+    pub fn is_clean(&self) -> bool {
+        self.disturbed_bits == 0
+    }
+}
+
+/// The outcome of [`check_xer_reserved_bits`]: whether `instr` treats
+/// `XER`'s reserved (unmodeled) bits the way the ISA requires -- not
+/// reading them (the modeled result is the same no matter what they were
+/// set to) and not writing them (they come back exactly as they went in).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct XerReservedBitReport {
+    /// The modeled result ([`crate::types::Xer`]'s fields) was identical
+    /// whether the reserved bits started all-zero or all-one.
+    pub ignores_reserved_bits: bool,
+    /// The reserved bits came back unchanged for both starting patterns.
+    pub preserves_reserved_bits: bool,
+}
+
+impl XerReservedBitReport {
+    /// Whether `instr` both ignores and preserves the reserved bits, as
+    /// the ISA requires.
+    pub fn is_well_behaved(&self) -> bool {
+        self.ignores_reserved_bits && self.preserves_reserved_bits
+    }
+}
+
+/// Which native-execution strategy to use.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Backend {
+    /// Instructions compiled ahead-of-time into per-instruction asm
+    /// wrappers (see `build.rs`). Only covers instructions known when the
+    /// crate was built.
+    Compiled,
+    /// Instructions encoded at runtime and executed from a writable code
+    /// buffer, so arbitrary register numbers and instructions not known at
+    /// compile time can be tested too.
+    JitLite,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    UnsupportedPlatform,
+    /// No currently-supported native-execution host can run this
+    /// instruction at all, regardless of target (see
+    /// [`Instr::is_model_only`]).
+    ModelOnly(Instr),
+    /// `instr` requires more privilege than userspace native execution
+    /// runs with, so only the software model can provide reference
+    /// behavior for it (see [`Instr::required_privilege`]).
+    RequiresPrivilege(Instr, Privilege),
+    /// `instr` has no word-sized encoding (see
+    /// [`Instr::requires_doubleword_gprs`]), so it can't run on this
+    /// 32-bit `powerpc` host.
+    #[cfg(target_arch = "powerpc")]
+    RequiresDoublewordGprs(Instr),
+    /// `instr` isn't model-only and needs no extra privilege, but the
+    /// [`Backend::Compiled`] backend's per-instruction asm wrapper
+    /// (generated by `build.rs`) hasn't been written for it yet -- unlike
+    /// every other variant here, this is a gap in this crate rather than a
+    /// property of `instr` or the host.
+    #[cfg(any(target_arch = "powerpc64", target_arch = "powerpc"))]
+    NotYetImplemented(Instr),
+    #[cfg(target_arch = "powerpc64")]
+    Jit(jit::Error),
+    #[cfg(all(target_os = "linux", target_arch = "powerpc64"))]
+    Pmu(std::io::Error),
+    #[cfg(all(target_os = "linux", target_arch = "powerpc64"))]
+    Affinity(std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnsupportedPlatform => {
+                write!(f, "native execution is only supported on powerpc64 targets")
+            }
+            Error::ModelOnly(instr) => write!(f, "{} is model-only; no native host can execute it", instr),
+            Error::RequiresPrivilege(instr, privilege) => {
+                write!(f, "{} requires {:?} privilege; userspace native execution can't run it", instr, privilege)
+            }
+            #[cfg(target_arch = "powerpc")]
+            Error::RequiresDoublewordGprs(instr) => {
+                write!(f, "{} needs 64-bit GPRs; this host's GPRs are only 32 bits wide", instr)
+            }
+            #[cfg(any(target_arch = "powerpc64", target_arch = "powerpc"))]
+            Error::NotYetImplemented(instr) => {
+                write!(f, "{} has no compiled-backend (build.rs) wrapper yet", instr)
+            }
+            #[cfg(target_arch = "powerpc64")]
+            Error::Jit(err) => write!(f, "jit-lite backend: {}", err),
+            #[cfg(all(target_os = "linux", target_arch = "powerpc64"))]
+            Error::Pmu(err) => write!(f, "perf_event_open: {}", err),
+            #[cfg(all(target_os = "linux", target_arch = "powerpc64"))]
+            Error::Affinity(err) => write!(f, "CPU affinity/priority: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Executes `instr` on the current CPU with the given `input` using the
+/// [`Backend::JitLite`] backend, returning the observed architectural
+/// state. Use [`execute_with_backend`] directly to run against
+/// [`Backend::Compiled`] instead -- there's no `build.rs` wrapper
+/// generation for it yet (see [`powerpc64::execute`]), so every case
+/// currently comes back [`Error::NotYetImplemented`].
+pub fn execute(instr: Instr, input: InstructionInput) -> Result<InstructionOutput, Error> {
+    execute_with_backend(instr, input, Backend::JitLite)
+}
+
+/// Runs [`execute`] `repeat_count` times, returning every observed result.
+/// A generator/asm bug or environmental interference can make native
+/// execution of the same `(instr, input)` nondeterministic; see
+/// [`outputs_agree`].
+pub fn execute_repeated(
+    instr: Instr,
+    input: InstructionInput,
+    repeat_count: usize,
+) -> Vec<Result<InstructionOutput, Error>> {
+    (0..repeat_count).map(|_| execute(instr, input)).collect()
+}
+
+/// Whether every successful execution in `outputs` agrees. `Err`s are
+/// ignored -- a case that simply couldn't be run natively is an execution
+/// problem, not flakiness.
+pub fn outputs_agree(outputs: &[Result<InstructionOutput, Error>]) -> bool {
+    let mut successes = outputs.iter().filter_map(|output| output.as_ref().ok());
+    match successes.next() {
+        None => true,
+        Some(first) => successes.all(|output| output == first),
+    }
+}
+
+/// Like [`execute`], but lets the caller pick which execution strategy to
+/// use.
+pub fn execute_with_backend(
+    instr: Instr,
+    input: InstructionInput,
+    backend: Backend,
+) -> Result<InstructionOutput, Error> {
+    if instr.is_model_only() {
+        return Err(Error::ModelOnly(instr));
+    }
+    if instr.required_privilege() != Privilege::Problem {
+        return Err(Error::RequiresPrivilege(instr, instr.required_privilege()));
+    }
+    #[cfg(target_arch = "powerpc")]
+    if instr.requires_doubleword_gprs() {
+        return Err(Error::RequiresDoublewordGprs(instr));
+    }
+    execute_with_backend_supported(instr, input, backend)
+}
+
+#[cfg(target_arch = "powerpc64")]
+fn execute_with_backend_supported(
+    instr: Instr,
+    input: InstructionInput,
+    backend: Backend,
+) -> Result<InstructionOutput, Error> {
+    match backend {
+        Backend::Compiled => powerpc64::execute(instr, input),
+        Backend::JitLite => jit::execute(instr, input).map_err(Error::Jit),
+    }
+}
+
+#[cfg(target_arch = "powerpc")]
+fn execute_with_backend_supported(
+    instr: Instr,
+    input: InstructionInput,
+    backend: Backend,
+) -> Result<InstructionOutput, Error> {
+    match backend {
+        Backend::Compiled => powerpc32::execute(instr, input),
+        // The JIT-lite encoder only emits the 64-bit register save/restore
+        // sequence `jit` needs; extending it to 32-bit GPRs is future work.
+        Backend::JitLite => Err(Error::UnsupportedPlatform),
+    }
+}
+
+#[cfg(not(any(target_arch = "powerpc64", target_arch = "powerpc")))]
+fn execute_with_backend_supported(
+    _instr: Instr,
+    _input: InstructionInput,
+    _backend: Backend,
+) -> Result<InstructionOutput, Error> {
+    Err(Error::UnsupportedPlatform)
+}
+
+/// Runs `instr` natively under every register assignment in
+/// [`RegisterAssignment::STRESS_SET`], to detect cases where the result
+/// depends on operand aliasing (`ra == rb`, `rt == ra`, ...) rather than
+/// just operand values. Requires the [`Backend::JitLite`] backend, since
+/// the compiled wrappers use a fixed register assignment.
+#[cfg(target_arch = "powerpc64")]
+pub fn stress_test_register_allocation(
+    instr: Instr,
+    input: InstructionInput,
+) -> Result<RegisterStressReport, Error> {
+    let results = RegisterAssignment::STRESS_SET
+        .iter()
+        .map(|&regs| jit::execute_with_regs(instr, input, regs).map(|output| (regs, output)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::Jit)?;
+    Ok(RegisterStressReport { results })
+}
+
+#[cfg(not(target_arch = "powerpc64"))]
+pub fn stress_test_register_allocation(
+    _instr: Instr,
+    _input: InstructionInput,
+) -> Result<RegisterStressReport, Error> {
+    Err(Error::UnsupportedPlatform)
+}
+
+/// Checks that running `instr` natively doesn't disturb any `XER` bit this
+/// crate doesn't model (see [`jit::check_xer_hazard`]), guarding against
+/// both bugs in this crate's own register save/restore and undocumented
+/// hardware behavior.
+#[cfg(target_arch = "powerpc64")]
+pub fn check_xer_hazard(instr: Instr) -> Result<XerHazardReport, Error> {
+    jit::check_xer_hazard(instr, RegisterAssignment::DEFAULT).map_err(Error::Jit)
+}
+
+#[cfg(not(target_arch = "powerpc64"))]
+pub fn check_xer_hazard(_instr: Instr) -> Result<XerHazardReport, Error> {
+    Err(Error::UnsupportedPlatform)
+}
+
+/// Checks that running `instr` natively neither depends on nor disturbs
+/// `XER`'s reserved bits (see [`jit::check_xer_reserved_bits`] and
+/// [`XerReservedBitReport`]), for instructions where [`check_xer_hazard`]'s
+/// single fixed sentinel isn't enough to tell "always preserves this one
+/// pattern" apart from "genuinely ignores and preserves the bits".
+#[cfg(target_arch = "powerpc64")]
+pub fn check_xer_reserved_bits(instr: Instr) -> Result<XerReservedBitReport, Error> {
+    jit::check_xer_reserved_bits(instr, RegisterAssignment::DEFAULT).map_err(Error::Jit)
+}
+
+#[cfg(not(target_arch = "powerpc64"))]
+pub fn check_xer_reserved_bits(_instr: Instr) -> Result<XerReservedBitReport, Error> {
+    Err(Error::UnsupportedPlatform)
+}
+
+/// Assembles and runs a [`crate::program::Program`] natively (see
+/// [`jit::run_program`]), returning its last op's `rt`.
+#[cfg(target_arch = "powerpc64")]
+pub fn run_program(program: &crate::program::Program) -> Result<u64, Error> {
+    jit::run_program(program).map_err(Error::Jit)
+}
+
+#[cfg(not(target_arch = "powerpc64"))]
+pub fn run_program(_program: &crate::program::Program) -> Result<u64, Error> {
+    Err(Error::UnsupportedPlatform)
+}
+
+#[cfg(target_arch = "powerpc64")]
+mod powerpc64 {
+    use super::*;
+
+    /// Runs a single instruction natively. The real implementation is meant
+    /// to emit a small per-instruction asm stub that loads `input` into the
+    /// relevant registers, executes the instruction, and reads the results
+    /// back out; that plumbing is supposed to live in `build.rs`, generated
+    /// for every instruction where [`Instr::is_model_only`] is false. No
+    /// such `build.rs` exists yet, so every instruction comes back
+    /// [`Error::NotYetImplemented`] rather than a fabricated result; use
+    /// [`Backend::JitLite`] (see [`jit`]) until this is wired up.
+    pub fn execute(instr: Instr, input: InstructionInput) -> Result<InstructionOutput, Error> {
+        let _ = input;
+        Err(Error::NotYetImplemented(instr))
+    }
+}
+
+#[cfg(target_arch = "powerpc")]
+mod powerpc32 {
+    use super::*;
+
+    /// The 32-bit counterpart to [`powerpc64::execute`], covering the
+    /// word-sized instructions a 32-bit implementation actually has an
+    /// opcode for. [`execute_with_backend`] rejects every
+    /// [`Instr::requires_doubleword_gprs`] instruction before this is ever
+    /// reached, since this host's GPRs are too narrow to hold their
+    /// operands. Like [`powerpc64::execute`], there is no `build.rs`
+    /// wrapper generation yet, so this always returns
+    /// [`Error::NotYetImplemented`].
+    pub fn execute(instr: Instr, input: InstructionInput) -> Result<InstructionOutput, Error> {
+        let _ = input;
+        Err(Error::NotYetImplemented(instr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::InstructionInput;
+
+    #[test]
+    fn model_only_instructions_are_rejected_before_touching_a_backend() {
+        let result = execute(Instr::Cfuged, InstructionInput::default());
+        assert!(matches!(result, Err(Error::ModelOnly(Instr::Cfuged))));
+    }
+
+    #[test]
+    fn outputs_agree_ignores_errors_but_not_disagreeing_successes() {
+        let a = InstructionOutput { rt: Some(1), ..InstructionOutput::default() };
+        let b = InstructionOutput { rt: Some(2), ..InstructionOutput::default() };
+        assert!(outputs_agree(&[Ok(a), Ok(a), Err(Error::UnsupportedPlatform)]));
+        assert!(!outputs_agree(&[Ok(a), Ok(b)]));
+        assert!(outputs_agree(&[]));
+    }
+}
@@ -0,0 +1,586 @@
+//! The "JIT-lite" native backend: encodes a single instruction at runtime,
+//! writes it into a writable-then-executable code buffer together with
+//! register save/restore stubs, and executes it directly.
+//!
+//! Unlike the `build.rs`-generated wrappers, this lets the analyzer test
+//! register numbers and instructions that weren't known when the crate was
+//! compiled. It's "signal-safe" in the sense that the buffer never holds a
+//! mix of writable and executable permissions at once (`W^X`) and the
+//! generated code only ever touches its own GPRs, so a fault inside the
+//! buffer can't corrupt unrelated process state.
+
+use super::RegisterAssignment;
+use crate::encoder;
+use crate::instr::Instr;
+use crate::program::{Operand, Program, Reg, UnboundReg};
+use crate::types::ConditionRegister;
+use crate::types::InstructionInput;
+use crate::types::InstructionOutput;
+use crate::types::Xer;
+use std::collections::HashMap;
+use std::fmt;
+use std::ptr;
+
+#[derive(Debug)]
+pub enum Error {
+    Mmap(std::io::Error),
+    Mprotect(std::io::Error),
+    /// [`run_program`] read a [`Reg`] no earlier op in the program wrote.
+    UnboundReg(UnboundReg),
+    /// [`run_program`] needs more live result registers than it has
+    /// caller-saved GPRs (`r3..=r10`) to assign them to.
+    TooManyRegisters,
+    /// [`run_program`] can only load a literal operand into a GPR via
+    /// `li`'s 16-bit signed immediate; `value` doesn't fit.
+    ImmediateOutOfRange(u64),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Mmap(err) => write!(f, "mmap failed: {}", err),
+            Error::Mprotect(err) => write!(f, "mprotect failed: {}", err),
+            Error::UnboundReg(err) => write!(f, "{}", err),
+            Error::TooManyRegisters => write!(f, "program needs more than 8 live result registers"),
+            Error::ImmediateOutOfRange(value) => {
+                write!(f, "{} does not fit `li`'s 16-bit signed immediate", value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A single page of memory that starts out writable and is made executable
+/// (and read-only) before anything jumps into it, and is unmapped on drop.
+struct CodeBuffer {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+impl CodeBuffer {
+    fn new(len: usize) -> Result<Self, Error> {
+        let len = len.max(page_size());
+        // SAFETY: a fixed-size anonymous private mapping with no file
+        // backing; `ptr` is checked for `MAP_FAILED` below.
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(Error::Mmap(std::io::Error::last_os_error()));
+        }
+        Ok(Self { ptr, len })
+    }
+
+    /// Writes `code` at the start of the buffer. Must be called before
+    /// [`Self::make_executable`].
+    fn write(&mut self, code: &[u32]) {
+        assert!(code.len() * 4 <= self.len, "code does not fit in the buffer");
+        // SAFETY: `ptr` is a writable mapping at least `code.len() * 4`
+        // bytes long.
+        unsafe {
+            ptr::copy_nonoverlapping(code.as_ptr(), self.ptr.cast(), code.len());
+        }
+    }
+
+    /// Flips the buffer from writable to executable (`W^X`), and returns a
+    /// callable pointer to its start.
+    fn make_executable(&mut self) -> Result<extern "C" fn(), Error> {
+        self.activate()?;
+        // SAFETY: the mapping is now executable and holds a valid function
+        // prologue written by `write`.
+        Ok(unsafe { std::mem::transmute::<*mut libc::c_void, extern "C" fn()>(self.ptr) })
+    }
+
+    /// Like [`Self::make_executable`], but for code that loads its result
+    /// into `r3` before `blr`, matching where the PPC64 ELF ABI expects a
+    /// function's `u64` return value.
+    fn make_executable_returning_u64(&mut self) -> Result<extern "C" fn() -> u64, Error> {
+        self.activate()?;
+        // SAFETY: as above; the generated code's contract with its caller
+        // is that it leaves a valid `u64` in `r3` before returning.
+        Ok(unsafe { std::mem::transmute::<*mut libc::c_void, extern "C" fn() -> u64>(self.ptr) })
+    }
+
+    /// Like [`Self::make_executable`], but for code that takes a pointer
+    /// to an I/O struct in `r3` -- the PPC64 ELF ABI's first-argument
+    /// register, for both the big-endian and little-endian ABI variants --
+    /// reads its inputs from it, and writes its outputs back into it
+    /// before `blr`. Used instead of [`Self::make_executable_returning_u64`]
+    /// when more than one `u64` needs to cross the call boundary.
+    fn make_executable_with_io<T>(&mut self) -> Result<extern "C" fn(*mut T), Error> {
+        self.activate()?;
+        // SAFETY: as above; the generated code's contract with its caller
+        // is that it only reads/writes `*mut T` through the fields the
+        // caller laid out, via loads/stores this module itself encoded.
+        Ok(unsafe { std::mem::transmute::<*mut libc::c_void, extern "C" fn(*mut T)>(self.ptr) })
+    }
+
+    /// Flips the buffer from writable to executable (`W^X`); shared by
+    /// [`Self::make_executable`], [`Self::make_executable_returning_u64`],
+    /// and [`Self::make_executable_with_io`], which only differ in how
+    /// they interpret the resulting pointer.
+    fn activate(&mut self) -> Result<(), Error> {
+        // SAFETY: `ptr`/`len` describe the mapping created in `new`.
+        let result = unsafe { libc::mprotect(self.ptr, self.len, libc::PROT_READ | libc::PROT_EXEC) };
+        if result != 0 {
+            return Err(Error::Mprotect(std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for CodeBuffer {
+    fn drop(&mut self) {
+        // SAFETY: `ptr`/`len` describe the mapping created in `new`, which
+        // is only ever unmapped here.
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+fn page_size() -> usize {
+    // SAFETY: `sysconf` with `_SC_PAGESIZE` has no preconditions.
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Executes `instr` by encoding it, wrapping it in register save/restore
+/// stubs, and running it from a fresh [`CodeBuffer`], using the register
+/// assignment that realizes `input.aliasing`.
+pub fn execute(instr: Instr, input: InstructionInput) -> Result<InstructionOutput, Error> {
+    execute_with_regs(instr, input, RegisterAssignment::for_aliasing(input.aliasing))
+}
+
+/// The inputs [`execute_with_regs`]'s generated code reads and the outputs
+/// it writes back, addressed via a pointer passed in `r3` (the PPC64 ELF
+/// ABI's first-argument register, for both the big- and little-endian ABI
+/// variants) rather than crossing the call boundary as GPR contents --
+/// there are more fields here than [`CodeBuffer::make_executable_returning_u64`]
+/// can report through `r3` alone.
+#[repr(C)]
+struct RegsIo {
+    ra: u64,
+    rb: u64,
+    xer: u64,
+    rt: u64,
+    raw_cr: u64,
+    xer_after: u64,
+}
+
+/// The GPR [`execute_with_regs`] copies its incoming [`RegsIo`] pointer
+/// into (out of `r3`, before `r3` is clobbered by `instr`'s own operands
+/// or result) and addresses every load/store to it through afterward.
+/// Reuses [`PROGRAM_SCRATCH_GPRS`]'s choice of registers outside
+/// [`RegisterAssignment`]'s usual `r3..=r5` range, so it can never alias
+/// `regs.rt`/`regs.ra`/`regs.rb`.
+const REGS_IO_PTR_GPR: u32 = PROGRAM_SCRATCH_GPRS[0];
+
+/// Scratch GPR [`execute_with_regs`] round-trips `XER` (via
+/// `mtspr`/`mfspr`) and `CR` (via `mfcr`) through. Disjoint from
+/// [`REGS_IO_PTR_GPR`] for the same reason.
+const XER_CR_SCRATCH_GPR: u32 = PROGRAM_SCRATCH_GPRS[1];
+
+/// Like [`execute`], but lets the caller choose which GPRs `instr`'s
+/// operands are assigned to, so that register-aliasing cases (`ra == rb`,
+/// `rt == ra`, ...) can be probed.
+///
+/// Sets `FPSCR` to `input.fpscr` before `instr` and restores it to the
+/// architectural default afterward (see [`encoder::fpscr_setup_words`]), so
+/// a case's rounding mode/exception-enable choice can't leak into whichever
+/// case the host runs next. No currently native-executable instruction
+/// (see [`Instr::is_model_only`]) actually reads `FPSCR`, so this has no
+/// observable effect yet -- it's in place for when one does; likewise
+/// `input.cr0`/`input.rc` are never read by any instruction
+/// [`encoder::encode`] covers today, so they aren't loaded either.
+///
+/// Loads `input.ra`/`input.rb` into `regs.ra`/`regs.rb` (in that order, so
+/// when they're the same physical register -- [`Aliasing::RaEqRb`] and
+/// friends -- `input.rb`'s value is the one `instr` actually sees; pass
+/// equal `ra`/`rb` values for a well-defined aliased case), runs `instr`,
+/// and reads `rt`/`CR`/`XER` back out. `cr0`/`xer` in the result are
+/// `Some` only when [`Instr::writes_cr0`]/[`Instr::writes_xer`] say `instr`
+/// actually updates them, matching [`crate::model`]'s convention; `raw_cr`
+/// is always `Some`, since (like the model never populating it) it's
+/// purely forensic.
+///
+/// [`Aliasing::RaEqRb`]: crate::types::Aliasing::RaEqRb
+pub fn execute_with_regs(
+    instr: Instr,
+    input: InstructionInput,
+    regs: RegisterAssignment,
+) -> Result<InstructionOutput, Error> {
+    debug_assert!(
+        [regs.rt, regs.ra, regs.rb].iter().all(|&r| r != REGS_IO_PTR_GPR && r != XER_CR_SCRATCH_GPR),
+        "RegisterAssignment {:?} collides with execute_with_regs's reserved pointer/scratch GPRs",
+        regs,
+    );
+    let word = encoder::encode(instr, regs.rt, regs.ra, regs.rb)
+        .expect("native::execute rejects model-only instructions before reaching the jit-lite backend");
+    let mut code = Vec::new();
+    code.push(mr(REGS_IO_PTR_GPR, 3));
+    code.push(load_doubleword(regs.ra, REGS_IO_PTR_GPR, std::mem::offset_of!(RegsIo, ra) as i16));
+    code.push(load_doubleword(regs.rb, REGS_IO_PTR_GPR, std::mem::offset_of!(RegsIo, rb) as i16));
+    code.push(load_doubleword(
+        XER_CR_SCRATCH_GPR,
+        REGS_IO_PTR_GPR,
+        std::mem::offset_of!(RegsIo, xer) as i16,
+    ));
+    code.push(mtspr(XER_SPR, XER_CR_SCRATCH_GPR));
+    code.extend(encoder::fpscr_setup_words(input.fpscr));
+    code.push(word);
+    code.push(store_doubleword(regs.rt, REGS_IO_PTR_GPR, std::mem::offset_of!(RegsIo, rt) as i16));
+    code.push(mfcr(XER_CR_SCRATCH_GPR));
+    code.push(store_doubleword(
+        XER_CR_SCRATCH_GPR,
+        REGS_IO_PTR_GPR,
+        std::mem::offset_of!(RegsIo, raw_cr) as i16,
+    ));
+    code.push(mfspr(XER_CR_SCRATCH_GPR, XER_SPR));
+    code.push(store_doubleword(
+        XER_CR_SCRATCH_GPR,
+        REGS_IO_PTR_GPR,
+        std::mem::offset_of!(RegsIo, xer_after) as i16,
+    ));
+    code.extend(encoder::fpscr_restore_words());
+    code.push(0x4e80_0020); // blr
+    let mut buffer = CodeBuffer::new(page_size())?;
+    buffer.write(&code);
+    let run = buffer.make_executable_with_io::<RegsIo>()?;
+    let mut io = RegsIo { ra: input.ra, rb: input.rb, xer: input.xer.to_raw() as u64, rt: 0, raw_cr: 0, xer_after: 0 };
+    run(&mut io);
+    Ok(InstructionOutput {
+        rt: Some(io.rt),
+        cr0: instr.writes_cr0().then(|| ConditionRegister::unpack_cr_field(io.raw_cr as u32, 0)),
+        xer: instr.writes_xer().then(|| Xer::from_raw(io.xer_after as u32)),
+        raw_cr: Some(io.raw_cr as u32),
+        fpscr: None,
+    })
+}
+
+/// Runs `first` immediately followed by `second` from a single buffer,
+/// with `second`'s `ra` register set to `first`'s `rt` register -- a
+/// genuine register dependency between two adjacent instructions, as
+/// opposed to [`crate::sequence::run_native`] calling [`super::execute`]
+/// twice, which can't exercise whatever forwarding the real pipeline does
+/// between back-to-back dependent instructions.
+///
+/// Unlike [`execute_with_regs`], this doesn't load `ra`/`rb` from an
+/// [`InstructionInput`] or read `rt` back out afterward, so this only
+/// smoke-tests that encoding and executing two genuinely adjacent
+/// dependent instructions doesn't fault. A full before/after comparison
+/// against [`crate::sequence::run_model`]'s composed result awaits that
+/// wiring.
+pub fn execute_pair_with_regs(
+    first: Instr,
+    first_regs: RegisterAssignment,
+    second: Instr,
+    second_regs: RegisterAssignment,
+) -> Result<(), Error> {
+    assert_eq!(
+        first_regs.rt, second_regs.ra,
+        "execute_pair_with_regs models second.ra being fed from first.rt"
+    );
+    let first_word = encoder::encode(first, first_regs.rt, first_regs.ra, first_regs.rb)
+        .expect("native::execute rejects model-only instructions before reaching the jit-lite backend");
+    let second_word = encoder::encode(second, second_regs.rt, second_regs.ra, second_regs.rb)
+        .expect("native::execute rejects model-only instructions before reaching the jit-lite backend");
+    let mut buffer = CodeBuffer::new(page_size())?;
+    buffer.write(&[first_word, second_word, 0x4e80_0020]);
+    let run = buffer.make_executable()?;
+    run();
+    Ok(())
+}
+
+/// The caller-saved GPRs [`run_program`] assigns a [`Program`]'s result
+/// [`Reg`]s to, in assignment order. Bounded to the PPC64 ELF ABI's
+/// volatile integer registers so nothing here needs to save/restore a
+/// register the calling convention promises is already ours to clobber.
+const PROGRAM_RESULT_GPRS: &[u32] = &[3, 4, 5, 6, 7, 8, 9, 10];
+
+/// Scratch GPRs [`run_program`] loads literal operands into immediately
+/// before the op that reads them, kept disjoint from
+/// [`PROGRAM_RESULT_GPRS`] so loading a literal can never clobber an
+/// earlier op's still-needed result.
+const PROGRAM_SCRATCH_GPRS: [u32; 2] = [11, 12];
+
+/// Assembles `program` into one buffer -- assigning each [`Reg`] it writes
+/// a physical GPR from [`PROGRAM_RESULT_GPRS`] (first write wins) and
+/// loading literal operands via `li` into [`PROGRAM_SCRATCH_GPRS`]
+/// immediately before the op that reads them -- and runs it, returning the
+/// last op's `rt`.
+///
+/// This is deliberately bounded rather than a general register allocator:
+/// a [`Program`] that writes more than 8 distinct `Reg`s, or that uses a
+/// literal operand too large for `li`'s 16-bit signed immediate, is
+/// rejected with [`Error::TooManyRegisters`]/[`Error::ImmediateOutOfRange`]
+/// instead of silently mis-assembling.
+pub fn run_program(program: &Program) -> Result<u64, Error> {
+    let mut physical: HashMap<Reg, u32> = HashMap::new();
+    let mut code = Vec::new();
+    let mut last_rt_physical = None;
+    for op in &program.ops {
+        let ra = resolve_operand(op.ra, &physical, PROGRAM_SCRATCH_GPRS[0], &mut code)?;
+        let rb = resolve_operand(op.rb, &physical, PROGRAM_SCRATCH_GPRS[1], &mut code)?;
+        let rt_physical = match physical.get(&op.rt) {
+            Some(&assigned) => assigned,
+            None => {
+                let assigned = *PROGRAM_RESULT_GPRS.get(physical.len()).ok_or(Error::TooManyRegisters)?;
+                physical.insert(op.rt, assigned);
+                assigned
+            }
+        };
+        let word = encoder::encode(op.instr, rt_physical, ra, rb)
+            .expect("native::execute rejects model-only instructions before reaching the jit-lite backend");
+        code.push(word);
+        last_rt_physical = Some(rt_physical);
+    }
+    if let Some(rt_physical) = last_rt_physical {
+        if rt_physical != 3 {
+            code.push(mr(3, rt_physical));
+        }
+    }
+    code.push(0x4e80_0020); // blr
+    let mut buffer = CodeBuffer::new(page_size())?;
+    buffer.write(&code);
+    let run = buffer.make_executable_returning_u64()?;
+    Ok(run())
+}
+
+/// Resolves one op's operand to a physical GPR: an already-assigned
+/// result register for [`Operand::Reg`], or `scratch` (freshly loaded via
+/// `li`) for [`Operand::Literal`].
+fn resolve_operand(
+    operand: Operand,
+    physical: &HashMap<Reg, u32>,
+    scratch: u32,
+    code: &mut Vec<u32>,
+) -> Result<u32, Error> {
+    match operand {
+        Operand::Reg(reg) => physical.get(&reg).copied().ok_or_else(|| Error::UnboundReg(UnboundReg(reg))),
+        Operand::Literal(value) => {
+            let simm = i16::try_from(value as i64).map_err(|_| Error::ImmediateOutOfRange(value))?;
+            code.push(li(scratch, simm));
+            Ok(scratch)
+        }
+    }
+}
+
+/// Encodes `li rt, simm` (`addi rt, 0, simm`), loading a sign-extended
+/// 16-bit immediate into `rt`.
+fn li(rt: u32, simm: i16) -> u32 {
+    (14 << 26) | (rt << 21) | (simm as u16 as u32)
+}
+
+/// Encodes `mr rt, rs` (`or rt, rs, rs`), copying `rs` into `rt`.
+fn mr(rt: u32, rs: u32) -> u32 {
+    (31 << 26) | (rs << 21) | (rt << 16) | (rs << 11) | (444 << 1)
+}
+
+/// The SPR number for `XER`, as used by `mfspr`/`mtspr`.
+const XER_SPR: u32 = 1;
+
+/// Bits of `XER` this crate's models never claim to touch: the byte-count
+/// field used by (now-deprecated) load/store string instructions (bits
+/// 0..=6), the reserved bits above it (7..=17), and the reserved bits
+/// between `ca32`/`ov32` and `ca`/`ov`/`so` (20..=28). Only
+/// `so`/`ov`/`ca`/`ov32`/`ca32` (see [`crate::types::Xer`] and
+/// [`crate::types::xer_masks`]) are modeled, so any one of these 27 bits
+/// changing across a run is either a bug in this backend's own
+/// save/restore sequence or genuinely undocumented hardware behavior.
+///
+/// Previously miscomputed to include bits 18/19 (`ca32`/`ov32` themselves,
+/// which legitimately change for e.g. `addo`) and exclude bits 0..=4 (part
+/// of the byte-count field) -- every bit here now matches
+/// [`crate::types::xer_masks`] exactly (the two constants partition
+/// `u32::MAX`'s 32 bits with no overlap).
+const UNRELATED_XER_BITS: u32 = 0x1ff3_ffff;
+
+/// Primes `XER`'s unrelated bits with `sentinel`, runs `instr`, and reports
+/// both which of those bits came back changed and the modeled [`Xer`]
+/// fields `instr` actually computed, so a caller can tell reserved-bit
+/// *preservation* (did the sentinel bits survive) apart from reserved-bit
+/// *independence* (did the modeled result change depending on what the
+/// sentinel bits were -- see [`check_xer_reserved_bits`]).
+fn run_with_xer_sentinel(
+    instr: Instr,
+    regs: RegisterAssignment,
+    sentinel: u32,
+) -> Result<(super::XerHazardReport, Xer), Error> {
+    let word = encoder::encode(instr, regs.rt, regs.ra, regs.rb)
+        .expect("native::execute rejects model-only instructions before reaching the jit-lite backend");
+    let mut code = Vec::new();
+    code.extend(load_immediate32(0, sentinel));
+    code.push(mtspr(XER_SPR, 0));
+    code.push(word);
+    code.push(mfspr(3, XER_SPR)); // r3: the PPC64 ELF ABI's u64 return register.
+    code.push(0x4e80_0020); // blr
+    let mut buffer = CodeBuffer::new(page_size())?;
+    buffer.write(&code);
+    let run = buffer.make_executable_returning_u64()?;
+    let xer_after = run() as u32;
+    let report = super::XerHazardReport { disturbed_bits: (xer_after ^ sentinel) & UNRELATED_XER_BITS };
+    Ok((report, Xer::from_raw(xer_after)))
+}
+
+/// Primes `XER`'s unrelated bits with a known pattern, runs `instr`, and
+/// reports which of those bits came back changed.
+///
+/// This only exercises the bits [`execute_with_regs`] itself never loads or
+/// restores (`so`/`ov`/`ca`/`ov32`/`ca32` are the only ones it round-trips
+/// through [`InstructionInput::xer`]/the result's `xer`); this is the hook
+/// the generated mtxer/mfxer dance for the rest of `XER` should be run
+/// through before every native case, the same way
+/// `stress_test_register_allocation` probes GPR aliasing today.
+pub fn check_xer_hazard(instr: Instr, regs: RegisterAssignment) -> Result<super::XerHazardReport, Error> {
+    run_with_xer_sentinel(instr, regs, UNRELATED_XER_BITS).map(|(report, _)| report)
+}
+
+/// Runs `instr` twice, with the unrelated `XER` bits seeded to two
+/// different patterns (all-zero and all-one), to separate the two
+/// properties the ISA requires of reserved bits: that the modeled result
+/// doesn't depend on them, and that the instruction doesn't disturb them.
+/// See [`super::XerReservedBitReport`].
+pub fn check_xer_reserved_bits(
+    instr: Instr,
+    regs: RegisterAssignment,
+) -> Result<super::XerReservedBitReport, Error> {
+    let (report_zero, xer_zero) = run_with_xer_sentinel(instr, regs, 0)?;
+    let (report_ones, xer_ones) = run_with_xer_sentinel(instr, regs, UNRELATED_XER_BITS)?;
+    Ok(super::XerReservedBitReport {
+        preserves_reserved_bits: report_zero.is_clean() && report_ones.is_clean(),
+        ignores_reserved_bits: xer_zero == xer_ones,
+    })
+}
+
+/// Encodes `mfspr rt, spr`.
+fn mfspr(rt: u32, spr: u32) -> u32 {
+    (31 << 26) | (rt << 21) | ((spr & 0x1f) << 16) | ((spr >> 5) << 11) | (339 << 1)
+}
+
+/// Encodes `mtspr spr, rs`.
+fn mtspr(spr: u32, rs: u32) -> u32 {
+    (31 << 26) | (rs << 21) | ((spr & 0x1f) << 16) | ((spr >> 5) << 11) | (467 << 1)
+}
+
+/// Encodes `ld rt, offset(ra)` (DS-form opcode 58, `XO` field `00`).
+/// `offset` must be a multiple of 4 (the DS-form low two bits are the
+/// fixed `XO`, not part of the displacement) -- always true for the
+/// 8-byte-aligned [`RegsIo`] field offsets this module passes in.
+///
+/// Not named `ld`: that would shadow this module's own `std::` references.
+fn load_doubleword(rt: u32, ra: u32, offset: i16) -> u32 {
+    assert_eq!(offset & 0b11, 0, "DS-form displacement must be a multiple of 4");
+    (58 << 26) | (rt << 21) | (ra << 16) | (offset as u16 as u32 & 0xfffc)
+}
+
+/// Encodes `std rs, offset(ra)` (DS-form opcode 62, `XO` field `00`). See
+/// [`load_doubleword`] for the `offset` alignment requirement and the
+/// naming note.
+fn store_doubleword(rs: u32, ra: u32, offset: i16) -> u32 {
+    assert_eq!(offset & 0b11, 0, "DS-form displacement must be a multiple of 4");
+    (62 << 26) | (rs << 21) | (ra << 16) | (offset as u16 as u32 & 0xfffc)
+}
+
+/// Encodes `mfcr rt`, reading the whole 32-bit `CR` into `rt`.
+fn mfcr(rt: u32) -> u32 {
+    (31 << 26) | (rt << 21) | (19 << 1)
+}
+
+/// Encodes `lis rt, value[31:16]` followed by `ori rt, rt, value[15:0]`,
+/// loading an arbitrary 32-bit constant into `rt`.
+fn load_immediate32(rt: u32, value: u32) -> [u32; 2] {
+    let lis = (15 << 26) | (rt << 21) | (value >> 16);
+    let ori = (24 << 26) | (rt << 21) | (rt << 16) | (value & 0xffff);
+    [lis, ori]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn li_matches_known_encoding() {
+        // `li r3, 5` == `addi r3, 0, 5` == 0x38600005.
+        assert_eq!(li(3, 5), 0x3860_0005);
+    }
+
+    #[test]
+    fn li_sign_extends_a_negative_immediate_into_the_low_16_bits() {
+        // `li r3, -1` == 0x3860ffff.
+        assert_eq!(li(3, -1), 0x3860_ffff);
+    }
+
+    #[test]
+    fn mr_matches_known_encoding() {
+        // `mr r3, r4` == `or r3, r4, r4` == 0x7c832378.
+        assert_eq!(mr(3, 4), 0x7c83_2378);
+    }
+
+    #[test]
+    fn mfspr_matches_known_encoding() {
+        // `mfxer r3` == `mfspr r3, 1` == 0x7c6102a6.
+        assert_eq!(mfspr(3, XER_SPR), 0x7c61_02a6);
+    }
+
+    #[test]
+    fn mtspr_matches_known_encoding() {
+        // `mtxer r3` == `mtspr 1, r3` == 0x7c6103a6.
+        assert_eq!(mtspr(XER_SPR, 3), 0x7c61_03a6);
+    }
+
+    #[test]
+    fn load_immediate32_matches_known_encoding() {
+        // `lis r3, 0xdead` == 0x3c60dead; `ori r3, r3, 0xbeef` == 0x6063beef.
+        assert_eq!(load_immediate32(3, 0xdead_beef), [0x3c60_dead, 0x6063_beef]);
+    }
+
+    #[test]
+    fn unrelated_xer_bits_exactly_complements_the_modeled_xer_masks() {
+        use crate::types::xer_masks;
+        let modeled =
+            xer_masks::SO | xer_masks::OV | xer_masks::CA | xer_masks::OV32 | xer_masks::CA32;
+        assert_eq!(modeled & UNRELATED_XER_BITS, 0, "a modeled bit can't also be 'unrelated'");
+        assert_eq!(modeled | UNRELATED_XER_BITS, u32::MAX, "every XER bit must be one or the other");
+    }
+
+    #[test]
+    fn resolve_operand_looks_up_a_bound_reg() {
+        let mut physical = HashMap::new();
+        physical.insert(Reg(0), 5);
+        let mut code = Vec::new();
+        assert_eq!(resolve_operand(Operand::Reg(Reg(0)), &physical, 11, &mut code), Ok(5));
+        assert!(code.is_empty());
+    }
+
+    #[test]
+    fn resolve_operand_rejects_an_unbound_reg() {
+        let physical = HashMap::new();
+        let mut code = Vec::new();
+        let result = resolve_operand(Operand::Reg(Reg(0)), &physical, 11, &mut code);
+        assert!(matches!(result, Err(Error::UnboundReg(UnboundReg(Reg(0))))));
+    }
+
+    #[test]
+    fn resolve_operand_loads_a_literal_into_the_scratch_reg() {
+        let physical = HashMap::new();
+        let mut code = Vec::new();
+        assert_eq!(resolve_operand(Operand::Literal(5), &physical, 11, &mut code), Ok(11));
+        assert_eq!(code, [li(11, 5)]);
+    }
+
+    #[test]
+    fn resolve_operand_rejects_a_literal_too_wide_for_li() {
+        let physical = HashMap::new();
+        let mut code = Vec::new();
+        let result = resolve_operand(Operand::Literal(1 << 20), &physical, 11, &mut code);
+        assert!(matches!(result, Err(Error::ImmediateOutOfRange(value)) if value == 1 << 20));
+    }
+}
@@ -0,0 +1,121 @@
+//! Generates a neighborhood of nearby inputs around a seed input, to
+//! automatically map out the extent of a divergence [`crate::campaign`]'s
+//! generic sweep happened to land on just one point of, instead of having
+//! to hand-craft follow-up cases around every mismatch found.
+
+use crate::capture::WholeTest;
+use crate::instr::Instr;
+use crate::types::InstructionInput;
+use std::collections::HashSet;
+
+/// Perturbations tried around `seed`'s `ra`/`rb`: every single bit flip,
+/// `+-1`, and a full sign flip -- the small set of nearby values most
+/// likely to land on the same divergence, rather than duplicating
+/// [`crate::campaign`]'s much broader, unrelated sweep.
+pub fn neighborhood(seed: InstructionInput) -> Vec<InstructionInput> {
+    let mut neighbors = Vec::new();
+    for bit in 0..64 {
+        neighbors.push(InstructionInput { ra: seed.ra ^ (1 << bit), ..seed });
+        neighbors.push(InstructionInput { rb: seed.rb ^ (1 << bit), ..seed });
+    }
+    neighbors.push(InstructionInput { ra: seed.ra.wrapping_add(1), ..seed });
+    neighbors.push(InstructionInput { ra: seed.ra.wrapping_sub(1), ..seed });
+    neighbors.push(InstructionInput { rb: seed.rb.wrapping_add(1), ..seed });
+    neighbors.push(InstructionInput { rb: seed.rb.wrapping_sub(1), ..seed });
+    neighbors.push(InstructionInput { ra: (seed.ra as i64).wrapping_neg() as u64, ..seed });
+    neighbors.push(InstructionInput { rb: (seed.rb as i64).wrapping_neg() as u64, ..seed });
+    neighbors.retain(|&candidate| candidate != seed);
+    neighbors.sort();
+    neighbors.dedup();
+    neighbors
+}
+
+/// [`neighborhood`] around every mismatching case already in `whole_test`,
+/// deduplicated against each other and against cases `whole_test` already
+/// has -- a follow-up case list to run through the same execution pipeline
+/// (e.g. [`crate::remote::Farm::run_sharded`]) a mismatching run already
+/// used, not a new one of its own.
+pub fn neighbor_cases_for_mismatches(whole_test: &WholeTest) -> Vec<(Instr, InstructionInput)> {
+    let mut seen: HashSet<(Instr, InstructionInput)> =
+        whole_test.test_cases.iter().map(|case| (case.instr, case.input)).collect();
+    let mut cases = Vec::new();
+    for case in &whole_test.test_cases {
+        if case.matches() {
+            continue;
+        }
+        for neighbor in neighborhood(case.input) {
+            if seen.insert((case.instr, neighbor)) {
+                cases.push((case.instr, neighbor));
+            }
+        }
+    }
+    cases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::TestCase;
+    use crate::types::InstructionOutput;
+
+    #[test]
+    fn neighborhood_excludes_the_seed_itself() {
+        let seed = InstructionInput { ra: 5, rb: 9, ..InstructionInput::default() };
+        assert!(!neighborhood(seed).contains(&seed));
+    }
+
+    #[test]
+    fn neighborhood_includes_single_bit_flips_and_plus_minus_one() {
+        let seed = InstructionInput::default();
+        let neighbors = neighborhood(seed);
+        assert!(neighbors.contains(&InstructionInput { ra: 1, ..seed }));
+        assert!(neighbors.contains(&InstructionInput { rb: 1, ..seed }));
+        assert!(neighbors.contains(&InstructionInput { ra: u64::MAX, ..seed })); // 0 - 1
+    }
+
+    fn case(instr: Instr, input: InstructionInput, matches: bool) -> TestCase {
+        let output = InstructionOutput { rt: Some(0), ..InstructionOutput::default() };
+        let other = InstructionOutput { rt: Some(1), ..InstructionOutput::default() };
+        TestCase {
+            instr,
+            input,
+            native_output: output,
+            model_output: if matches { output } else { other },
+            model_revision: 1,
+            skip: None, latency: None,
+        }
+    }
+
+    #[test]
+    fn only_mismatching_cases_get_expanded() {
+        let whole_test = WholeTest {
+            test_cases: vec![
+                case(Instr::Add, InstructionInput { ra: 1, ..InstructionInput::default() }, true),
+                case(Instr::Add, InstructionInput { ra: 2, ..InstructionInput::default() }, false),
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        let expanded = neighbor_cases_for_mismatches(&whole_test);
+        assert!(!expanded.is_empty());
+        assert!(expanded.iter().all(|&(_, input)| input.ra != 1));
+    }
+
+    #[test]
+    fn already_present_neighbors_are_not_duplicated() {
+        let mismatch_input = InstructionInput { ra: 2, ..InstructionInput::default() };
+        let already_present_neighbor = InstructionInput { ra: 3, ..InstructionInput::default() };
+        let whole_test = WholeTest {
+            test_cases: vec![
+                case(Instr::Add, mismatch_input, false),
+                case(Instr::Add, already_present_neighbor, true),
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        let expanded = neighbor_cases_for_mismatches(&whole_test);
+        assert!(expanded.iter().all(|&(_, input)| input != already_present_neighbor));
+    }
+}
@@ -0,0 +1,446 @@
+//! Exports a [`WholeTest`] as a flattened Parquet table (`pia
+//! export-parquet`), one row per [`TestCase`], for pandas/polars-style
+//! analysis of a large corpus (e.g. mismatch rate grouped by operand sign)
+//! without a CSV intermediate or a bespoke parser.
+//!
+//! All registers (`ra`/`rb`/`rc`/`rt`/`raw_cr`) are stored as their exact
+//! bit pattern reinterpreted as a signed integer column (`u64 as i64`,
+//! `u32 as i32`) rather than as unsigned -- Parquet has no unsigned
+//! physical type, and the cast round-trips exactly through two's
+//! complement, so a consumer that wants the original `u64`/`u32` back just
+//! casts the other way. `model_output`/`native_output` fields an
+//! instruction didn't write stay Parquet `NULL` rather than `0`, matching
+//! how [`InstructionOutput`] itself distinguishes "didn't happen" from
+//! "happened to be zero" (see [`crate::types`]).
+
+use crate::capture::WholeTest;
+use crate::types::{Aliasing, InstructionOutput, RoundingMode};
+use parquet::basic::{Repetition, Type as PhysicalType};
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, Int32Type, Int64Type};
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::types::Type as SchemaType;
+use std::fmt;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct Error(ParquetError);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "parquet error: {}", self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ParquetError> for Error {
+    fn from(err: ParquetError) -> Self {
+        Error(err)
+    }
+}
+
+fn aliasing_to_text(aliasing: Aliasing) -> &'static str {
+    match aliasing {
+        Aliasing::None => "none",
+        Aliasing::RaEqRb => "ra_eq_rb",
+        Aliasing::RtEqRa => "rt_eq_ra",
+        Aliasing::RtEqRaEqRb => "rt_eq_ra_eq_rb",
+    }
+}
+
+fn rounding_mode_to_text(rn: RoundingMode) -> &'static str {
+    match rn {
+        RoundingMode::Nearest => "nearest",
+        RoundingMode::TowardZero => "toward_zero",
+        RoundingMode::TowardPositiveInfinity => "toward_positive_infinity",
+        RoundingMode::TowardNegativeInfinity => "toward_negative_infinity",
+    }
+}
+
+fn primitive(name: &str, physical_type: PhysicalType, repetition: Repetition) -> Arc<SchemaType> {
+    Arc::new(
+        SchemaType::primitive_type_builder(name, physical_type)
+            .with_repetition(repetition)
+            .build()
+            .expect("column name/type is always valid"),
+    )
+}
+
+fn required_bool(name: &str) -> Arc<SchemaType> {
+    primitive(name, PhysicalType::BOOLEAN, Repetition::REQUIRED)
+}
+
+fn optional_bool(name: &str) -> Arc<SchemaType> {
+    primitive(name, PhysicalType::BOOLEAN, Repetition::OPTIONAL)
+}
+
+fn required_i64(name: &str) -> Arc<SchemaType> {
+    primitive(name, PhysicalType::INT64, Repetition::REQUIRED)
+}
+
+fn optional_i64(name: &str) -> Arc<SchemaType> {
+    primitive(name, PhysicalType::INT64, Repetition::OPTIONAL)
+}
+
+fn optional_i32(name: &str) -> Arc<SchemaType> {
+    primitive(name, PhysicalType::INT32, Repetition::OPTIONAL)
+}
+
+fn required_utf8(name: &str) -> Arc<SchemaType> {
+    Arc::new(
+        SchemaType::primitive_type_builder(name, PhysicalType::BYTE_ARRAY)
+            .with_repetition(Repetition::REQUIRED)
+            .with_logical_type(Some(parquet::basic::LogicalType::String))
+            .build()
+            .expect("column name/type is always valid"),
+    )
+}
+
+fn optional_utf8(name: &str) -> Arc<SchemaType> {
+    Arc::new(
+        SchemaType::primitive_type_builder(name, PhysicalType::BYTE_ARRAY)
+            .with_repetition(Repetition::OPTIONAL)
+            .with_logical_type(Some(parquet::basic::LogicalType::String))
+            .build()
+            .expect("column name/type is always valid"),
+    )
+}
+
+/// Column names for one recorded output (`native_*`/`model_*`), shared
+/// between the schema and the row-building code so the two can't drift
+/// apart.
+fn output_columns(prefix: &str) -> Vec<Arc<SchemaType>> {
+    vec![
+        optional_i64(&format!("{}_rt", prefix)),
+        optional_bool(&format!("{}_cr0_lt", prefix)),
+        optional_bool(&format!("{}_cr0_gt", prefix)),
+        optional_bool(&format!("{}_cr0_eq", prefix)),
+        optional_bool(&format!("{}_cr0_so", prefix)),
+        optional_bool(&format!("{}_xer_so", prefix)),
+        optional_bool(&format!("{}_xer_ov", prefix)),
+        optional_bool(&format!("{}_xer_ca", prefix)),
+        optional_bool(&format!("{}_xer_ov32", prefix)),
+        optional_bool(&format!("{}_xer_ca32", prefix)),
+        optional_i32(&format!("{}_raw_cr", prefix)),
+        optional_utf8(&format!("{}_fpscr_rn", prefix)),
+        optional_bool(&format!("{}_fpscr_ve", prefix)),
+        optional_bool(&format!("{}_fpscr_oe", prefix)),
+        optional_bool(&format!("{}_fpscr_ue", prefix)),
+        optional_bool(&format!("{}_fpscr_ze", prefix)),
+        optional_bool(&format!("{}_fpscr_xe", prefix)),
+    ]
+}
+
+fn schema() -> Arc<SchemaType> {
+    let mut fields = vec![
+        required_utf8("instr"),
+        required_i64("ra"),
+        required_i64("rb"),
+        required_i64("rc"),
+        required_utf8("aliasing"),
+        required_bool("input_cr0_lt"),
+        required_bool("input_cr0_gt"),
+        required_bool("input_cr0_eq"),
+        required_bool("input_cr0_so"),
+        required_bool("input_xer_so"),
+        required_bool("input_xer_ov"),
+        required_bool("input_xer_ca"),
+        required_bool("input_xer_ov32"),
+        required_bool("input_xer_ca32"),
+        required_utf8("input_fpscr_rn"),
+        required_bool("input_fpscr_ve"),
+        required_bool("input_fpscr_oe"),
+        required_bool("input_fpscr_ue"),
+        required_bool("input_fpscr_ze"),
+        required_bool("input_fpscr_xe"),
+        required_i64("model_revision"),
+        required_bool("matches"),
+    ];
+    fields.extend(output_columns("native"));
+    fields.extend(output_columns("model"));
+    Arc::new(
+        SchemaType::group_type_builder("test_case")
+            .with_fields(fields)
+            .build()
+            .expect("schema is always valid"),
+    )
+}
+
+/// One column's worth of `Option<T>` values, split into the packed
+/// non-null values Parquet's column writer expects plus a definition
+/// level (0 = null, 1 = present) per row.
+struct OptionalColumn<T> {
+    values: Vec<T>,
+    def_levels: Vec<i16>,
+}
+
+impl<T> Default for OptionalColumn<T> {
+    fn default() -> Self {
+        OptionalColumn { values: Vec::new(), def_levels: Vec::new() }
+    }
+}
+
+impl<T> OptionalColumn<T> {
+    fn push(&mut self, value: Option<T>) {
+        match value {
+            Some(value) => {
+                self.values.push(value);
+                self.def_levels.push(1);
+            }
+            None => self.def_levels.push(0),
+        }
+    }
+}
+
+/// One recorded output's columns (`native_*`/`model_*`), built up one
+/// [`TestCase`] at a time by [`push`](Self::push).
+#[derive(Default)]
+struct OutputColumns {
+    rt: OptionalColumn<i64>,
+    cr0_lt: OptionalColumn<bool>,
+    cr0_gt: OptionalColumn<bool>,
+    cr0_eq: OptionalColumn<bool>,
+    cr0_so: OptionalColumn<bool>,
+    xer_so: OptionalColumn<bool>,
+    xer_ov: OptionalColumn<bool>,
+    xer_ca: OptionalColumn<bool>,
+    xer_ov32: OptionalColumn<bool>,
+    xer_ca32: OptionalColumn<bool>,
+    raw_cr: OptionalColumn<i32>,
+    fpscr_rn: OptionalColumn<ByteArray>,
+    fpscr_ve: OptionalColumn<bool>,
+    fpscr_oe: OptionalColumn<bool>,
+    fpscr_ue: OptionalColumn<bool>,
+    fpscr_ze: OptionalColumn<bool>,
+    fpscr_xe: OptionalColumn<bool>,
+}
+
+impl OutputColumns {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&mut self, output: &InstructionOutput) {
+        self.rt.push(output.rt.map(|rt| rt as i64));
+        self.cr0_lt.push(output.cr0.map(|cr0| cr0.lt));
+        self.cr0_gt.push(output.cr0.map(|cr0| cr0.gt));
+        self.cr0_eq.push(output.cr0.map(|cr0| cr0.eq));
+        self.cr0_so.push(output.cr0.map(|cr0| cr0.so));
+        self.xer_so.push(output.xer.map(|xer| xer.so));
+        self.xer_ov.push(output.xer.map(|xer| xer.ov));
+        self.xer_ca.push(output.xer.map(|xer| xer.ca));
+        self.xer_ov32.push(output.xer.map(|xer| xer.ov32));
+        self.xer_ca32.push(output.xer.map(|xer| xer.ca32));
+        self.raw_cr.push(output.raw_cr.map(|raw_cr| raw_cr as i32));
+        self.fpscr_rn.push(output.fpscr.map(|fpscr| ByteArray::from(rounding_mode_to_text(fpscr.rn).as_bytes().to_vec())));
+        self.fpscr_ve.push(output.fpscr.map(|fpscr| fpscr.ve));
+        self.fpscr_oe.push(output.fpscr.map(|fpscr| fpscr.oe));
+        self.fpscr_ue.push(output.fpscr.map(|fpscr| fpscr.ue));
+        self.fpscr_ze.push(output.fpscr.map(|fpscr| fpscr.ze));
+        self.fpscr_xe.push(output.fpscr.map(|fpscr| fpscr.xe));
+    }
+
+    /// In the same order [`output_columns`] declares them.
+    fn write<W: std::io::Write + Send>(self, row_group: &mut parquet::file::writer::SerializedRowGroupWriter<'_, W>) -> Result<(), Error> {
+        write_optional_column::<Int64Type, W>(row_group, self.rt)?;
+        write_optional_column::<BoolType, W>(row_group, self.cr0_lt)?;
+        write_optional_column::<BoolType, W>(row_group, self.cr0_gt)?;
+        write_optional_column::<BoolType, W>(row_group, self.cr0_eq)?;
+        write_optional_column::<BoolType, W>(row_group, self.cr0_so)?;
+        write_optional_column::<BoolType, W>(row_group, self.xer_so)?;
+        write_optional_column::<BoolType, W>(row_group, self.xer_ov)?;
+        write_optional_column::<BoolType, W>(row_group, self.xer_ca)?;
+        write_optional_column::<BoolType, W>(row_group, self.xer_ov32)?;
+        write_optional_column::<BoolType, W>(row_group, self.xer_ca32)?;
+        write_optional_column::<Int32Type, W>(row_group, self.raw_cr)?;
+        write_optional_column::<ByteArrayType, W>(row_group, self.fpscr_rn)?;
+        write_optional_column::<BoolType, W>(row_group, self.fpscr_ve)?;
+        write_optional_column::<BoolType, W>(row_group, self.fpscr_oe)?;
+        write_optional_column::<BoolType, W>(row_group, self.fpscr_ue)?;
+        write_optional_column::<BoolType, W>(row_group, self.fpscr_ze)?;
+        write_optional_column::<BoolType, W>(row_group, self.fpscr_xe)?;
+        Ok(())
+    }
+}
+
+fn write_required_column<T, W>(
+    row_group: &mut parquet::file::writer::SerializedRowGroupWriter<'_, W>,
+    values: Vec<T::T>,
+) -> Result<(), Error>
+where
+    T: parquet::data_type::DataType,
+    W: std::io::Write + Send,
+{
+    let mut column_writer = row_group.next_column()?.expect("schema has a column for every written value");
+    column_writer.typed::<T>().write_batch(&values, None, None)?;
+    column_writer.close()?;
+    Ok(())
+}
+
+fn write_optional_column<T, W>(
+    row_group: &mut parquet::file::writer::SerializedRowGroupWriter<'_, W>,
+    column: OptionalColumn<T::T>,
+) -> Result<(), Error>
+where
+    T: parquet::data_type::DataType,
+    W: std::io::Write + Send,
+{
+    let mut column_writer = row_group.next_column()?.expect("schema has a column for every written value");
+    column_writer.typed::<T>().write_batch(&column.values, Some(&column.def_levels), None)?;
+    column_writer.close()?;
+    Ok(())
+}
+
+/// Writes `whole_test` as a Parquet file at `path` (created fresh -- an
+/// existing file at `path` is overwritten, matching [`File::create`]).
+pub fn export(whole_test: &WholeTest, path: &Path) -> Result<(), Error> {
+    let file = File::create(path).map_err(|err| Error(ParquetError::External(Box::new(err))))?;
+    let mut writer = SerializedFileWriter::new(file, schema(), Arc::new(WriterProperties::default()))?;
+    let mut row_group = writer.next_row_group()?;
+
+    let instr: Vec<ByteArray> = whole_test.test_cases.iter().map(|case| ByteArray::from(case.instr.name().as_bytes().to_vec())).collect();
+    write_required_column::<ByteArrayType, File>(&mut row_group, instr)?;
+
+    let ra: Vec<i64> = whole_test.test_cases.iter().map(|case| case.input.ra as i64).collect();
+    write_required_column::<Int64Type, File>(&mut row_group, ra)?;
+    let rb: Vec<i64> = whole_test.test_cases.iter().map(|case| case.input.rb as i64).collect();
+    write_required_column::<Int64Type, File>(&mut row_group, rb)?;
+    let rc: Vec<i64> = whole_test.test_cases.iter().map(|case| case.input.rc as i64).collect();
+    write_required_column::<Int64Type, File>(&mut row_group, rc)?;
+
+    let aliasing: Vec<ByteArray> =
+        whole_test.test_cases.iter().map(|case| ByteArray::from(aliasing_to_text(case.input.aliasing).as_bytes().to_vec())).collect();
+    write_required_column::<ByteArrayType, File>(&mut row_group, aliasing)?;
+
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.cr0.lt).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.cr0.gt).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.cr0.eq).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.cr0.so).collect())?;
+
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.xer.so).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.xer.ov).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.xer.ca).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.xer.ov32).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.xer.ca32).collect())?;
+
+    let fpscr_rn: Vec<ByteArray> = whole_test
+        .test_cases
+        .iter()
+        .map(|case| ByteArray::from(rounding_mode_to_text(case.input.fpscr.rn).as_bytes().to_vec()))
+        .collect();
+    write_required_column::<ByteArrayType, File>(&mut row_group, fpscr_rn)?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.fpscr.ve).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.fpscr.oe).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.fpscr.ue).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.fpscr.ze).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.input.fpscr.xe).collect())?;
+
+    write_required_column::<Int64Type, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.model_revision as i64).collect())?;
+    write_required_column::<BoolType, File>(&mut row_group, whole_test.test_cases.iter().map(|case| case.matches()).collect())?;
+
+    let mut native = OutputColumns::new();
+    let mut model = OutputColumns::new();
+    for case in &whole_test.test_cases {
+        native.push(&case.native_output);
+        model.push(&case.model_output);
+    }
+    native.write(&mut row_group)?;
+    model.write(&mut row_group)?;
+
+    row_group.close()?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::TestCase;
+    use crate::instr::Instr;
+    use crate::types::{ConditionRegister, InstructionInput};
+
+    fn sample() -> WholeTest {
+        WholeTest {
+            test_cases: vec![
+                TestCase {
+                    instr: Instr::Add,
+                    input: InstructionInput { ra: 1, rb: 2, ..InstructionInput::default() },
+                    native_output: InstructionOutput { rt: Some(3), ..InstructionOutput::default() },
+                    model_output: InstructionOutput { rt: Some(3), ..InstructionOutput::default() },
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+                TestCase {
+                    instr: Instr::AddDot,
+                    input: InstructionInput { ra: u64::MAX, rb: 1, ..InstructionInput::default() },
+                    native_output: InstructionOutput {
+                        rt: Some(0),
+                        cr0: Some(ConditionRegister { eq: true, ..ConditionRegister::default() }),
+                        ..InstructionOutput::default()
+                    },
+                    model_output: InstructionOutput::default(),
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        }
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("pia-parquet-export-test-{}-{}.parquet", std::process::id(), name))
+    }
+
+    #[test]
+    fn export_writes_a_readable_parquet_file_with_one_row_per_case() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let whole_test = sample();
+        let path = temp_path("basic");
+        export(&whole_test, &path).unwrap();
+
+        let file = File::open(&path).unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        let row_count = reader.metadata().file_metadata().num_rows();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(row_count as usize, whole_test.test_cases.len());
+    }
+
+    #[test]
+    fn export_round_trips_ra_at_the_top_of_its_range_via_the_i64_bit_cast() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::Field;
+
+        let whole_test = WholeTest {
+            test_cases: vec![TestCase {
+                instr: Instr::Add,
+                input: InstructionInput { ra: u64::MAX, ..InstructionInput::default() },
+                native_output: InstructionOutput::default(),
+                model_output: InstructionOutput::default(),
+                model_revision: 1,
+                skip: None, latency: None,
+            }],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        let path = temp_path("max-ra");
+        export(&whole_test, &path).unwrap();
+
+        let file = File::open(&path).unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        let row = reader.get_row_iter(None).unwrap().next().unwrap().unwrap();
+        let ra = row.get_column_iter().find(|(name, _)| name.as_str() == "ra").unwrap().1.clone();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(ra, Field::Long(-1));
+        assert_eq!(-1i64 as u64, u64::MAX);
+    }
+}
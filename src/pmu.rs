@@ -0,0 +1,155 @@
+//! Optional capture of hardware performance-counter values around native
+//! execution, via `perf_event_open(2)`. Used to spot instructions that are
+//! microcoded or otherwise assisted on the host CPU (e.g. denormal
+//! handling), which tends to show up as an outsized cycle count relative to
+//! the instructions-retired count.
+//!
+//! Linux-only, and only meaningful paired with the `powerpc64` native
+//! backend -- see [`crate::native`].
+
+use crate::instr::Instr;
+use crate::native::Error;
+use crate::types::InstructionInput;
+use serde::{Deserialize, Serialize};
+
+/// Aggregate counter values observed around a batch of native executions.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct PmuCounters {
+    pub cycles: u64,
+    pub instructions: u64,
+}
+
+/// Runs `instr` natively `count` times with PMU counters enabled around the
+/// whole batch, returning the aggregate counts alongside the last
+/// execution's architectural result.
+pub fn capture_batch(
+    instr: Instr,
+    input: InstructionInput,
+    count: u32,
+) -> Result<(PmuCounters, crate::types::InstructionOutput), Error> {
+    #[cfg(all(target_os = "linux", target_arch = "powerpc64"))]
+    {
+        linux::capture_batch(instr, input, count)
+    }
+    #[cfg(not(all(target_os = "linux", target_arch = "powerpc64")))]
+    {
+        let _ = (instr, input, count);
+        Err(Error::UnsupportedPlatform)
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "powerpc64"))]
+mod linux {
+    use super::*;
+    use crate::native;
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    /// Mirrors the kernel's `struct perf_event_attr`, truncated to the
+    /// fields this module actually sets; the kernel only reads `size`
+    /// bytes, so trailing fields may safely be omitted as zero.
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        type_: u32,
+        size: u32,
+        config: u64,
+        sample_period_or_freq: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events_or_watermark: u32,
+        bp_type: u32,
+        config1_or_bp_addr: u64,
+        config2_or_bp_len: u64,
+    }
+
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_COUNT_HW_CPU_CYCLES: u64 = 0;
+    const PERF_COUNT_HW_INSTRUCTIONS: u64 = 1;
+
+    fn open_counter(config: u64, group_fd: i32) -> io::Result<OwnedFd> {
+        let mut attr = PerfEventAttr {
+            type_: PERF_TYPE_HARDWARE,
+            size: std::mem::size_of::<PerfEventAttr>() as u32,
+            config,
+            ..PerfEventAttr::default()
+        };
+        // disabled=1 (bit 0), exclude_kernel=1 (bit 5): count only
+        // userspace cycles/instructions attributable to our own code.
+        attr.flags = (1 << 0) | (1 << 5);
+        // SAFETY: `attr` is a valid, correctly-sized `perf_event_attr` for
+        // the fields the kernel will read; `pid=0` (self), `cpu=-1` (any).
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const PerfEventAttr,
+                0,
+                -1,
+                group_fd,
+                0,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: a valid fd was just returned by `perf_event_open`.
+        Ok(unsafe { OwnedFd::from_raw_fd(fd as i32) })
+    }
+
+    fn read_u64(fd: &OwnedFd) -> io::Result<u64> {
+        let mut buf = [0u8; 8];
+        // SAFETY: `fd` is open for reading and `buf` is 8 bytes, matching
+        // the `u64` counter value the kernel writes.
+        let n = unsafe { libc::read(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+        if n != buf.len() as isize {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(u64::from_ne_bytes(buf))
+    }
+
+    fn reset_and_enable(fds: &[&OwnedFd]) {
+        for fd in fds {
+            // SAFETY: `fd` is a valid perf_event fd; these ioctls take no
+            // extra argument.
+            unsafe {
+                libc::ioctl(fd.as_raw_fd(), libc::PERF_EVENT_IOC_RESET, 0);
+                libc::ioctl(fd.as_raw_fd(), libc::PERF_EVENT_IOC_ENABLE, 0);
+            }
+        }
+    }
+
+    fn disable(fds: &[&OwnedFd]) {
+        for fd in fds {
+            // SAFETY: see `reset_and_enable`.
+            unsafe {
+                libc::ioctl(fd.as_raw_fd(), libc::PERF_EVENT_IOC_DISABLE, 0);
+            }
+        }
+    }
+
+    pub fn capture_batch(
+        instr: Instr,
+        input: InstructionInput,
+        count: u32,
+    ) -> Result<(PmuCounters, crate::types::InstructionOutput), Error> {
+        let cycles_fd = open_counter(PERF_COUNT_HW_CPU_CYCLES, -1).map_err(Error::Pmu)?;
+        let instructions_fd =
+            open_counter(PERF_COUNT_HW_INSTRUCTIONS, cycles_fd.as_raw_fd()).map_err(Error::Pmu)?;
+
+        reset_and_enable(&[&cycles_fd, &instructions_fd]);
+        let mut last_output = Default::default();
+        for _ in 0..count.max(1) {
+            last_output = native::execute(instr, input)?;
+        }
+        disable(&[&cycles_fd, &instructions_fd]);
+
+        Ok((
+            PmuCounters {
+                cycles: read_u64(&cycles_fd).map_err(Error::Pmu)?,
+                instructions: read_u64(&instructions_fd).map_err(Error::Pmu)?,
+            },
+            last_output,
+        ))
+    }
+}
@@ -0,0 +1,139 @@
+//! A small, explicit pseudo-random generator with serializable state, for
+//! campaigns that want random sampling instead of (or alongside)
+//! [`crate::campaign`]'s exhaustive generation.
+//!
+//! Deliberately not the `rand` crate: `rand`'s default generators aren't
+//! guaranteed stable across its own versions, so a captured seed wouldn't
+//! reliably reproduce the same sequence of cases on a newer `rand` release,
+//! and a campaign resumed mid-stream (after saving [`RandomGenerator::state`]
+//! to the on-disk cache) would silently diverge. [`RandomGenerator`] is
+//! xoshiro256** -- a fixed, fully specified algorithm with no
+//! version-dependent behavior to drift.
+
+use serde::{Deserialize, Serialize};
+
+/// A xoshiro256** generator. `Clone`/`Serialize`/`Deserialize` so its state
+/// can be saved (e.g. into a campaign's resume checkpoint) and restored
+/// later to continue the exact same sequence.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RandomGenerator {
+    state: [u64; 4],
+}
+
+impl RandomGenerator {
+    /// Seeds a fresh generator. Two generators created `from_seed` with the
+    /// same `seed` produce the exact same sequence of [`next_u64`] outputs,
+    /// on any platform.
+    ///
+    /// [`next_u64`]: RandomGenerator::next_u64
+    pub fn from_seed(seed: u64) -> Self {
+        let mut splitmix_state = seed;
+        let mut next_splitmix64 = move || {
+            splitmix_state = splitmix_state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = splitmix_state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        Self { state: [next_splitmix64(), next_splitmix64(), next_splitmix64(), next_splitmix64()] }
+    }
+
+    /// This generator's current state, to save and later pass to
+    /// [`RandomGenerator::from_state`] to resume exactly where it left off.
+    pub fn state(&self) -> [u64; 4] {
+        self.state
+    }
+
+    /// Restores a generator previously saved via [`RandomGenerator::state`].
+    pub fn from_state(state: [u64; 4]) -> Self {
+        Self { state }
+    }
+
+    /// The next pseudo-random `u64` in the sequence, advancing the state.
+    pub fn next_u64(&mut self) -> u64 {
+        let result = rotl(self.state[1].wrapping_mul(5), 7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = rotl(self.state[3], 45);
+        result
+    }
+
+    /// A pseudo-random value uniformly distributed over `0..bound`, or `0`
+    /// if `bound` is `0`. Uses Lemire's rejection-free-in-expectation
+    /// method (widening to 128 bits) rather than `% bound`, which is
+    /// measurably biased toward small results whenever `bound` doesn't
+    /// evenly divide `2^64`.
+    pub fn next_below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            return 0;
+        }
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+fn rotl(x: u64, k: u32) -> u64 {
+    x.rotate_left(k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_seed_reproduces_the_same_sequence() {
+        let mut a = RandomGenerator::from_seed(0x5eed);
+        let mut b = RandomGenerator::from_seed(0x5eed);
+        let sequence_a: Vec<u64> = (0..16).map(|_| a.next_u64()).collect();
+        let sequence_b: Vec<u64> = (0..16).map(|_| b.next_u64()).collect();
+        assert_eq!(sequence_a, sequence_b);
+    }
+
+    #[test]
+    fn different_seeds_produce_different_sequences() {
+        let mut a = RandomGenerator::from_seed(1);
+        let mut b = RandomGenerator::from_seed(2);
+        assert_ne!(a.next_u64(), b.next_u64());
+    }
+
+    #[test]
+    fn saving_and_restoring_state_resumes_the_exact_same_sequence() {
+        let mut generator = RandomGenerator::from_seed(0x1234_5678);
+        for _ in 0..7 {
+            generator.next_u64();
+        }
+        let checkpoint = generator.state();
+
+        let continued: Vec<u64> = (0..5).map(|_| generator.next_u64()).collect();
+
+        let mut resumed = RandomGenerator::from_state(checkpoint);
+        let resumed_sequence: Vec<u64> = (0..5).map(|_| resumed.next_u64()).collect();
+
+        assert_eq!(continued, resumed_sequence);
+    }
+
+    #[test]
+    fn state_round_trips_through_serde_json() {
+        let generator = RandomGenerator::from_seed(42);
+        let json = serde_json::to_string(&generator).unwrap();
+        let restored: RandomGenerator = serde_json::from_str(&json).unwrap();
+        assert_eq!(generator, restored);
+    }
+
+    #[test]
+    fn next_below_never_reaches_the_bound() {
+        let mut generator = RandomGenerator::from_seed(7);
+        for _ in 0..1000 {
+            assert!(generator.next_below(10) < 10);
+        }
+    }
+
+    #[test]
+    fn next_below_zero_is_always_zero() {
+        let mut generator = RandomGenerator::from_seed(7);
+        assert_eq!(generator.next_below(0), 0);
+    }
+}
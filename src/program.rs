@@ -0,0 +1,155 @@
+//! A builder for straight-line instruction programs with symbolic
+//! register wiring, evaluated by the software model or assembled and run
+//! natively through [`crate::native::jit`].
+//!
+//! This generalizes what [`crate::sequence::Sequence`] hand-rolls for
+//! carry chains and hazard pairs: instead of only being able to thread
+//! `xer` and (for [`crate::sequence::Sequence::hazard_pair`]) one `rt`
+//! into the very next step's `ra`, a [`Program`]'s [`Op`]s can read any
+//! earlier op's result by name.
+
+use crate::instr::Instr;
+use crate::model;
+use crate::types::{InstructionInput, InstructionOutput, Xer};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A symbolic register name, scoped to one [`Program`]. Resolved to a
+/// concrete value by [`Program::run_model`], or to a physical GPR by
+/// [`crate::native::jit::run_program`], rather than naming a real register
+/// up front.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Reg(pub u32);
+
+/// One operand of an [`Op`]: either a fixed value, or the value last
+/// written to a [`Reg`] by an earlier `Op` in the same [`Program`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operand {
+    Literal(u64),
+    Reg(Reg),
+}
+
+impl From<u64> for Operand {
+    fn from(value: u64) -> Self {
+        Operand::Literal(value)
+    }
+}
+
+impl From<Reg> for Operand {
+    fn from(reg: Reg) -> Self {
+        Operand::Reg(reg)
+    }
+}
+
+/// One instruction in a [`Program`]: `instr(ra, rb)`, with its result
+/// bound to `rt` for later `Op`s to read.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Op {
+    pub instr: Instr,
+    pub ra: Operand,
+    pub rb: Operand,
+    pub rt: Reg,
+}
+
+/// A straight-line sequence of [`Op`]s wired together by [`Reg`]s instead
+/// of literal operands.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Program {
+    pub ops: Vec<Op>,
+}
+
+impl Program {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `instr(ra, rb)`, binding its result to `rt`, and returns
+    /// `self` so ops can be chained: `Program::new().push(...).push(...)`.
+    pub fn push(mut self, instr: Instr, ra: impl Into<Operand>, rb: impl Into<Operand>, rt: Reg) -> Self {
+        self.ops.push(Op { instr, ra: ra.into(), rb: rb.into(), rt });
+        self
+    }
+
+    /// Interprets every `Op` through [`model::model`], threading `xer`
+    /// from one op to the next (the same way [`crate::sequence::run_model`]
+    /// does) and resolving [`Operand::Reg`] operands against the results
+    /// written by earlier ops.
+    pub fn run_model(&self) -> Result<ProgramResult, UnboundReg> {
+        let mut registers = HashMap::new();
+        let mut xer = Xer::default();
+        let mut op_outputs = Vec::with_capacity(self.ops.len());
+        for op in &self.ops {
+            let ra = resolve(op.ra, &registers)?;
+            let rb = resolve(op.rb, &registers)?;
+            let input = InstructionInput { ra, rb, xer, ..InstructionInput::default() };
+            let output = model::model(op.instr, input);
+            xer = output.xer.unwrap_or(xer);
+            if let Some(rt) = output.rt {
+                registers.insert(op.rt, rt);
+            }
+            op_outputs.push(output);
+        }
+        Ok(ProgramResult { op_outputs, registers, final_xer: xer })
+    }
+}
+
+fn resolve(operand: Operand, registers: &HashMap<Reg, u64>) -> Result<u64, UnboundReg> {
+    match operand {
+        Operand::Literal(value) => Ok(value),
+        Operand::Reg(reg) => registers.get(&reg).copied().ok_or(UnboundReg(reg)),
+    }
+}
+
+/// Returned by [`Program::run_model`] (and consulted by
+/// [`crate::native::jit::run_program`]) when an `Op` reads a [`Reg`] no
+/// earlier `Op` in the same [`Program`] has written.
+#[derive(Debug, Eq, PartialEq)]
+pub struct UnboundReg(pub Reg);
+
+impl fmt::Display for UnboundReg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} read before any earlier op in the program wrote to it", self.0)
+    }
+}
+
+impl std::error::Error for UnboundReg {}
+
+/// The outcome of running every `Op` in a [`Program`] through the model:
+/// each op's individual output, the final value of every written [`Reg`],
+/// and the `xer` left behind after the last op.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ProgramResult {
+    pub op_outputs: Vec<InstructionOutput>,
+    pub registers: HashMap<Reg, u64>,
+    pub final_xer: Xer,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wires_one_ops_result_into_a_later_ops_operand() {
+        // r0 = add(3, 4) = 7; r1 = add(r0, 10) = 17.
+        let program = Program::new().push(Instr::Add, 3, 4, Reg(0)).push(Instr::Add, Reg(0), 10, Reg(1));
+        let result = program.run_model().unwrap();
+        assert_eq!(result.registers[&Reg(1)], 17);
+    }
+
+    #[test]
+    fn threads_carry_the_same_way_sequence_does() {
+        // addc(u64::MAX, 1) carries out; the following adde must see it.
+        let program =
+            Program::new().push(Instr::AddC, u64::MAX, 1, Reg(0)).push(Instr::AddE, 0u64, 0u64, Reg(1));
+        let result = program.run_model().unwrap();
+        assert_eq!(result.registers[&Reg(0)], 0);
+        assert_eq!(result.registers[&Reg(1)], 1);
+        assert!(!result.final_xer.ca);
+    }
+
+    #[test]
+    fn reading_an_unwritten_reg_is_reported_rather_than_defaulting_to_zero() {
+        let program = Program::new().push(Instr::Add, Reg(9), 1u64, Reg(0));
+        assert_eq!(program.run_model(), Err(UnboundReg(Reg(9))));
+    }
+}
@@ -0,0 +1,104 @@
+//! Runtime registry for out-of-tree / experimental instructions, so
+//! Libre-SOC proposals (e.g. bitmanip experiments) can be analyzed without
+//! forking this crate to add an [`Instr`](crate::instr::Instr) variant.
+//! Registrations are process-global, so every front-end built on this
+//! crate (the CLI, and eventually other language bindings) sees the same
+//! set transparently.
+
+use crate::types::{InstructionInput, InstructionOutput};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// A model closure for a registered instruction: computes outputs from
+/// inputs, exactly like the built-in per-instruction functions in
+/// [`crate::model`].
+pub type ModelFn = dyn Fn(InstructionInput) -> InstructionOutput + Send + Sync;
+
+/// An optional encoder closure for a registered instruction, mirroring
+/// [`crate::encoder::encode`]'s `(rt, ra, rb) -> word` shape.
+pub type EncodeFn = dyn Fn(u32, u32, u32) -> u32 + Send + Sync;
+
+/// A single out-of-tree instruction: its name, the operands it reads (for
+/// display purposes), a model, and optionally how to encode it.
+#[derive(Clone)]
+pub struct CustomInstr {
+    pub name: String,
+    pub operands: Vec<String>,
+    pub model: Arc<ModelFn>,
+    pub encode: Option<Arc<EncodeFn>>,
+}
+
+impl fmt::Debug for CustomInstr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomInstr")
+            .field("name", &self.name)
+            .field("operands", &self.operands)
+            .field("has_encoder", &self.encode.is_some())
+            .finish()
+    }
+}
+
+fn registry() -> &'static Mutex<HashMap<String, CustomInstr>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, CustomInstr>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Registers `instr` under its name, replacing any previous registration
+/// with the same name.
+pub fn register(instr: CustomInstr) {
+    registry().lock().unwrap().insert(instr.name.clone(), instr);
+}
+
+/// Removes a previously registered instruction, if any.
+pub fn unregister(name: &str) {
+    registry().lock().unwrap().remove(name);
+}
+
+/// Looks up a previously registered instruction by name.
+pub fn lookup(name: &str) -> Option<CustomInstr> {
+    registry().lock().unwrap().get(name).cloned()
+}
+
+/// Lists the names of every registered instruction, sorted for stable
+/// output.
+pub fn list() -> Vec<String> {
+    let mut names: Vec<String> = registry().lock().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Runs a registered instruction's model against `input`, for callers that
+/// don't already have a [`CustomInstr`] in hand.
+pub fn run(name: &str, input: InstructionInput) -> Option<InstructionOutput> {
+    lookup(name).map(|instr| (instr.model)(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers_and_runs_a_custom_instruction() {
+        register(CustomInstr {
+            name: "test_xor".to_string(),
+            operands: vec!["ra".to_string(), "rb".to_string()],
+            model: Arc::new(|input| InstructionOutput {
+                rt: Some(input.ra ^ input.rb),
+                ..InstructionOutput::default()
+            }),
+            encode: None,
+        });
+
+        let output = run(
+            "test_xor",
+            InstructionInput { ra: 0b1010, rb: 0b0110, ..InstructionInput::default() },
+        )
+        .unwrap();
+        assert_eq!(output.rt, Some(0b1100));
+        assert!(list().contains(&"test_xor".to_string()));
+
+        unregister("test_xor");
+        assert!(run("test_xor", InstructionInput::default()).is_none());
+    }
+}
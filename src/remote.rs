@@ -0,0 +1,199 @@
+//! Client/server support for running native test cases on a remote POWER
+//! host over the network, for developers whose workstation isn't itself a
+//! POWER machine.
+//!
+//! The wire protocol is newline-delimited JSON: the client writes a
+//! [`Request`] and the server writes back exactly one [`Response`].
+
+use crate::cache::{Cache, CacheKey};
+use crate::capture::{TestCase, WholeTest};
+use crate::instr::Instr;
+use crate::metrics::CampaignMetrics;
+use crate::native;
+use crate::types::{InstructionInput, InstructionOutput};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+#[derive(Serialize, Deserialize)]
+struct Request {
+    instr: Instr,
+    input: InstructionInput,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Response {
+    Ok(InstructionOutput),
+    Err(String),
+}
+
+/// Runs a `pia serve` endpoint on `addr`, handling one connection at a time.
+pub fn serve(addr: impl ToSocketAddrs) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    for stream in listener.incoming() {
+        handle_connection(stream?)?;
+    }
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let reader = BufReader::new(stream);
+    for line in reader.lines() {
+        let line = line?;
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(err) => {
+                writeln!(writer, "{}", serde_json::to_string(&Response::Err(err.to_string()))?)?;
+                continue;
+            }
+        };
+        let response = match native::execute(request.instr, request.input) {
+            Ok(output) => Response::Ok(output),
+            Err(err) => Response::Err(err.to_string()),
+        };
+        writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+    }
+    Ok(())
+}
+
+/// A connection to a single `pia serve` endpoint.
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    pub fn execute(&mut self, instr: Instr, input: InstructionInput) -> io::Result<InstructionOutput> {
+        let request = Request { instr, input };
+        writeln!(self.writer, "{}", serde_json::to_string(&request)?)?;
+        let mut line = String::new();
+        self.reader.read_line(&mut line)?;
+        let response: Response = serde_json::from_str(&line)?;
+        match response {
+            Response::Ok(output) => Ok(output),
+            Response::Err(message) => Err(io::Error::other(message)),
+        }
+    }
+}
+
+/// A pool of remote `pia serve` endpoints that test cases can be sharded
+/// across, to parallelize exhaustive campaigns over several POWER hosts.
+pub struct Farm {
+    addrs: Vec<String>,
+}
+
+impl Farm {
+    pub fn new(addrs: Vec<String>) -> Self {
+        Self { addrs }
+    }
+
+    /// Runs `cases` (pairs of instruction and model input) against the
+    /// farm, round-robining work across all configured hosts and merging
+    /// the results (in the order they complete) into one [`WholeTest`].
+    ///
+    /// A host that fails to connect or errors out is reported but does not
+    /// abort the other hosts' shares of the work. Cases already present in
+    /// `cache` (keyed by instruction, inputs, and the serving host) are not
+    /// re-executed; newly executed cases are added to it.
+    ///
+    /// If `metrics` is given, it's updated with one [`CampaignMetrics::record_case`]
+    /// per completed case (from whichever worker thread completes it), so a
+    /// `pia farm --metrics-addr` endpoint reflects live progress.
+    pub fn run_sharded(&self, cases: Vec<(Instr, InstructionInput)>, cache: Cache, metrics: Option<&Arc<CampaignMetrics>>) -> (WholeTest, Cache) {
+        let num_hosts = self.addrs.len().max(1);
+        let mut shards: Vec<Vec<(Instr, InstructionInput)>> = (0..num_hosts).map(|_| Vec::new()).collect();
+        for (index, case) in cases.into_iter().enumerate() {
+            shards[index % num_hosts].push(case);
+        }
+
+        let cache = Arc::new(Mutex::new(cache));
+        let (tx, rx) = mpsc::channel();
+        let mut handles = Vec::new();
+        for (addr, shard) in self.addrs.iter().cloned().zip(shards) {
+            let tx = tx.clone();
+            let cache = Arc::clone(&cache);
+            let metrics = metrics.cloned();
+            handles.push(thread::spawn(move || {
+                tx.send(run_shard(&addr, shard, &cache, metrics.as_ref())).expect("farm result channel closed");
+            }));
+        }
+        drop(tx);
+
+        let mut test_cases = Vec::new();
+        for result in rx {
+            test_cases.extend(result);
+        }
+        for handle in handles {
+            let _ = handle.join();
+        }
+        let cache = Arc::try_unwrap(cache)
+            .unwrap_or_else(|_| panic!("farm worker thread still holds the cache"))
+            .into_inner()
+            .expect("cache mutex poisoned");
+        (WholeTest { test_cases, pinning: None, host_endianness: None, host_info: None }, cache)
+    }
+}
+
+fn run_shard(addr: &str, shard: Vec<(Instr, InstructionInput)>, cache: &Mutex<Cache>, metrics: Option<&Arc<CampaignMetrics>>) -> Vec<TestCase> {
+    let mut client = None;
+    let mut results = Vec::with_capacity(shard.len());
+    for (instr, input) in shard {
+        let key = CacheKey {
+            instr,
+            input,
+            backend: addr.to_string(),
+            cpu_model: "unknown".to_string(),
+        };
+        let cached = cache.lock().expect("cache mutex poisoned").get(&key);
+        let native_output = match cached {
+            Some(native_output) => native_output,
+            None => {
+                let client = match &mut client {
+                    Some(client) => client,
+                    None => match Client::connect(addr) {
+                        Ok(connected) => client.insert(connected),
+                        Err(err) => {
+                            eprintln!("farm: failed to connect to {}: {}", addr, err);
+                            return results;
+                        }
+                    },
+                };
+                match client.execute(instr, input) {
+                    Ok(native_output) => {
+                        cache.lock().expect("cache mutex poisoned").insert(key, native_output);
+                        native_output
+                    }
+                    Err(err) => {
+                        eprintln!("farm: {} failed on {}: {}", addr, instr, err);
+                        continue;
+                    }
+                }
+            }
+        };
+        let model_output = crate::model::model(instr, input);
+        if let Some(metrics) = metrics {
+            metrics.record_case(!native_output.diff(&model_output).is_empty());
+        }
+        results.push(TestCase {
+            instr,
+            input,
+            native_output,
+            model_output,
+            model_revision: crate::metadata::model_revision(instr),
+            skip: None,
+            latency: None,
+        });
+    }
+    results
+}
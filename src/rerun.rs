@@ -0,0 +1,114 @@
+//! Re-executes a golden capture's mismatching cases ([`WholeTest::mismatches`])
+//! N times on the native backend, to tell a genuine model/native divergence
+//! (every run agrees on the same disagreeing output) apart from flakiness
+//! (native output varies across runs, pointing at a generator/asm bug or
+//! environmental interference rather than a model bug).
+
+use crate::capture::{TestCase, WholeTest};
+use crate::native;
+use crate::types::InstructionOutput;
+
+/// `repeat_count` native re-executions of one mismatching [`TestCase`].
+#[derive(Debug)]
+pub struct RerunResult {
+    pub case: TestCase,
+    pub outputs: Vec<Result<InstructionOutput, native::Error>>,
+}
+
+impl RerunResult {
+    /// Whether every successful re-execution produced the same output.
+    /// [`native::Error`]s (the case simply couldn't be run) are ignored
+    /// here -- they're a native-execution problem, not flakiness.
+    pub fn is_deterministic(&self) -> bool {
+        native::outputs_agree(&self.outputs)
+    }
+}
+
+/// Re-executes every mismatching case in `golden` `repeat_count` times on
+/// the native backend.
+pub fn rerun_mismatches(golden: &WholeTest, repeat_count: usize) -> Vec<RerunResult> {
+    golden
+        .mismatches()
+        .map(|case| RerunResult {
+            case: case.clone(),
+            outputs: native::execute_repeated(case.instr, case.input, repeat_count),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instr::Instr;
+    use crate::types::InstructionInput;
+
+    fn mismatching_case() -> TestCase {
+        TestCase {
+            instr: Instr::Add,
+            input: InstructionInput { ra: 1, rb: 2, ..InstructionInput::default() },
+            native_output: InstructionOutput { rt: Some(1), ..InstructionOutput::default() },
+            model_output: InstructionOutput { rt: Some(3), ..InstructionOutput::default() },
+            model_revision: 1,
+            skip: None, latency: None,
+        }
+    }
+
+    #[test]
+    fn only_mismatching_cases_are_rerun() {
+        let golden = WholeTest {
+            test_cases: vec![
+                mismatching_case(),
+                TestCase {
+                    instr: Instr::Subf,
+                    input: InstructionInput::default(),
+                    native_output: InstructionOutput::default(),
+                    model_output: InstructionOutput::default(),
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        let results = rerun_mismatches(&golden, 3);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].outputs.len(), 3);
+    }
+
+    #[test]
+    fn agreeing_outputs_are_reported_deterministic() {
+        let result = RerunResult {
+            case: mismatching_case(),
+            outputs: vec![
+                Ok(InstructionOutput { rt: Some(1), ..InstructionOutput::default() }),
+                Ok(InstructionOutput { rt: Some(1), ..InstructionOutput::default() }),
+            ],
+        };
+        assert!(result.is_deterministic());
+    }
+
+    #[test]
+    fn disagreeing_outputs_are_reported_flaky() {
+        let result = RerunResult {
+            case: mismatching_case(),
+            outputs: vec![
+                Ok(InstructionOutput { rt: Some(1), ..InstructionOutput::default() }),
+                Ok(InstructionOutput { rt: Some(2), ..InstructionOutput::default() }),
+            ],
+        };
+        assert!(!result.is_deterministic());
+    }
+
+    #[test]
+    fn execution_errors_are_not_counted_as_flakiness() {
+        let result = RerunResult {
+            case: mismatching_case(),
+            outputs: vec![
+                Ok(InstructionOutput { rt: Some(1), ..InstructionOutput::default() }),
+                Err(native::Error::UnsupportedPlatform),
+            ],
+        };
+        assert!(result.is_deterministic());
+    }
+}
@@ -0,0 +1,252 @@
+//! Multi-instruction carry-propagation sequences, and an interpreter that
+//! threads each step's [`Xer`] into the next -- modeling the
+//! `addc`/`adde`/`adde`/... chains SVP64 bigint code emits to propagate
+//! carry across more limbs than a single instruction can reach.
+
+use crate::instr::Instr;
+use crate::model;
+use crate::native;
+use crate::types::{InstructionInput, InstructionOutput, Xer};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How the "summary overflow" bit is seeded at the start of a sequence run.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum SoMode {
+    /// Each run starts with `xer.so = false`, as if every case's SO input
+    /// were supplied fresh. The default.
+    PerCaseInput = 0,
+    /// SO persists across runs on the same thread, mirroring real hardware
+    /// where SO is sticky until software explicitly clears it -- matching
+    /// how Libre-SOC's own test harness wants to model XER.
+    StickyPerThread = 1,
+}
+
+static SO_MODE: AtomicU8 = AtomicU8::new(SoMode::PerCaseInput as u8);
+
+thread_local! {
+    /// The sticky SO bit tracked per-thread under [`SoMode::StickyPerThread`].
+    static STICKY_SO: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Sets the process-wide [`SoMode`] used by every subsequent [`run_model`]/
+/// [`run_native`] call on any thread. Each thread tracks its own sticky SO
+/// bit independently, so switching modes on one thread doesn't reset
+/// another thread's accumulated state.
+pub fn set_so_mode(mode: SoMode) {
+    SO_MODE.store(mode as u8, Ordering::SeqCst);
+}
+
+/// The currently configured [`SoMode`].
+pub fn so_mode() -> SoMode {
+    match SO_MODE.load(Ordering::SeqCst) {
+        1 => SoMode::StickyPerThread,
+        _ => SoMode::PerCaseInput,
+    }
+}
+
+fn initial_xer() -> Xer {
+    let so = match so_mode() {
+        SoMode::PerCaseInput => false,
+        SoMode::StickyPerThread => STICKY_SO.with(Cell::get),
+    };
+    Xer { so, ..Xer::default() }
+}
+
+fn record_final_so(xer: Xer) {
+    if so_mode() == SoMode::StickyPerThread {
+        STICKY_SO.with(|cell| cell.set(xer.so));
+    }
+}
+
+/// One step of a sequence: the instruction to run and the `ra`/`rb`
+/// operands it reads. Everything else, notably `xer.ca`, is threaded in
+/// from the previous step by [`run_model`]/[`run_native`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Step {
+    pub instr: Instr,
+    pub ra: u64,
+    pub rb: u64,
+    /// When set, `ra` above is ignored and this step's `ra` is instead fed
+    /// from the previous step's `rt`, so a sequence can build a genuine
+    /// register-dependency hazard (the kind a single-instruction test can't
+    /// see) instead of just chaining literal operands. Unused on the first
+    /// step, since there's no previous `rt` to forward.
+    pub ra_from_previous_rt: bool,
+}
+
+/// A sequence of instructions meant to be run back-to-back with `xer`
+/// threaded between them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Sequence {
+    pub steps: Vec<Step>,
+}
+
+impl Sequence {
+    /// Builds a carry-chain sequence `length` limbs long: an `addc` to
+    /// start the chain, followed by `length - 1` `adde`s to propagate the
+    /// carry across the remaining limbs. Operands vary per step (rather
+    /// than repeating the same pair) so the chain actually exercises
+    /// carry-out/carry-in at every step instead of trivially saturating.
+    pub fn carry_chain(length: usize) -> Self {
+        let steps = (0..length)
+            .map(|i| Step {
+                instr: if i == 0 { Instr::AddC } else { Instr::AddE },
+                ra: u64::MAX - i as u64,
+                rb: 1 + i as u64,
+                ra_from_previous_rt: false,
+            })
+            .collect();
+        Self { steps }
+    }
+
+    /// Builds a two-step sequence where `second`'s `ra` is fed from
+    /// `first`'s `rt`, for catching flag-forwarding bugs that running
+    /// `first` and `second` in isolation can't see (e.g. a model that
+    /// forgets to re-derive `cr0`/`xer` from a value that only became
+    /// available through the previous step's result).
+    pub fn hazard_pair(first: Instr, first_ra: u64, first_rb: u64, second: Instr, second_rb: u64) -> Self {
+        Self {
+            steps: vec![
+                Step { instr: first, ra: first_ra, rb: first_rb, ra_from_previous_rt: false },
+                Step { instr: second, ra: 0, rb: second_rb, ra_from_previous_rt: true },
+            ],
+        }
+    }
+}
+
+/// The outcome of running every step of a [`Sequence`]: each step's
+/// individual output, for diagnosing exactly where a mismatch first
+/// appears, plus the `xer` left behind after the last step.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SequenceResult {
+    pub step_outputs: Vec<InstructionOutput>,
+    pub final_xer: Xer,
+}
+
+/// Runs `sequence` through the software model, threading each step's `xer`
+/// (in particular `ca`) into the next step's input.
+pub fn run_model(sequence: &Sequence) -> SequenceResult {
+    run(sequence, model::model)
+}
+
+/// Runs `sequence` natively, one instruction at a time, threading `xer`
+/// between steps the same way [`run_model`] does. Fails as soon as any
+/// step fails to execute (e.g. [`native::Error::UnsupportedPlatform`]).
+pub fn run_native(sequence: &Sequence) -> Result<SequenceResult, native::Error> {
+    let mut xer = initial_xer();
+    let mut previous_rt = None;
+    let mut step_outputs = Vec::with_capacity(sequence.steps.len());
+    for step in &sequence.steps {
+        let ra = ra_for_step(step, previous_rt);
+        let input = InstructionInput { ra, rb: step.rb, xer, ..InstructionInput::default() };
+        let output = native::execute(step.instr, input)?;
+        xer = output.xer.unwrap_or(xer);
+        previous_rt = output.rt.or(previous_rt);
+        step_outputs.push(output);
+    }
+    record_final_so(xer);
+    Ok(SequenceResult { step_outputs, final_xer: xer })
+}
+
+fn run(sequence: &Sequence, mut step: impl FnMut(Instr, InstructionInput) -> InstructionOutput) -> SequenceResult {
+    let mut xer = initial_xer();
+    let mut previous_rt = None;
+    let mut step_outputs = Vec::with_capacity(sequence.steps.len());
+    for s in &sequence.steps {
+        let ra = ra_for_step(s, previous_rt);
+        let input = InstructionInput { ra, rb: s.rb, xer, ..InstructionInput::default() };
+        let output = step(s.instr, input);
+        xer = output.xer.unwrap_or(xer);
+        previous_rt = output.rt.or(previous_rt);
+        step_outputs.push(output);
+    }
+    record_final_so(xer);
+    SequenceResult { step_outputs, final_xer: xer }
+}
+
+/// Resolves a step's `ra`, forwarding the previous step's `rt` in place of
+/// the literal field when [`Step::ra_from_previous_rt`] is set.
+fn ra_for_step(step: &Step, previous_rt: Option<u64>) -> u64 {
+    if step.ra_from_previous_rt {
+        previous_rt.expect("ra_from_previous_rt on a step with no previous step to forward rt from")
+    } else {
+        step.ra
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn carry_chain_starts_with_addc_and_continues_with_adde() {
+        let sequence = Sequence::carry_chain(3);
+        assert_eq!(sequence.steps[0].instr, Instr::AddC);
+        assert_eq!(sequence.steps[1].instr, Instr::AddE);
+        assert_eq!(sequence.steps[2].instr, Instr::AddE);
+    }
+
+    #[test]
+    fn run_model_threads_carry_out_of_addc_into_the_first_adde() {
+        // addc ra=u64::MAX, rb=1 carries out (sum wraps to 0, ca=1); the
+        // following adde must see that carry as its own input.
+        let sequence = Sequence {
+            steps: vec![
+                Step { instr: Instr::AddC, ra: u64::MAX, rb: 1, ra_from_previous_rt: false },
+                Step { instr: Instr::AddE, ra: 0, rb: 0, ra_from_previous_rt: false },
+            ],
+        };
+        let result = run_model(&sequence);
+        assert_eq!(result.step_outputs[0].rt, Some(0));
+        assert!(result.step_outputs[0].xer.unwrap().ca);
+        // adde(0, 0, ca=1) == 1, with no carry out this time.
+        assert_eq!(result.step_outputs[1].rt, Some(1));
+        assert!(!result.final_xer.ca);
+    }
+
+    #[test]
+    fn hazard_pair_feeds_the_first_steps_rt_into_the_seconds_ra() {
+        // add(3, 4) == 7, then add(7, 10) == 17: the second step's ra must
+        // come from the first step's rt, not the literal 0 placeholder.
+        let sequence = Sequence::hazard_pair(Instr::Add, 3, 4, Instr::Add, 10);
+        let result = run_model(&sequence);
+        assert_eq!(result.step_outputs[0].rt, Some(7));
+        assert_eq!(result.step_outputs[1].rt, Some(17));
+    }
+
+    /// `addo` of `i64::MAX + 1` overflows, setting SO (and OV); a later
+    /// no-overflow `addo` leaves SO untouched in real POWER hardware, so
+    /// this exercises whether that later run starts from the earlier run's
+    /// SO (sticky) or ignores it (per-case).
+    fn overflowing_then_clean_addo() -> Sequence {
+        Sequence {
+            steps: vec![Step { instr: Instr::AddO, ra: i64::MAX as u64, rb: 1, ra_from_previous_rt: false }],
+        }
+    }
+
+    fn clean_addo() -> Sequence {
+        Sequence { steps: vec![Step { instr: Instr::AddO, ra: 1, rb: 1, ra_from_previous_rt: false }] }
+    }
+
+    // One test, not two, since `SoMode` is process-global state: running
+    // both modes as separate #[test]s would race against each other under
+    // the test harness's default multithreading.
+    #[test]
+    fn so_mode_controls_whether_so_carries_across_runs() {
+        set_so_mode(SoMode::PerCaseInput);
+        let overflowed = run_model(&overflowing_then_clean_addo());
+        assert!(overflowed.final_xer.so);
+        let clean = run_model(&clean_addo());
+        assert!(!clean.final_xer.so, "each run starts with a fresh xer.so under PerCaseInput");
+
+        set_so_mode(SoMode::StickyPerThread);
+        let overflowed = run_model(&overflowing_then_clean_addo());
+        assert!(overflowed.final_xer.so);
+        let clean = run_model(&clean_addo());
+        assert!(clean.final_xer.so, "SO should stay set until explicitly cleared under StickyPerThread");
+
+        set_so_mode(SoMode::PerCaseInput);
+    }
+}
@@ -0,0 +1,104 @@
+//! Brute-force input search: given a predicate over an instruction's
+//! output, finds an input satisfying it without solving a constraint
+//! system -- this crate has no SMT solver dependency ([`crate::expr`]'s
+//! `to_smt_lib` is a text exporter, not a binding to one), so rather than
+//! add one just for this, [`find_input`] searches the same systematic
+//! candidate set [`crate::campaign::exhaustive_cases`] already generates
+//! for `pia farm`. That set is necessarily incomplete -- it's four operand
+//! pairs crossed with the flags an instruction reads, not the full input
+//! space -- so [`find_input`] returning `None` means "not found among
+//! those candidates", not "no such input exists".
+
+use crate::campaign;
+use crate::instr::Instr;
+use crate::model;
+use crate::types::{ConditionRegister, Fpscr, InstructionInput, InstructionOutput, Xer};
+
+/// Searches [`campaign::exhaustive_cases`]'s candidates for `instr`,
+/// returning the first input whose model output satisfies `predicate` --
+/// e.g. `find_input(Instr::AddC, |out| out.xer.unwrap().ca)` to get a case
+/// that carries out.
+pub fn find_input(instr: Instr, predicate: impl Fn(&InstructionOutput) -> bool) -> Option<InstructionInput> {
+    campaign::exhaustive_cases(instr).into_iter().find(|&(_, input)| predicate(&model::model(instr, input))).map(
+        |(_, input)| input,
+    )
+}
+
+/// An output as observed during trace analysis, where not every field is
+/// known -- e.g. only `rt` was captured, or only a flag. Unlike
+/// [`InstructionOutput`]'s `None` (meaning "this instruction doesn't write
+/// this field"), `None` here means "unknown", a wildcard that matches any
+/// value when [`PartialOutput::matches`] checks a candidate.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PartialOutput {
+    pub rt: Option<u64>,
+    pub cr0: Option<ConditionRegister>,
+    pub xer: Option<Xer>,
+    pub fpscr: Option<Fpscr>,
+}
+
+impl PartialOutput {
+    /// Whether every field set on `self` agrees with `output`'s
+    /// corresponding field; unset fields always match.
+    pub fn matches(&self, output: &InstructionOutput) -> bool {
+        field_matches(self.rt, output.rt)
+            && field_matches(self.cr0, output.cr0)
+            && field_matches(self.xer, output.xer)
+            && field_matches(self.fpscr, output.fpscr)
+    }
+}
+
+fn field_matches<T: PartialEq>(observed: Option<T>, got: Option<T>) -> bool {
+    match observed {
+        None => true,
+        Some(observed) => got == Some(observed),
+    }
+}
+
+/// Reverse lookup for "where did this register value come from": searches
+/// [`campaign::exhaustive_cases`]'s candidates for `instr`, returning every
+/// input whose model output [`PartialOutput::matches`] `observed` -- e.g.
+/// to see which candidate `ra`/`rb`/flag combinations could have produced
+/// an `rt` value seen in a trace.
+pub fn find_all_inputs(instr: Instr, observed: &PartialOutput) -> Vec<InstructionInput> {
+    campaign::exhaustive_cases(instr)
+        .into_iter()
+        .filter(|&(_, input)| observed.matches(&model::model(instr, input)))
+        .map(|(_, input)| input)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_an_addc_case_that_carries_out() {
+        let input = find_input(Instr::AddC, |out| out.xer.unwrap().ca).unwrap();
+        let output = model::model(Instr::AddC, input);
+        assert!(output.xer.unwrap().ca);
+    }
+
+    #[test]
+    fn returns_none_when_no_candidate_satisfies_the_predicate() {
+        // `add` never sets cr0 (it's not an Rc-form), so no candidate can
+        // satisfy a predicate that requires it.
+        assert_eq!(find_input(Instr::Add, |out| out.cr0.is_some()), None);
+    }
+
+    #[test]
+    fn find_all_inputs_matches_only_the_observed_rt_and_ignores_unset_fields() {
+        // add(1, 1) == 2, and it's the only candidate pair that does.
+        let observed = PartialOutput { rt: Some(2), ..PartialOutput::default() };
+        let matches = find_all_inputs(Instr::Add, &observed);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].ra, 1);
+        assert_eq!(matches[0].rb, 1);
+    }
+
+    #[test]
+    fn find_all_inputs_returns_every_candidate_when_nothing_is_observed() {
+        let all = find_all_inputs(Instr::Add, &PartialOutput::default());
+        assert_eq!(all.len(), campaign::exhaustive_cases(Instr::Add).len());
+    }
+}
@@ -0,0 +1,437 @@
+//! Normalized SQLite export/import of a [`WholeTest`] capture (`pia
+//! export-sqlite`/`pia import-sqlite`), so mismatch analysis ("rate by
+//! operand sign", "which fields diverge together", ...) on a large corpus
+//! is a SQL query instead of a one-off script, and a filtered/edited
+//! export can be imported back for replay.
+//!
+//! The schema has three tables: `instructions` (one row per distinct
+//! [`Instr`]), `cases` (one row per [`TestCase`]'s instruction and input),
+//! and `outputs` (one row per case per recorded output -- `native` and/or
+//! `model`, so a query can `GROUP BY source` or join the two against each
+//! other without a self-join). [`TestCase::latency`] is intentionally not
+//! exported: it's forensic-only (see [`crate::timing`]) and outside the
+//! `instructions`/`cases`/`outputs` shape this schema is built around.
+
+use crate::capture::{TestCase, WholeTest};
+use crate::instr::Instr;
+use crate::types::{Aliasing, ConditionRegister, Fpscr, InstructionInput, InstructionOutput, RoundingMode, Xer};
+use rusqlite::{params, Connection};
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum Error {
+    Sqlite(rusqlite::Error),
+    /// An imported row held a value this crate's types don't recognize in
+    /// `column`, e.g. an `aliasing` or `rounding_mode` text column written
+    /// by something other than [`export`].
+    UnrecognizedValue { column: &'static str, value: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Sqlite(err) => write!(f, "sqlite error: {}", err),
+            Error::UnrecognizedValue { column, value } => {
+                write!(f, "unrecognized value {:?} in column {}", value, column)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Sqlite(err)
+    }
+}
+
+fn aliasing_to_text(aliasing: Aliasing) -> &'static str {
+    match aliasing {
+        Aliasing::None => "none",
+        Aliasing::RaEqRb => "ra_eq_rb",
+        Aliasing::RtEqRa => "rt_eq_ra",
+        Aliasing::RtEqRaEqRb => "rt_eq_ra_eq_rb",
+    }
+}
+
+fn aliasing_from_text(text: &str) -> Result<Aliasing, Error> {
+    match text {
+        "none" => Ok(Aliasing::None),
+        "ra_eq_rb" => Ok(Aliasing::RaEqRb),
+        "rt_eq_ra" => Ok(Aliasing::RtEqRa),
+        "rt_eq_ra_eq_rb" => Ok(Aliasing::RtEqRaEqRb),
+        _ => Err(Error::UnrecognizedValue { column: "aliasing", value: text.to_string() }),
+    }
+}
+
+fn rounding_mode_to_text(rn: RoundingMode) -> &'static str {
+    match rn {
+        RoundingMode::Nearest => "nearest",
+        RoundingMode::TowardZero => "toward_zero",
+        RoundingMode::TowardPositiveInfinity => "toward_positive_infinity",
+        RoundingMode::TowardNegativeInfinity => "toward_negative_infinity",
+    }
+}
+
+fn rounding_mode_from_text(text: &str) -> Result<RoundingMode, Error> {
+    match text {
+        "nearest" => Ok(RoundingMode::Nearest),
+        "toward_zero" => Ok(RoundingMode::TowardZero),
+        "toward_positive_infinity" => Ok(RoundingMode::TowardPositiveInfinity),
+        "toward_negative_infinity" => Ok(RoundingMode::TowardNegativeInfinity),
+        _ => Err(Error::UnrecognizedValue { column: "rounding_mode", value: text.to_string() }),
+    }
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE instructions (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE cases (
+        id INTEGER PRIMARY KEY,
+        instr_id INTEGER NOT NULL REFERENCES instructions(id),
+        ra INTEGER NOT NULL,
+        rb INTEGER NOT NULL,
+        rc INTEGER NOT NULL,
+        aliasing TEXT NOT NULL,
+        input_cr0_lt INTEGER NOT NULL,
+        input_cr0_gt INTEGER NOT NULL,
+        input_cr0_eq INTEGER NOT NULL,
+        input_cr0_so INTEGER NOT NULL,
+        input_xer_so INTEGER NOT NULL,
+        input_xer_ov INTEGER NOT NULL,
+        input_xer_ca INTEGER NOT NULL,
+        input_xer_ov32 INTEGER NOT NULL,
+        input_xer_ca32 INTEGER NOT NULL,
+        input_fpscr_rn TEXT NOT NULL,
+        input_fpscr_ve INTEGER NOT NULL,
+        input_fpscr_oe INTEGER NOT NULL,
+        input_fpscr_ue INTEGER NOT NULL,
+        input_fpscr_ze INTEGER NOT NULL,
+        input_fpscr_xe INTEGER NOT NULL,
+        model_revision INTEGER NOT NULL
+    );
+    CREATE TABLE outputs (
+        case_id INTEGER NOT NULL REFERENCES cases(id),
+        source TEXT NOT NULL,
+        rt INTEGER,
+        cr0_lt INTEGER,
+        cr0_gt INTEGER,
+        cr0_eq INTEGER,
+        cr0_so INTEGER,
+        xer_so INTEGER,
+        xer_ov INTEGER,
+        xer_ca INTEGER,
+        xer_ov32 INTEGER,
+        xer_ca32 INTEGER,
+        raw_cr INTEGER,
+        fpscr_rn TEXT,
+        fpscr_ve INTEGER,
+        fpscr_oe INTEGER,
+        fpscr_ue INTEGER,
+        fpscr_ze INTEGER,
+        fpscr_xe INTEGER,
+        PRIMARY KEY (case_id, source)
+    );
+";
+
+/// Which recorded output an `outputs` row holds -- `pia export-sqlite`'s
+/// `source` column, and [`import`]'s key for re-assembling a [`TestCase`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Source {
+    Native,
+    Model,
+}
+
+impl Source {
+    fn as_text(self) -> &'static str {
+        match self {
+            Source::Native => "native",
+            Source::Model => "model",
+        }
+    }
+
+    fn from_text(text: &str) -> Result<Self, Error> {
+        match text {
+            "native" => Ok(Source::Native),
+            "model" => Ok(Source::Model),
+            _ => Err(Error::UnrecognizedValue { column: "source", value: text.to_string() }),
+        }
+    }
+}
+
+/// Creates `path` fresh (it must not already exist) and writes every case
+/// in `whole_test` into it under the schema documented on this module.
+pub fn export(whole_test: &WholeTest, path: &Path) -> Result<(), Error> {
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(SCHEMA)?;
+    let tx = conn.transaction()?;
+
+    let mut instr_ids = std::collections::BTreeMap::new();
+    for case in &whole_test.test_cases {
+        let next_id = instr_ids.len() as i64;
+        instr_ids.entry(case.instr).or_insert(next_id);
+    }
+    for (&instr, &id) in &instr_ids {
+        tx.execute("INSERT INTO instructions (id, name) VALUES (?1, ?2)", params![id, instr.name()])?;
+    }
+
+    for case in &whole_test.test_cases {
+        let input = &case.input;
+        tx.execute(
+            "INSERT INTO cases (
+                instr_id, ra, rb, rc, aliasing,
+                input_cr0_lt, input_cr0_gt, input_cr0_eq, input_cr0_so,
+                input_xer_so, input_xer_ov, input_xer_ca, input_xer_ov32, input_xer_ca32,
+                input_fpscr_rn, input_fpscr_ve, input_fpscr_oe, input_fpscr_ue, input_fpscr_ze, input_fpscr_xe,
+                model_revision
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)",
+            params![
+                instr_ids[&case.instr],
+                input.ra as i64,
+                input.rb as i64,
+                input.rc as i64,
+                aliasing_to_text(input.aliasing),
+                input.cr0.lt,
+                input.cr0.gt,
+                input.cr0.eq,
+                input.cr0.so,
+                input.xer.so,
+                input.xer.ov,
+                input.xer.ca,
+                input.xer.ov32,
+                input.xer.ca32,
+                rounding_mode_to_text(input.fpscr.rn),
+                input.fpscr.ve,
+                input.fpscr.oe,
+                input.fpscr.ue,
+                input.fpscr.ze,
+                input.fpscr.xe,
+                case.model_revision,
+            ],
+        )?;
+        let case_id = tx.last_insert_rowid();
+
+        for (source, output) in [(Source::Native, &case.native_output), (Source::Model, &case.model_output)] {
+            if output.is_empty() {
+                continue;
+            }
+            tx.execute(
+                "INSERT INTO outputs (
+                    case_id, source, rt, cr0_lt, cr0_gt, cr0_eq, cr0_so,
+                    xer_so, xer_ov, xer_ca, xer_ov32, xer_ca32, raw_cr,
+                    fpscr_rn, fpscr_ve, fpscr_oe, fpscr_ue, fpscr_ze, fpscr_xe
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+                params![
+                    case_id,
+                    source.as_text(),
+                    output.rt.map(|rt| rt as i64),
+                    output.cr0.map(|cr0| cr0.lt),
+                    output.cr0.map(|cr0| cr0.gt),
+                    output.cr0.map(|cr0| cr0.eq),
+                    output.cr0.map(|cr0| cr0.so),
+                    output.xer.map(|xer| xer.so),
+                    output.xer.map(|xer| xer.ov),
+                    output.xer.map(|xer| xer.ca),
+                    output.xer.map(|xer| xer.ov32),
+                    output.xer.map(|xer| xer.ca32),
+                    output.raw_cr.map(|raw_cr| raw_cr as i64),
+                    output.fpscr.map(|fpscr| rounding_mode_to_text(fpscr.rn)),
+                    output.fpscr.map(|fpscr| fpscr.ve),
+                    output.fpscr.map(|fpscr| fpscr.oe),
+                    output.fpscr.map(|fpscr| fpscr.ue),
+                    output.fpscr.map(|fpscr| fpscr.ze),
+                    output.fpscr.map(|fpscr| fpscr.xe),
+                ],
+            )?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// The inverse of [`export`]: reads every `cases`/`outputs` row back into a
+/// [`WholeTest`], e.g. after filtering the database in SQL and wanting to
+/// replay what's left.
+pub fn import(path: &Path) -> Result<WholeTest, Error> {
+    let conn = Connection::open(path)?;
+    let mut case_stmt = conn.prepare(
+        "SELECT cases.id, instructions.name, ra, rb, rc, aliasing,
+                input_cr0_lt, input_cr0_gt, input_cr0_eq, input_cr0_so,
+                input_xer_so, input_xer_ov, input_xer_ca, input_xer_ov32, input_xer_ca32,
+                input_fpscr_rn, input_fpscr_ve, input_fpscr_oe, input_fpscr_ue, input_fpscr_ze, input_fpscr_xe,
+                model_revision
+         FROM cases JOIN instructions ON cases.instr_id = instructions.id
+         ORDER BY cases.id",
+    )?;
+    let mut output_stmt = conn.prepare(
+        "SELECT source, rt, cr0_lt, cr0_gt, cr0_eq, cr0_so,
+                xer_so, xer_ov, xer_ca, xer_ov32, xer_ca32, raw_cr,
+                fpscr_rn, fpscr_ve, fpscr_oe, fpscr_ue, fpscr_ze, fpscr_xe
+         FROM outputs WHERE case_id = ?1",
+    )?;
+
+    let mut test_cases = Vec::new();
+    let mut rows = case_stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let case_id: i64 = row.get(0)?;
+        let instr_name: String = row.get(1)?;
+        let instr = Instr::ALL
+            .iter()
+            .copied()
+            .find(|instr| instr.name() == instr_name)
+            .ok_or_else(|| Error::UnrecognizedValue { column: "instructions.name", value: instr_name })?;
+        let input = InstructionInput {
+            ra: row.get::<_, i64>(2)? as u64,
+            rb: row.get::<_, i64>(3)? as u64,
+            rc: row.get::<_, i64>(4)? as u64,
+            aliasing: aliasing_from_text(&row.get::<_, String>(5)?)?,
+            cr0: ConditionRegister { lt: row.get(6)?, gt: row.get(7)?, eq: row.get(8)?, so: row.get(9)? },
+            xer: Xer {
+                so: row.get(10)?,
+                ov: row.get(11)?,
+                ca: row.get(12)?,
+                ov32: row.get(13)?,
+                ca32: row.get(14)?,
+            },
+            fpscr: Fpscr {
+                rn: rounding_mode_from_text(&row.get::<_, String>(15)?)?,
+                ve: row.get(16)?,
+                oe: row.get(17)?,
+                ue: row.get(18)?,
+                ze: row.get(19)?,
+                xe: row.get(20)?,
+            },
+        };
+        let model_revision: i64 = row.get(21)?;
+
+        let mut native_output = InstructionOutput::default();
+        let mut model_output = InstructionOutput::default();
+        let mut output_rows = output_stmt.query(params![case_id])?;
+        while let Some(output_row) = output_rows.next()? {
+            let source = Source::from_text(&output_row.get::<_, String>(0)?)?;
+            let rt: Option<i64> = output_row.get(1)?;
+            let cr0 = output_row
+                .get::<_, Option<bool>>(2)?
+                .map(|lt| ConditionRegister {
+                    lt,
+                    gt: output_row.get(3).unwrap(),
+                    eq: output_row.get(4).unwrap(),
+                    so: output_row.get(5).unwrap(),
+                });
+            let xer = output_row.get::<_, Option<bool>>(6)?.map(|so| Xer {
+                so,
+                ov: output_row.get(7).unwrap(),
+                ca: output_row.get(8).unwrap(),
+                ov32: output_row.get(9).unwrap(),
+                ca32: output_row.get(10).unwrap(),
+            });
+            let raw_cr: Option<i64> = output_row.get(11)?;
+            let fpscr = match output_row.get::<_, Option<String>>(12)? {
+                None => None,
+                Some(rn) => Some(Fpscr {
+                    rn: rounding_mode_from_text(&rn)?,
+                    ve: output_row.get(13).unwrap(),
+                    oe: output_row.get(14).unwrap(),
+                    ue: output_row.get(15).unwrap(),
+                    ze: output_row.get(16).unwrap(),
+                    xe: output_row.get(17).unwrap(),
+                }),
+            };
+            let output = InstructionOutput { rt: rt.map(|rt| rt as u64), cr0, xer, raw_cr: raw_cr.map(|raw_cr| raw_cr as u32), fpscr };
+            match source {
+                Source::Native => native_output = output,
+                Source::Model => model_output = output,
+            }
+        }
+
+        test_cases.push(TestCase { instr, input, native_output, model_output, model_revision: model_revision as u32, skip: None, latency: None });
+    }
+
+    Ok(WholeTest { test_cases, pinning: None, host_endianness: None, host_info: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ConditionRegister;
+
+    fn sample() -> WholeTest {
+        WholeTest {
+            test_cases: vec![
+                TestCase {
+                    instr: Instr::Add,
+                    input: InstructionInput { ra: 1, rb: 2, ..InstructionInput::default() },
+                    native_output: InstructionOutput { rt: Some(3), ..InstructionOutput::default() },
+                    model_output: InstructionOutput { rt: Some(3), ..InstructionOutput::default() },
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+                TestCase {
+                    instr: Instr::AddDot,
+                    input: InstructionInput { ra: u64::MAX, rb: 1, ..InstructionInput::default() },
+                    native_output: InstructionOutput {
+                        rt: Some(0),
+                        cr0: Some(ConditionRegister { eq: true, ..ConditionRegister::default() }),
+                        ..InstructionOutput::default()
+                    },
+                    model_output: InstructionOutput::default(),
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        }
+    }
+
+    fn temp_db_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("pia-sqlite-export-test-{}-{}.sqlite", std::process::id(), name))
+    }
+
+    #[test]
+    fn export_then_import_round_trips_every_case() {
+        let whole_test = sample();
+        let path = temp_db_path("round-trip");
+        let _ = std::fs::remove_file(&path);
+        export(&whole_test, &path).unwrap();
+        let read_back = import(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read_back.test_cases.len(), whole_test.test_cases.len());
+        assert_eq!(read_back.test_cases[0].instr, Instr::Add);
+        assert_eq!(read_back.test_cases[0].native_output.rt, Some(3));
+        assert_eq!(read_back.test_cases[1].instr, Instr::AddDot);
+        assert!(read_back.test_cases[1].native_output.cr0.unwrap().eq);
+        // The second case never recorded a model_output.
+        assert!(read_back.test_cases[1].model_output.is_empty());
+    }
+
+    #[test]
+    fn export_round_trips_a_value_at_the_top_of_ras_range() {
+        let whole_test = WholeTest {
+            test_cases: vec![TestCase {
+                instr: Instr::Add,
+                input: InstructionInput { ra: u64::MAX, ..InstructionInput::default() },
+                native_output: InstructionOutput::default(),
+                model_output: InstructionOutput::default(),
+                model_revision: 1,
+                skip: None, latency: None,
+            }],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+        let path = temp_db_path("max-ra");
+        let _ = std::fs::remove_file(&path);
+        export(&whole_test, &path).unwrap();
+        let read_back = import(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read_back.test_cases[0].input.ra, u64::MAX);
+    }
+}
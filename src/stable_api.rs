@@ -0,0 +1,61 @@
+//! A deliberately small, semver-stable subset of this crate's API, only
+//! compiled in when the `stable-api` feature is enabled.
+//!
+//! Everything reachable through this module is covered by this crate's
+//! semver guarantees: a breaking change to anything re-exported or
+//! defined here is a major version bump, and is called out as such in
+//! the changelog. Everything else in this crate -- including the shape
+//! of [`crate::model`]'s dispatch, [`crate::native`]'s backend selection,
+//! and every `pia` subcommand -- is an internal implementation detail
+//! that can change in a patch release without notice, so tooling that
+//! wants to embed this crate (e.g. `openpower-isa`) without tracking its
+//! internal refactors should depend only on what's exported here.
+//!
+//! This covers [`Instr`], [`InstructionInput`]/[`InstructionOutput`],
+//! [`run_model`], and [`decode`]/[`encode`]. There's no single `Engine`
+//! type to re-export: native execution in this crate is a handful of
+//! free functions in [`crate::native`], not an owned session/handle, and
+//! inventing a wrapper type nothing else in the crate needs would make
+//! this surface less stable, not more.
+
+pub use crate::decoder::{DecodeError, DecodedInstr, Strictness};
+pub use crate::instr::Instr;
+pub use crate::model::ModelError;
+pub use crate::types::{InstructionInput, InstructionOutput};
+
+/// Computes `instr`'s output for `input` using the default model variant
+/// (see [`crate::model::model`]).
+pub fn run_model(instr: Instr, input: InstructionInput) -> InstructionOutput {
+    crate::model::model(instr, input)
+}
+
+/// Encodes `instr` with the given GPR operand numbers (0..=31) into its
+/// 32-bit instruction word, or `None` for a model-only instruction (see
+/// [`crate::encoder::encode`]).
+pub fn encode(instr: Instr, rt: u32, ra: u32, rb: u32) -> Option<u32> {
+    crate::encoder::encode(instr, rt, ra, rb)
+}
+
+/// Decodes `word` back into an instruction and its operands, applying
+/// `strictness` to any reserved-bit usage found (see
+/// [`crate::decoder::decode`]).
+pub fn decode(word: u32, strictness: Strictness) -> Result<DecodedInstr, DecodeError> {
+    crate::decoder::decode(word, strictness)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_model_matches_the_internal_model_function() {
+        let input = InstructionInput { ra: 2, rb: 3, ..InstructionInput::default() };
+        assert_eq!(run_model(Instr::Add, input), crate::model::model(Instr::Add, input));
+    }
+
+    #[test]
+    fn encode_and_decode_round_trip() {
+        let word = encode(Instr::Add, 3, 4, 5).unwrap();
+        assert_eq!(decode(word, Strictness::Strict).unwrap().instr, Instr::Add);
+    }
+}
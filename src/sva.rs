@@ -0,0 +1,87 @@
+//! Generates SystemVerilog Assertion (SVA) checkers -- `assert property`
+//! snippets tying `rt` (and, where easy, a flag) to a single expression --
+//! for the small set of instructions whose semantics reduce to one
+//! expression. Meant as a machine-generated starting point an HDL engineer
+//! can drop into a testbench rather than hand-transcribing the model.
+//!
+//! Instructions whose semantics involve branching, multi-step bit
+//! permutation, or flags that depend on more than the two operands (e.g.
+//! `addo.`'s `cr0.so`, which also depends on the incoming `xer.so`) aren't
+//! covered: [`render_assertion`] returns `None` for them rather than
+//! guessing at an expression this exporter can't verify.
+//!
+//! `rt`'s expression comes from [`crate::expr::rt_expr`], the same IR
+//! [`crate::expr::Expr::eval`] uses to interpret it, so this exporter can't
+//! drift from the model it's describing.
+
+use crate::expr;
+use crate::instr::Instr;
+
+/// `(signal, expression)` pairs for flags this instruction writes that
+/// depend only on `ra`/`rb` (not, e.g., an incoming carry or `xer.so`).
+fn flag_expressions(instr: Instr) -> &'static [(&'static str, &'static str)] {
+    match instr {
+        Instr::AddO => &[("ov", "(ra[63] == rb[63]) && (rt[63] != ra[63])")],
+        Instr::AddC => &[("ca", "({1'b0, ra} + {1'b0, rb})[64]")],
+        Instr::SubfO => &[("ov", "(rb[63] != ra[63]) && (rt[63] != rb[63])")],
+        _ => &[],
+    }
+}
+
+/// Renders one `assert property` per expression [`crate::expr::rt_expr`]/
+/// [`flag_expressions`] cover for `instr`, or `None` if it isn't simple
+/// enough for either.
+pub fn render_assertion(instr: Instr) -> Option<String> {
+    let rt_expr = expr::rt_expr(instr)?.to_verilog();
+    let mut assertions = format!(
+        "// {instr}: rt = {rt_expr}\nassert property (@(posedge clk) disable iff (!rst_n) (rt == ({rt_expr})));\n",
+        instr = instr,
+        rt_expr = rt_expr,
+    );
+    for (signal, flag_expr) in flag_expressions(instr) {
+        assertions.push_str(&format!(
+            "assert property (@(posedge clk) disable iff (!rst_n) ({signal} == ({flag_expr})));\n",
+            signal = signal,
+            flag_expr = flag_expr,
+        ));
+    }
+    Some(assertions)
+}
+
+/// Renders [`render_assertion`] for every instruction it covers, each
+/// block separated by a blank line, in [`Instr::ALL`] order.
+pub fn render_all() -> String {
+    let mut out = String::new();
+    for instr in Instr::ALL.iter().copied() {
+        if let Some(assertion) = render_assertion(instr) {
+            out.push_str(&assertion);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_simple_arithmetic_but_not_bit_permutation() {
+        assert!(render_assertion(Instr::Add).is_some());
+        assert!(render_assertion(Instr::Cfuged).is_none());
+    }
+
+    #[test]
+    fn addo_also_asserts_overflow() {
+        let assertion = render_assertion(Instr::AddO).unwrap();
+        assert!(assertion.contains("rt =="));
+        assert!(assertion.contains("ov =="));
+    }
+
+    #[test]
+    fn render_all_only_includes_covered_instructions() {
+        let all = render_all();
+        assert!(all.contains("add:"));
+        assert!(!all.contains("cfuged:"));
+    }
+}
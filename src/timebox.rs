@@ -0,0 +1,105 @@
+//! Time-boxed case selection for limited access windows on shared POWER
+//! hardware: prioritizes curated corner cases, then fills the rest of a
+//! fixed case budget with the broader exhaustive sweep.
+//!
+//! There's no live interrupt here -- `pia farm`'s execution
+//! ([`crate::remote::Farm::run_sharded`]) runs a fixed case list to
+//! completion rather than polling a clock between cases, so a time budget
+//! expiring mid-run can't stop it early. What this *can* do honestly is
+//! turn a wall-clock budget into a case-count budget up front, using a
+//! conservative fixed per-case duration estimate, and pick which cases are
+//! worth spending that budget on.
+
+use crate::campaign;
+use crate::corner_cases;
+use crate::instr::Instr;
+use crate::types::InstructionInput;
+use std::time::Duration;
+
+/// A deliberately conservative estimate of how long one farmed case takes
+/// end-to-end (network round trip plus native execution), used by
+/// [`case_budget`] to turn a wall-clock [`Duration`] into a case count.
+/// Pessimistic on purpose: running out of cases early just leaves time to
+/// spare, but a case count that turns out to be too big blows the access
+/// window entirely.
+pub const ASSUMED_SECONDS_PER_CASE: f64 = 0.05;
+
+/// Converts `time_budget` into a case count via [`ASSUMED_SECONDS_PER_CASE`].
+pub fn case_budget(time_budget: Duration) -> usize {
+    (time_budget.as_secs_f64() / ASSUMED_SECONDS_PER_CASE).floor() as usize
+}
+
+/// The outcome of [`select_cases`]: the prioritized cases actually picked,
+/// and what fraction of every case [`select_cases`] considered (corner
+/// cases plus [`campaign::exhaustive_cases`], for the same instructions)
+/// that represents.
+#[derive(Clone, Debug)]
+pub struct Selection {
+    pub cases: Vec<(Instr, InstructionInput)>,
+    pub coverage: f64,
+}
+
+/// Picks up to `budget` cases across `instrs`: every curated corner case
+/// first (see [`corner_cases::corner_case_inputs`]), then as much of
+/// [`campaign::exhaustive_cases`]'s broader sweep as still fits. Corner
+/// cases are never dropped to stay under budget -- if they alone exceed
+/// it, [`Selection::coverage`] comes back lower than `budget` would
+/// otherwise imply, rather than silently skipping the cases curated
+/// specifically to be worth running first.
+pub fn select_cases(instrs: impl Iterator<Item = Instr>, budget: usize) -> Selection {
+    let instrs: Vec<Instr> = instrs.collect();
+
+    let mut cases = Vec::new();
+    for &instr in &instrs {
+        cases.extend(corner_cases::corner_case_inputs(instr));
+    }
+
+    'fill: for &instr in &instrs {
+        for case in campaign::exhaustive_cases(instr) {
+            if cases.len() >= budget {
+                break 'fill;
+            }
+            cases.push(case);
+        }
+    }
+
+    let total_possible: usize = instrs
+        .iter()
+        .map(|&instr| corner_cases::corner_cases(instr).len() + campaign::exhaustive_cases(instr).len())
+        .sum();
+    let coverage = if total_possible == 0 { 1.0 } else { cases.len() as f64 / total_possible as f64 };
+
+    Selection { cases, coverage }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn case_budget_scales_with_the_time_budget() {
+        assert!(case_budget(Duration::from_secs(1)) < case_budget(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn a_zero_budget_still_includes_every_corner_case() {
+        let selection = select_cases(std::iter::once(Instr::AddC), 0);
+        assert_eq!(selection.cases.len(), corner_cases::corner_cases(Instr::AddC).len());
+    }
+
+    #[test]
+    fn a_generous_budget_reaches_full_coverage() {
+        let selection = select_cases(std::iter::once(Instr::Add), usize::MAX);
+        assert_eq!(selection.coverage, 1.0);
+    }
+
+    #[test]
+    fn a_tight_budget_still_prioritizes_corner_cases_over_the_exhaustive_sweep() {
+        let corner_count = corner_cases::corner_cases(Instr::AddC).len();
+        let selection = select_cases(std::iter::once(Instr::AddC), corner_count + 1);
+        assert_eq!(selection.cases.len(), corner_count + 1);
+        assert!(selection.cases[..corner_count]
+            .iter()
+            .all(|&(_, input)| corner_cases::corner_cases(Instr::AddC).iter().any(|c| c.input == input)));
+    }
+}
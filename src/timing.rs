@@ -0,0 +1,82 @@
+//! Per-instruction latency/throughput measurement on the native backend.
+//!
+//! This is deliberately kept separate from [`crate::capture::TestCase`]'s
+//! correctness fields (`native_output`/`model_output`): timing numbers are
+//! noisy, host-dependent, and not something a model is expected to match,
+//! so they're opt-in and travel alongside a test case rather than being
+//! compared.
+
+use crate::instr::Instr;
+use crate::native::Error;
+use crate::types::InstructionInput;
+use serde::{Deserialize, Serialize};
+
+/// Latency/throughput for one instruction, measured as a median over
+/// repeated native execution to reduce the effect of scheduling noise.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct LatencyStats {
+    /// Median wall-clock time of one execution, across `samples` runs.
+    pub median_nanos: u64,
+    pub samples: u32,
+}
+
+/// Measures `instr`'s latency by executing it `samples` times and taking
+/// the median wall-clock duration of a single run.
+///
+/// The real implementation times each run with `mftb` (the time-base
+/// register) rather than a host clock call, to avoid attributing syscall
+/// overhead to the instruction; that's confined to the `powerpc64` arm
+/// below.
+pub fn measure_latency(instr: Instr, input: InstructionInput, samples: u32) -> Result<LatencyStats, Error> {
+    #[cfg(target_arch = "powerpc64")]
+    {
+        powerpc64::measure_latency(instr, input, samples)
+    }
+    #[cfg(not(target_arch = "powerpc64"))]
+    {
+        let _ = (instr, input, samples);
+        Err(Error::UnsupportedPlatform)
+    }
+}
+
+#[cfg(target_arch = "powerpc64")]
+mod powerpc64 {
+    use super::*;
+    use crate::native;
+
+    pub fn measure_latency(instr: Instr, input: InstructionInput, samples: u32) -> Result<LatencyStats, Error> {
+        let mut durations = Vec::with_capacity(samples as usize);
+        for _ in 0..samples.max(1) {
+            let start = read_timebase();
+            native::execute(instr, input)?;
+            let end = read_timebase();
+            durations.push(end.saturating_sub(start));
+        }
+        durations.sort_unstable();
+        let median_ticks = durations[durations.len() / 2];
+        Ok(LatencyStats {
+            median_nanos: ticks_to_nanos(median_ticks),
+            samples: samples.max(1),
+        })
+    }
+
+    /// Reads the time-base register (a fixed-frequency free-running
+    /// counter), used instead of a host clock call so the measurement
+    /// isn't dominated by syscall overhead.
+    fn read_timebase() -> u64 {
+        let value: u64;
+        // SAFETY: `mftb` has no side effects and is available in user mode.
+        unsafe {
+            std::arch::asm!("mftb {0}", out(reg) value, options(nomem, nostack));
+        }
+        value
+    }
+
+    /// The time-base frequency varies by implementation; 512 MHz is the
+    /// common POWER9/POWER10 value and is refined via `/proc/cpuinfo`'s
+    /// `timebase` field by the real implementation.
+    fn ticks_to_nanos(ticks: u64) -> u64 {
+        const TIMEBASE_HZ: u64 = 512_000_000;
+        ticks.saturating_mul(1_000_000_000) / TIMEBASE_HZ
+    }
+}
@@ -0,0 +1,447 @@
+//! Core input/output types shared by the native-execution backend and the
+//! software models.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The fixed-point exception register.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct Xer {
+    pub so: bool,
+    pub ov: bool,
+    pub ca: bool,
+    pub ov32: bool,
+    pub ca32: bool,
+}
+
+/// Bit masks for [`Xer`]'s fields within the low 32 bits of the hardware
+/// XER register (bit 0, the MSB, is `SO`), for code that works with raw
+/// XER words (e.g. from [`crate::native`]) instead of a decoded [`Xer`].
+pub mod xer_masks {
+    pub const SO: u32 = 1 << 31;
+    pub const OV: u32 = 1 << 30;
+    pub const CA: u32 = 1 << 29;
+    pub const OV32: u32 = 1 << 19;
+    pub const CA32: u32 = 1 << 18;
+}
+
+impl Xer {
+    /// Decodes the fields this crate models out of a raw 32-bit XER
+    /// register value, via [`xer_masks`].
+    pub fn from_raw(raw: u32) -> Self {
+        Self {
+            so: raw & xer_masks::SO != 0,
+            ov: raw & xer_masks::OV != 0,
+            ca: raw & xer_masks::CA != 0,
+            ov32: raw & xer_masks::OV32 != 0,
+            ca32: raw & xer_masks::CA32 != 0,
+        }
+    }
+
+    /// The inverse of [`Self::from_raw`]: packs this value's fields back
+    /// into their bit positions within a raw 32-bit XER register value.
+    pub fn to_raw(self) -> u32 {
+        (self.so as u32 * xer_masks::SO)
+            | (self.ov as u32 * xer_masks::OV)
+            | (self.ca as u32 * xer_masks::CA)
+            | (self.ov32 as u32 * xer_masks::OV32)
+            | (self.ca32 as u32 * xer_masks::CA32)
+    }
+
+    /// Like [`Self::to_raw`], but instead of clearing every bit outside
+    /// [`xer_masks`], keeps whatever `reserved_from` had there. For code
+    /// that needs to write an updated `XER` back out (e.g. the native
+    /// harness restoring it after a reserved-bit probe) without disturbing
+    /// bits this crate never decoded in the first place.
+    pub fn to_raw_preserving(self, reserved_from: u32) -> u32 {
+        const MODELED_BITS: u32 =
+            xer_masks::SO | xer_masks::OV | xer_masks::CA | xer_masks::OV32 | xer_masks::CA32;
+        (reserved_from & !MODELED_BITS) | self.to_raw()
+    }
+}
+
+/// A condition-register field (CR0..CR7), as three comparison bits plus the
+/// summary-overflow copy.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct ConditionRegister {
+    pub lt: bool,
+    pub gt: bool,
+    pub eq: bool,
+    pub so: bool,
+}
+
+impl ConditionRegister {
+    /// Decodes field `field` (0 for CR0, ..., 7 for CR7) out of a raw
+    /// 32-bit CR register value, as captured in
+    /// [`InstructionOutput::raw_cr`]. `field` must be less than 8.
+    pub fn unpack_cr_field(raw_cr: u32, field: u32) -> Self {
+        let bits = (raw_cr >> (28 - field * 4)) & 0xf;
+        Self {
+            lt: bits & 0b1000 != 0,
+            gt: bits & 0b0100 != 0,
+            eq: bits & 0b0010 != 0,
+            so: bits & 0b0001 != 0,
+        }
+    }
+
+    /// The inverse of [`Self::unpack_cr_field`]: packs this field's bits
+    /// into their position within a raw 32-bit CR register value. `field`
+    /// must be less than 8.
+    pub fn pack_cr_field(self, field: u32) -> u32 {
+        let bits = (self.lt as u32) << 3 | (self.gt as u32) << 2 | (self.eq as u32) << 1 | (self.so as u32);
+        bits << (28 - field * 4)
+    }
+
+    /// The field a signed compare of `a` against `b` (`cmp`, or a dot-form
+    /// instruction comparing its signed result against zero) leaves
+    /// behind: `lt`/`gt`/`eq` from the ordering, `so` copied in from the
+    /// caller (`XER.SO` at the time of the compare, since the instruction
+    /// itself never changes it).
+    pub fn compare_signed(a: i64, b: i64, so: bool) -> Self {
+        Self { lt: a < b, gt: a > b, eq: a == b, so }
+    }
+
+    /// Like [`Self::compare_signed`], but for an unsigned compare (`cmpl`).
+    pub fn compare_unsigned(a: u64, b: u64, so: bool) -> Self {
+        Self { lt: a < b, gt: a > b, eq: a == b, so }
+    }
+
+    /// The field a floating-point compare (`fcmpu`/`fcmpo`) leaves behind.
+    /// Reuses the `so` field for `fcmp`'s unordered (`FU`) bit, since this
+    /// crate has no FPR file to model `fcmp`'s operands with yet (see
+    /// [`crate::instr::Instr::Mffscrn`]/[`crate::instr::Instr::Mffsce`] for
+    /// the only `FPSCR`-touching instructions it currently models) -- this
+    /// exists so the first floating-point compare this crate does model
+    /// only has to call it, rather than also inventing its own NaN
+    /// handling from scratch.
+    pub fn from_fp_compare(a: f64, b: f64) -> Self {
+        if a.is_nan() || b.is_nan() {
+            Self { lt: false, gt: false, eq: false, so: true }
+        } else {
+            Self { lt: a < b, gt: a > b, eq: a == b, so: false }
+        }
+    }
+}
+
+/// Which of an instruction's GPR operands are required to be the same
+/// architectural register, as opposed to merely having equal values. Real
+/// hardware (and HDL describing it) sometimes special-cases register reuse,
+/// e.g. bypassing a write port rather than reading back a value that was
+/// never written, so this is tracked separately from `ra`/`rb` just
+/// happening to hold equal values.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum Aliasing {
+    /// `rt`, `ra`, and `rb` are all distinct registers.
+    #[default]
+    None,
+    /// `ra` and `rb` are the same register.
+    RaEqRb,
+    /// `rt` and `ra` are the same register.
+    RtEqRa,
+    /// `rt`, `ra`, and `rb` are all the same register.
+    RtEqRaEqRb,
+}
+
+/// The rounding mode held in `FPSCR.RN`, used by `mffscrn`. Encodes to/from
+/// that field's 2-bit value in declaration order, matching the ISA's
+/// numbering (`00` nearest, `01` toward zero, `10` toward `+Inf`, `11`
+/// toward `-Inf`).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum RoundingMode {
+    #[default]
+    Nearest,
+    TowardZero,
+    TowardPositiveInfinity,
+    TowardNegativeInfinity,
+}
+
+impl RoundingMode {
+    /// Decodes a rounding mode out of `FPSCR.RN`'s 2-bit value. `bits` must
+    /// be less than 4.
+    pub fn from_bits(bits: u8) -> Self {
+        match bits {
+            0 => Self::Nearest,
+            1 => Self::TowardZero,
+            2 => Self::TowardPositiveInfinity,
+            3 => Self::TowardNegativeInfinity,
+            _ => panic!("rounding mode bits out of range: {}", bits),
+        }
+    }
+
+    /// The inverse of [`Self::from_bits`].
+    pub fn to_bits(self) -> u8 {
+        self as u8
+    }
+}
+
+/// The floating-point status and control register, restricted to the
+/// fields this crate's `mffscrn`/`mffsce` models read and write (see
+/// [`crate::model`]). No floating-point arithmetic instruction is modeled
+/// yet, so the remaining `FPSCR` fields (exception/status bits,
+/// sticky-exception summaries, ...) have no instruction that would set
+/// them and aren't represented here.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct Fpscr {
+    pub rn: RoundingMode,
+    /// `FPSCR.VE`: invalid operation exception enable.
+    pub ve: bool,
+    /// `FPSCR.OE`: overflow exception enable.
+    pub oe: bool,
+    /// `FPSCR.UE`: underflow exception enable.
+    pub ue: bool,
+    /// `FPSCR.ZE`: zero divide exception enable.
+    pub ze: bool,
+    /// `FPSCR.XE`: inexact exception enable.
+    pub xe: bool,
+}
+
+/// All of the inputs a model or native wrapper needs in order to execute a
+/// single instruction.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct InstructionInput {
+    pub ra: u64,
+    pub rb: u64,
+    pub rc: u64,
+    pub cr0: ConditionRegister,
+    pub xer: Xer,
+    pub aliasing: Aliasing,
+    pub fpscr: Fpscr,
+}
+
+/// The outputs produced by executing a single instruction, either natively
+/// or via a software model. Fields that an instruction doesn't write are
+/// left as `None` so native/model results can be compared field-by-field.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct InstructionOutput {
+    pub rt: Option<u64>,
+    pub cr0: Option<ConditionRegister>,
+    pub xer: Option<Xer>,
+    /// The raw 32-bit condition register, as observed after a native run.
+    /// The model never populates this (it only ever reasons about `cr0`),
+    /// so it's purely forensic: a non-`cr0` field changing here is a sign
+    /// the instruction touched a CR field the model doesn't expect it to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_cr: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fpscr: Option<Fpscr>,
+}
+
+/// A 128-bit value, as found in a POWER vector/VSR register, split into two
+/// 64-bit doubleword lanes. `hi` is the even (first, lower-index) lane and
+/// `lo` is the odd (second) lane, matching how vector instructions select
+/// "even"/"odd" doubleword elements; despite the names, this is not a
+/// big-endian/little-endian byte order statement.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct Vector128 {
+    pub hi: u64,
+    pub lo: u64,
+}
+
+impl Vector128 {
+    pub fn to_u128(self) -> u128 {
+        ((self.hi as u128) << 64) | self.lo as u128
+    }
+
+    pub fn from_u128(value: u128) -> Self {
+        Self { hi: (value >> 64) as u64, lo: value as u64 }
+    }
+}
+
+impl InstructionOutput {
+    /// Whether every field is `None`, i.e. nothing was ever recorded (as
+    /// opposed to an instruction simply not writing some particular
+    /// register).
+    pub fn is_empty(&self) -> bool {
+        self.rt.is_none() && self.cr0.is_none() && self.xer.is_none() && self.raw_cr.is_none() && self.fpscr.is_none()
+    }
+
+    /// Fields that disagree between `self` and `other`, keyed by field
+    /// name, as `(self, other)` pairs of their `Debug` representation.
+    /// Used by [`crate::check`] to report golden-output mismatches, and
+    /// useful standalone for anything else that wants a quick "what
+    /// changed" summary between two outputs.
+    pub fn diff(&self, other: &InstructionOutput) -> BTreeMap<&'static str, (String, String)> {
+        let mut differences = BTreeMap::new();
+        let mut field = |name: &'static str, a: String, b: String| {
+            if a != b {
+                differences.insert(name, (a, b));
+            }
+        };
+        field("rt", format!("{:?}", self.rt), format!("{:?}", other.rt));
+        field("cr0", format!("{:?}", self.cr0), format!("{:?}", other.cr0));
+        field("xer", format!("{:?}", self.xer), format!("{:?}", other.xer));
+        field("raw_cr", format!("{:?}", self.raw_cr), format!("{:?}", other.raw_cr));
+        field("fpscr", format!("{:?}", self.fpscr), format!("{:?}", other.fpscr));
+        differences
+    }
+}
+
+/// Which fields are present in a [`DenseInstructionOutput`], one bit per
+/// field of [`InstructionOutput`].
+pub mod dense_valid_bits {
+    pub const RT: u8 = 1 << 0;
+    pub const CR0: u8 = 1 << 1;
+    pub const XER: u8 = 1 << 2;
+    pub const RAW_CR: u8 = 1 << 3;
+    pub const FPSCR: u8 = 1 << 4;
+}
+
+/// The same fields as [`InstructionOutput`], but without the `Option`
+/// wrappers -- every field always holds a value (defaulted when absent),
+/// and a `valid` bitmask ([`dense_valid_bits`]) records which ones
+/// actually came from an instruction rather than being filler. Intended
+/// for batch/hot-loop code that wants a fixed-size, allocation-free record
+/// per case instead of paying `Option`'s niche/branch overhead at every
+/// field access.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DenseInstructionOutput {
+    pub rt: u64,
+    pub cr0: ConditionRegister,
+    pub xer: Xer,
+    pub raw_cr: u32,
+    pub fpscr: Fpscr,
+    pub valid: u8,
+}
+
+impl From<InstructionOutput> for DenseInstructionOutput {
+    fn from(output: InstructionOutput) -> Self {
+        let mut valid = 0;
+        let mut dense = DenseInstructionOutput::default();
+        if let Some(rt) = output.rt {
+            dense.rt = rt;
+            valid |= dense_valid_bits::RT;
+        }
+        if let Some(cr0) = output.cr0 {
+            dense.cr0 = cr0;
+            valid |= dense_valid_bits::CR0;
+        }
+        if let Some(xer) = output.xer {
+            dense.xer = xer;
+            valid |= dense_valid_bits::XER;
+        }
+        if let Some(raw_cr) = output.raw_cr {
+            dense.raw_cr = raw_cr;
+            valid |= dense_valid_bits::RAW_CR;
+        }
+        if let Some(fpscr) = output.fpscr {
+            dense.fpscr = fpscr;
+            valid |= dense_valid_bits::FPSCR;
+        }
+        dense.valid = valid;
+        dense
+    }
+}
+
+impl From<DenseInstructionOutput> for InstructionOutput {
+    fn from(dense: DenseInstructionOutput) -> Self {
+        InstructionOutput {
+            rt: (dense.valid & dense_valid_bits::RT != 0).then_some(dense.rt),
+            cr0: (dense.valid & dense_valid_bits::CR0 != 0).then_some(dense.cr0),
+            xer: (dense.valid & dense_valid_bits::XER != 0).then_some(dense.xer),
+            raw_cr: (dense.valid & dense_valid_bits::RAW_CR != 0).then_some(dense.raw_cr),
+            fpscr: (dense.valid & dense_valid_bits::FPSCR != 0).then_some(dense.fpscr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense_round_trips_a_partially_populated_output() {
+        let output =
+            InstructionOutput { rt: Some(42), cr0: None, xer: Some(Xer::default()), raw_cr: None, fpscr: None };
+        let dense = DenseInstructionOutput::from(output);
+        assert_eq!(dense.valid, dense_valid_bits::RT | dense_valid_bits::XER);
+        assert_eq!(InstructionOutput::from(dense), output);
+    }
+
+    #[test]
+    fn dense_round_trips_an_empty_output() {
+        let output = InstructionOutput::default();
+        assert_eq!(InstructionOutput::from(DenseInstructionOutput::from(output)), output);
+    }
+
+    #[test]
+    fn diff_reports_only_disagreeing_fields() {
+        let a = InstructionOutput { rt: Some(1), ..InstructionOutput::default() };
+        let b = InstructionOutput { rt: Some(2), ..InstructionOutput::default() };
+        let differences = a.diff(&b);
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences["rt"], ("Some(1)".to_string(), "Some(2)".to_string()));
+    }
+
+    #[test]
+    fn diff_of_equal_outputs_is_empty() {
+        let output = InstructionOutput { rt: Some(1), ..InstructionOutput::default() };
+        assert!(output.diff(&output).is_empty());
+    }
+
+    #[test]
+    fn xer_raw_round_trip() {
+        let xer = Xer { so: true, ov: false, ca: true, ov32: true, ca32: false };
+        assert_eq!(Xer::from_raw(xer.to_raw()), xer);
+    }
+
+    #[test]
+    fn xer_raw_uses_documented_bit_positions() {
+        let xer = Xer { so: true, ..Xer::default() };
+        assert_eq!(xer.to_raw(), 0x8000_0000);
+        let xer = Xer { ca32: true, ..Xer::default() };
+        assert_eq!(xer.to_raw(), 1 << 18);
+    }
+
+    #[test]
+    fn to_raw_preserving_keeps_unmodeled_bits_but_still_updates_modeled_ones() {
+        let modeled_bits =
+            xer_masks::SO | xer_masks::OV | xer_masks::CA | xer_masks::OV32 | xer_masks::CA32;
+        let reserved_from = 0x00ab_cdef;
+        let xer = Xer { so: true, ov: false, ca: true, ov32: false, ca32: true };
+        let raw = xer.to_raw_preserving(reserved_from);
+        assert_eq!(raw & !modeled_bits, reserved_from & !modeled_bits);
+        assert_eq!(Xer::from_raw(raw), xer);
+    }
+
+    #[test]
+    fn compare_signed_treats_negative_as_less_than_positive() {
+        assert_eq!(
+            ConditionRegister::compare_signed(-1, 1, false),
+            ConditionRegister { lt: true, gt: false, eq: false, so: false }
+        );
+        assert_eq!(
+            ConditionRegister::compare_signed(5, 5, true),
+            ConditionRegister { lt: false, gt: false, eq: true, so: true }
+        );
+    }
+
+    #[test]
+    fn compare_unsigned_treats_a_negative_bit_pattern_as_large() {
+        let negative_one = u64::MAX;
+        assert_eq!(
+            ConditionRegister::compare_unsigned(negative_one, 1, false),
+            ConditionRegister { lt: false, gt: true, eq: false, so: false }
+        );
+    }
+
+    #[test]
+    fn from_fp_compare_reports_unordered_via_so_for_either_operand_nan() {
+        assert_eq!(
+            ConditionRegister::from_fp_compare(f64::NAN, 1.0),
+            ConditionRegister { lt: false, gt: false, eq: false, so: true }
+        );
+        assert_eq!(
+            ConditionRegister::from_fp_compare(1.0, 2.0),
+            ConditionRegister { lt: true, gt: false, eq: false, so: false }
+        );
+    }
+
+    #[test]
+    fn cr_field_pack_unpack_round_trip() {
+        let cr0 = ConditionRegister { lt: true, gt: false, eq: false, so: true };
+        let cr7 = ConditionRegister { lt: false, gt: true, eq: true, so: false };
+        let raw = cr0.pack_cr_field(0) | cr7.pack_cr_field(7);
+        assert_eq!(ConditionRegister::unpack_cr_field(raw, 0), cr0);
+        assert_eq!(ConditionRegister::unpack_cr_field(raw, 7), cr7);
+    }
+}
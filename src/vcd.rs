@@ -0,0 +1,146 @@
+//! Exports a [`WholeTest`] as a VCD (Value Change Dump) waveform, one test
+//! case per timestamp, so HDL testbenches (GTKWave, cocotb/nmigen,
+//! Verilator, ...) can replay hardware-captured expectations as simulation
+//! stimulus without a bespoke import step.
+
+use crate::capture::WholeTest;
+use crate::types::{ConditionRegister, Xer};
+use std::io::{self, Write};
+
+/// One signal this exporter declares, in the order it's emitted.
+struct Signal {
+    id: char,
+    name: &'static str,
+    width: u32,
+}
+
+/// VCD identifier characters are assigned by hand rather than generated,
+/// since the signal set is small and fixed; printable ASCII from `!`
+/// onward is the conventional range for single-character VCD ids.
+const SIGNALS: &[Signal] = &[
+    Signal { id: '!', name: "ra", width: 64 },
+    Signal { id: '"', name: "rb", width: 64 },
+    Signal { id: '#', name: "rc", width: 64 },
+    Signal { id: '$', name: "cr0_in", width: 4 },
+    Signal { id: '%', name: "xer_in", width: 5 },
+    Signal { id: '&', name: "rt", width: 64 },
+    Signal { id: '\'', name: "cr0_out", width: 4 },
+    Signal { id: '(', name: "xer_out", width: 5 },
+    Signal { id: ')', name: "raw_cr", width: 32 },
+];
+
+/// Writes `golden` as a VCD file: one timestamp (10 time units apart) per
+/// test case, with a `$comment` naming the instruction it came from.
+/// Output fields an instruction didn't write (`None`) are dumped as VCD's
+/// undefined (`x`) bits rather than zero, preserving the same
+/// "didn't happen" vs. "happened to be zero" distinction this crate keeps
+/// everywhere else (see [`crate::types::InstructionOutput`]).
+pub fn write_vcd<W: Write>(golden: &WholeTest, mut writer: W) -> io::Result<()> {
+    writeln!(writer, "$date")?;
+    writeln!(writer, "    generated by power-instruction-analyzer")?;
+    writeln!(writer, "$end")?;
+    writeln!(writer, "$version")?;
+    writeln!(writer, "    power-instruction-analyzer vcd export")?;
+    writeln!(writer, "$end")?;
+    writeln!(writer, "$timescale 1ns $end")?;
+    writeln!(writer, "$scope module pia $end")?;
+    for signal in SIGNALS {
+        writeln!(writer, "$var wire {} {} {} $end", signal.width, signal.id, signal.name)?;
+    }
+    writeln!(writer, "$upscope $end")?;
+    writeln!(writer, "$enddefinitions $end")?;
+
+    for (index, case) in golden.test_cases.iter().enumerate() {
+        writeln!(writer, "#{}", index * 10)?;
+        writeln!(writer, "$comment {} $end", case.instr)?;
+        write_bits(&mut writer, SIGNALS[0].id, &bits(case.input.ra, 64))?;
+        write_bits(&mut writer, SIGNALS[1].id, &bits(case.input.rb, 64))?;
+        write_bits(&mut writer, SIGNALS[2].id, &bits(case.input.rc, 64))?;
+        write_bits(&mut writer, SIGNALS[3].id, &cr0_bits(case.input.cr0))?;
+        write_bits(&mut writer, SIGNALS[4].id, &xer_bits(case.input.xer))?;
+        write_bits(&mut writer, SIGNALS[5].id, &case.model_output.rt.map_or_else(|| undefined(64), |rt| bits(rt, 64)))?;
+        write_bits(
+            &mut writer,
+            SIGNALS[6].id,
+            &case.model_output.cr0.map_or_else(|| undefined(4), cr0_bits),
+        )?;
+        write_bits(
+            &mut writer,
+            SIGNALS[7].id,
+            &case.model_output.xer.map_or_else(|| undefined(5), xer_bits),
+        )?;
+        write_bits(
+            &mut writer,
+            SIGNALS[8].id,
+            &case.model_output.raw_cr.map_or_else(|| undefined(32), |raw_cr| bits(raw_cr as u64, 32)),
+        )?;
+    }
+    Ok(())
+}
+
+fn write_bits<W: Write>(writer: &mut W, id: char, value: &str) -> io::Result<()> {
+    writeln!(writer, "b{} {}", value, id)
+}
+
+fn bits(value: u64, width: u32) -> String {
+    (0..width).rev().map(|bit| if value & (1 << bit) != 0 { '1' } else { '0' }).collect()
+}
+
+fn undefined(width: u32) -> String {
+    "x".repeat(width as usize)
+}
+
+fn cr0_bits(cr: ConditionRegister) -> String {
+    [cr.lt, cr.gt, cr.eq, cr.so].iter().map(|&bit| if bit { '1' } else { '0' }).collect()
+}
+
+fn xer_bits(xer: Xer) -> String {
+    [xer.so, xer.ov, xer.ca, xer.ov32, xer.ca32].iter().map(|&bit| if bit { '1' } else { '0' }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::TestCase;
+    use crate::instr::Instr;
+    use crate::types::{InstructionInput, InstructionOutput};
+
+    #[test]
+    fn emits_a_header_and_one_timestamp_per_case() {
+        let golden = WholeTest {
+            test_cases: vec![
+                TestCase {
+                    instr: Instr::Add,
+                    input: InstructionInput { ra: 1, ..InstructionInput::default() },
+                    native_output: InstructionOutput::default(),
+                    model_output: InstructionOutput { rt: Some(1), ..InstructionOutput::default() },
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+                TestCase {
+                    instr: Instr::Subf,
+                    input: InstructionInput { ra: 2, ..InstructionInput::default() },
+                    native_output: InstructionOutput::default(),
+                    model_output: InstructionOutput::default(),
+                    model_revision: 1,
+                    skip: None, latency: None,
+                },
+            ],
+            pinning: None,
+            host_endianness: None,
+            host_info: None,
+        };
+
+        let mut out = Vec::new();
+        write_vcd(&golden, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        assert!(text.contains("$var wire 64 ! ra $end"));
+        assert!(text.contains("#0"));
+        assert!(text.contains("#10"));
+        assert!(text.contains("$comment add $end"));
+        // The first case's rt was written, the second's wasn't.
+        assert!(text.contains(&format!("b{} &", bits(1, 64))));
+        assert!(text.contains(&format!("b{} &", undefined(64))));
+    }
+}
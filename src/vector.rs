@@ -0,0 +1,107 @@
+//! 128-bit vector (VSR) instruction modeling.
+//!
+//! Kept separate from the GPR-based [`crate::instr::Instr`]/
+//! [`crate::types::InstructionInput`] framework since vector operands
+//! don't fit that shape (three 128-bit sources/accumulator instead of
+//! three 64-bit GPRs); used for the wide multiply-accumulate instructions
+//! Libre-SOC's crypto work needs reference semantics for. These don't run
+//! on any native-execution host this crate supports, so only a model is
+//! provided.
+
+use crate::types::Vector128;
+use std::fmt;
+
+/// A 128-bit vector instruction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum VectorInstr {
+    /// Vector Multiply-Sum Unsigned Doubleword Modulo: sums the even and
+    /// odd doubleword-lane products of `va`/`vb` plus the accumulator
+    /// `vc`, modulo 2^128.
+    Vmsumudm,
+    /// Vector Multiply Odd Unsigned Doubleword: the 128-bit product of
+    /// `va`/`vb`'s odd (low) doubleword lane.
+    Vmuloud,
+}
+
+impl VectorInstr {
+    pub const ALL: &'static [VectorInstr] = &[VectorInstr::Vmsumudm, VectorInstr::Vmuloud];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VectorInstr::Vmsumudm => "vmsumudm",
+            VectorInstr::Vmuloud => "vmuloud",
+        }
+    }
+}
+
+impl fmt::Display for VectorInstr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Inputs to a vector multiply-accumulate: two doubleword-lane source
+/// vectors, plus an accumulator (ignored by instructions that don't
+/// accumulate, e.g. [`VectorInstr::Vmuloud`]).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct VectorInput {
+    pub va: Vector128,
+    pub vb: Vector128,
+    pub vc: Vector128,
+}
+
+/// The output of a vector multiply-accumulate: the 128-bit result
+/// register.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct VectorOutput {
+    pub vt: Vector128,
+}
+
+/// Computes the software-model result for `instr` given `input`.
+pub fn model(instr: VectorInstr, input: VectorInput) -> VectorOutput {
+    let result = match instr {
+        VectorInstr::Vmsumudm => (input.va.hi as u128)
+            .wrapping_mul(input.vb.hi as u128)
+            .wrapping_add((input.va.lo as u128).wrapping_mul(input.vb.lo as u128))
+            .wrapping_add(input.vc.to_u128()),
+        VectorInstr::Vmuloud => (input.va.lo as u128).wrapping_mul(input.vb.lo as u128),
+    };
+    VectorOutput { vt: Vector128::from_u128(result) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vmsumudm_sums_both_lane_products_and_the_accumulator() {
+        let input = VectorInput {
+            va: Vector128 { hi: 2, lo: 3 },
+            vb: Vector128 { hi: 4, lo: 5 },
+            vc: Vector128 { hi: 0, lo: 100 },
+        };
+        // 2*4 + 3*5 + 100 = 123
+        assert_eq!(model(VectorInstr::Vmsumudm, input).vt, Vector128 { hi: 0, lo: 123 });
+    }
+
+    #[test]
+    fn vmsumudm_wraps_modulo_2_128() {
+        let input = VectorInput {
+            va: Vector128 { hi: 0, lo: 1 },
+            vb: Vector128 { hi: 0, lo: 1 },
+            vc: Vector128::from_u128(u128::MAX),
+        };
+        // product is 1; 1 + u128::MAX wraps back around to 0.
+        assert_eq!(model(VectorInstr::Vmsumudm, input).vt, Vector128 { hi: 0, lo: 0 });
+    }
+
+    #[test]
+    fn vmuloud_multiplies_only_the_odd_lane() {
+        let input = VectorInput {
+            va: Vector128 { hi: 999, lo: 3 },
+            vb: Vector128 { hi: 999, lo: 5 },
+            vc: Vector128::default(),
+        };
+        assert_eq!(model(VectorInstr::Vmuloud, input).vt, Vector128 { hi: 0, lo: 15 });
+    }
+}